@@ -0,0 +1,1874 @@
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use bluer::{self, AdapterEvent as BlueZAdapterEvent, AdapterProperty, Address, Uuid};
+use futures::{lock::Mutex, Stream};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::{
+    sync::{broadcast, oneshot},
+    task::JoinHandle,
+};
+use tokio_stream::{
+    wrappers::{errors::BroadcastStreamRecvError, BroadcastStream},
+    StreamExt,
+};
+
+use super::{
+    backend::{AdapterBackend, BackendStream, DeviceBackend, IncomingConnections},
+    capture::{Capture, CaptureFormat, CaptureInfo},
+    class_of_device::ClassOfDevice,
+    device::{DeviceInfo, DevicePatch},
+    device_list::{DeviceDelta, DeviceList, DeviceSnapshot},
+    device_query::{DevicePage, DeviceQuery},
+    device_watcher::DeviceWatcher,
+    error::{BluetoothError, Result},
+    inventory::{Inventory, InventoryFormat},
+    media::{MediaCommand, MediaPlayerEvent, MediaPlayerInfo},
+    network::{NetworkEvent, NetworkInfo, NetworkServer, PanRole},
+    pairing::PairingRequest,
+    profiles::Profile,
+    rfkill::{RfkillSource, RfkillState},
+    terminal::{
+        ChannelId, ChannelInfo, ChannelKind, L2capServer, SerialServer, TerminalEvent,
+        TerminalOptions, Terminals,
+    },
+    transport::{MediaTransportEvent, TransportInfo, MAX_VOLUME},
+    uuids::{self, UuidInfo},
+};
+
+/// Failures raised by this crate itself rather than by BlueZ
+#[derive(Error, Debug)]
+pub enum AdapterErrors {
+    #[error("No adapters found in the system")]
+    AdapterNotFound,
+    #[error("Target adapter {0} is not powered on")]
+    AdapterNotPoweredOn(String),
+    #[error("Target adapter {0} is discovering")]
+    AdapterIsDiscovering(String),
+    #[error("Device is not found")]
+    DeviceNotFound,
+    #[error("Target adapter {0} is soft blocked through rfkill")]
+    AdapterSoftBlocked(String),
+    #[error("Target adapter {0} is hard blocked through rfkill, check the wireless switch")]
+    AdapterHardBlocked(String),
+}
+
+// Events buffered per subscriber before the slowest one starts lagging
+const EVENT_CHANNEL_CAPACITY: usize = 256;
+
+/// Change of a single device in the device list
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DeviceEvent {
+    DeviceAdded {
+        device: DeviceInfo,
+    },
+    DeviceRemoved {
+        device: DeviceInfo,
+    },
+    /// Only the patch is serialized, the full device is there for in-process consumers
+    DeviceUpdated {
+        #[serde(skip)]
+        device: DeviceInfo,
+        patch: DevicePatch,
+    },
+}
+/// Events published to every [`Adapter::subscribe`] stream
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum AdapterEvent {
+    AdapterPropertyChanged(AdapterInfo),
+    DeviceChanged(DeviceDelta),
+    /// Timeout of the discovery in seconds
+    DiscoveryStarted(u64),
+    DiscoveryStopped(DiscoveryStopReason),
+    /// Request of the pairing agent, see [`Adapter::register_agent`]
+    PairingRequested(PairingRequest),
+    /// Subscriber fell behind and missed this many events, it should resync its state
+    Lagged(u64),
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiscoveryStopReason {
+    Timeout,
+    Cancelled,
+    /// BlueZ closed the discovery session on its own, e.g. the adapter was powered off
+    Ended,
+}
+
+// How long to wait for BlueZ to report that discovery stopped once the session is dropped
+const DISCOVERY_TEARDOWN_TIMEOUT: Duration = Duration::from_secs(2);
+
+static NEXT_DISCOVERY_ID: AtomicU64 = AtomicU64::new(0);
+static NEXT_PAIRING_REQUEST_ID: AtomicU64 = AtomicU64::new(0);
+
+// Media and network state of a device, merged from the player, transport and network
+// streams of the backend
+enum DeviceStateUpdate {
+    Player(Address, Option<MediaPlayerInfo>),
+    Transport(Address, Option<TransportInfo>),
+    Network(Address, Option<NetworkInfo>),
+}
+
+#[derive(Debug)]
+struct DiscoverySession {
+    id: u64,
+    cancel_tx: oneshot::Sender<()>,
+    task: JoinHandle<()>,
+}
+
+#[derive(Debug)]
+struct ServerRegistration<S> {
+    server: S,
+    task: JoinHandle<()>,
+}
+
+/// Representation of a bluetooth adapter, cheap to clone and shared between clones
+#[derive(Debug, Clone)]
+pub struct Adapter {
+    name: String,
+    adapter_handle: Arc<dyn AdapterBackend>,
+    rfkill: Arc<dyn RfkillSource>,
+    adapter_info: Arc<Mutex<AdapterInfo>>,
+    discovery: Arc<Mutex<Option<DiscoverySession>>>,
+    known_devices: Arc<Mutex<DeviceList>>,
+    watcher: DeviceWatcher,
+    event_tx: broadcast::Sender<AdapterEvent>,
+    terminals: Terminals,
+    serial_servers: Arc<Mutex<HashMap<Uuid, ServerRegistration<SerialServer>>>>,
+    l2cap_servers: Arc<Mutex<HashMap<u16, ServerRegistration<L2capServer>>>>,
+    network_servers: Arc<Mutex<HashMap<PanRole, NetworkServer>>>,
+    capture: Arc<Mutex<Option<Capture>>>,
+    agent: Arc<Mutex<Option<JoinHandle<()>>>>,
+    // answers of the agent requests that wait for one, by request id
+    pairing_requests: Arc<Mutex<HashMap<u64, oneshot::Sender<bool>>>>,
+}
+
+impl Adapter {
+    /// Loads the adapter state and its known devices and starts following their changes and
+    /// the rfkill blocks of `rfkill`
+    pub async fn new(
+        adapter: Arc<dyn AdapterBackend>,
+        rfkill: Arc<dyn RfkillSource>,
+    ) -> Result<Adapter> {
+        let properties = adapter.all_properties().await?;
+        let name = adapter.name().to_string();
+
+        let adapter_info = AdapterInfo {
+            name: name.clone(),
+            ..AdapterInfo::from_properties(properties)
+        };
+
+        let (event_tx, _) = broadcast::channel::<AdapterEvent>(EVENT_CHANNEL_CAPACITY);
+
+        let device_list = DeviceList::new(Arc::clone(&adapter)).await?;
+        let addresses = device_list.addresses();
+        let known_devices = Arc::new(Mutex::new(device_list));
+        let watcher = DeviceWatcher::spawn(
+            Arc::clone(&adapter),
+            Arc::clone(&known_devices),
+            event_tx.clone(),
+            addresses,
+        );
+
+        let adapter = Adapter {
+            name,
+            known_devices,
+            watcher,
+            adapter_handle: adapter,
+            rfkill,
+            terminals: Terminals::default(),
+            serial_servers: Arc::new(Mutex::new(HashMap::new())),
+            l2cap_servers: Arc::new(Mutex::new(HashMap::new())),
+            network_servers: Arc::new(Mutex::new(HashMap::new())),
+            capture: Arc::new(Mutex::new(None)),
+            agent: Arc::new(Mutex::new(None)),
+            pairing_requests: Arc::new(Mutex::new(HashMap::new())),
+            discovery: Arc::new(Mutex::new(None)),
+            adapter_info: Arc::new(Mutex::new(adapter_info)),
+            event_tx,
+        };
+        adapter.spawn_event_pump().await?;
+        adapter.spawn_device_state_pump().await;
+        adapter.spawn_rfkill_pump().await;
+        Ok(adapter)
+    }
+
+    // Forwards BlueZ adapter events to every subscriber for the lifetime of the adapter
+    async fn spawn_event_pump(&self) -> Result<()> {
+        let mut adapter_event_stream = self.adapter_handle.events().await?;
+        let event_tx = self.event_tx.clone();
+        let device_list_arc = Arc::clone(&self.known_devices);
+        let adapter_info_arc = Arc::clone(&self.adapter_info);
+        let watcher = self.watcher.clone();
+        let adapter_name = self.name.clone();
+        tokio::spawn(async move {
+            while let Some(event) = adapter_event_stream.next().await {
+                // sending only fails when nobody is subscribed, the state is still kept up to date
+                match event {
+                    BlueZAdapterEvent::DeviceAdded(address) => {
+                        let delta = device_list_arc.lock().await.add_device(address).await;
+                        match delta {
+                            Ok(delta) => {
+                                watcher.watch(address);
+                                let _ = event_tx.send(AdapterEvent::DeviceChanged(delta));
+                            }
+                            Err(err) => warn!("could not add device {}: {}", address, err),
+                        }
+                    }
+                    BlueZAdapterEvent::DeviceRemoved(address) => {
+                        watcher.unwatch(address);
+                        let delta = device_list_arc.lock().await.remove_device(address).await;
+                        if let Some(delta) = delta {
+                            let _ = event_tx.send(AdapterEvent::DeviceChanged(delta));
+                        }
+                    }
+                    BlueZAdapterEvent::PropertyChanged(property) => {
+                        let mut adapter_info = adapter_info_arc.lock().await;
+                        adapter_info.update_property(property);
+                        let _ = event_tx
+                            .send(AdapterEvent::AdapterPropertyChanged(adapter_info.clone()));
+                    }
+                }
+            }
+            debug!("adapter event stream of {} closed", adapter_name);
+        });
+        Ok(())
+    }
+
+    // Keeps the media players, transports and network connections of the devices up to date,
+    // as far as the backend supports them
+    async fn spawn_device_state_pump(&self) {
+        let mut state_streams: Vec<BackendStream<DeviceStateUpdate>> = vec![];
+        match self.adapter_handle.media_player_events().await {
+            Ok(events) => state_streams.push(Box::pin(events.map(
+                |MediaPlayerEvent { address, player }| DeviceStateUpdate::Player(address, player),
+            ))),
+            Err(err) => debug!("media players of {} are not followed: {}", self.name, err),
+        }
+        match self.adapter_handle.media_transport_events().await {
+            Ok(events) => state_streams.push(Box::pin(events.map(
+                |MediaTransportEvent { address, transport }| {
+                    DeviceStateUpdate::Transport(address, transport)
+                },
+            ))),
+            Err(err) => debug!(
+                "media transports of {} are not followed: {}",
+                self.name, err
+            ),
+        }
+        match self.adapter_handle.network_events().await {
+            Ok(events) => {
+                state_streams.push(Box::pin(events.map(|NetworkEvent { address, network }| {
+                    DeviceStateUpdate::Network(address, network)
+                })))
+            }
+            Err(err) => debug!(
+                "network connections of {} are not followed: {}",
+                self.name, err
+            ),
+        }
+        if state_streams.is_empty() {
+            return;
+        }
+
+        let mut state_updates = futures::stream::select_all(state_streams);
+        let event_tx = self.event_tx.clone();
+        let device_list_arc = Arc::clone(&self.known_devices);
+        tokio::spawn(async move {
+            while let Some(update) = state_updates.next().await {
+                let mut device_list = device_list_arc.lock().await;
+                let delta = match update {
+                    DeviceStateUpdate::Player(address, player) => {
+                        device_list.update_media_player(address, player)
+                    }
+                    DeviceStateUpdate::Transport(address, transport) => {
+                        device_list.update_media_transport(address, transport)
+                    }
+                    DeviceStateUpdate::Network(address, network) => {
+                        device_list.update_network(address, network)
+                    }
+                };
+                if let Some(delta) = delta {
+                    let _ = event_tx.send(AdapterEvent::DeviceChanged(delta));
+                }
+            }
+        });
+    }
+
+    // Follows the rfkill blocks, without rfkill access the adapter is reported as unblocked
+    async fn spawn_rfkill_pump(&self) {
+        let mut rfkill_events = match self.rfkill.events().await {
+            Ok(rfkill_events) => rfkill_events,
+            Err(err) => {
+                debug!("rfkill blocks of {} are not followed: {}", self.name, err);
+                return;
+            }
+        };
+        let event_tx = self.event_tx.clone();
+        let adapter_info_arc = Arc::clone(&self.adapter_info);
+        tokio::spawn(async move {
+            let mut rfkill_state = RfkillState::default();
+            while let Some(event) = rfkill_events.next().await {
+                if !rfkill_state.apply(&event) {
+                    continue;
+                }
+                let mut adapter_info = adapter_info_arc.lock().await;
+                adapter_info.soft_blocked = rfkill_state.soft_blocked();
+                adapter_info.hard_blocked = rfkill_state.hard_blocked();
+                adapter_info.settle_power_state();
+                let _ = event_tx.send(AdapterEvent::AdapterPropertyChanged(adapter_info.clone()));
+            }
+        });
+    }
+
+    /// Any number of consumers can subscribe, each one gets every event from the point it
+    /// subscribed. A consumer that falls behind receives [`AdapterEvent::Lagged`] instead of
+    /// the events it missed
+    pub fn subscribe(&self) -> impl Stream<Item = AdapterEvent> {
+        BroadcastStream::new(self.event_tx.subscribe()).map(|event| {
+            event.unwrap_or_else(|BroadcastStreamRecvError::Lagged(skipped)| {
+                AdapterEvent::Lagged(skipped)
+            })
+        })
+    }
+
+    /// Latest known state of the adapter
+    pub async fn adapter_info(&self) -> AdapterInfo {
+        Arc::clone(&self.adapter_info).lock().await.clone()
+    }
+
+    /// Powers the adapter on or off. Fails without asking bluetoothd while the adapter is
+    /// rfkill blocked, see [`Adapter::unblock`]
+    pub async fn set_powered(&self, powered: bool) -> Result<()> {
+        {
+            let mut adapter_info = self.adapter_info.lock().await;
+            if powered && adapter_info.hard_blocked {
+                return Err(AdapterErrors::AdapterHardBlocked(self.name.clone()).into());
+            }
+            if powered && adapter_info.soft_blocked {
+                return Err(AdapterErrors::AdapterSoftBlocked(self.name.clone()).into());
+            }
+            if adapter_info.is_powered != powered {
+                adapter_info.power_state = if powered {
+                    PowerState::TurningOn
+                } else {
+                    PowerState::TurningOff
+                };
+                let _ = self
+                    .event_tx
+                    .send(AdapterEvent::AdapterPropertyChanged(adapter_info.clone()));
+            }
+        }
+
+        // the Powered property change settles the transition once bluetoothd is done
+        let result = self.adapter_handle.set_powered(powered).await;
+        if result.is_err() {
+            let mut adapter_info = self.adapter_info.lock().await;
+            adapter_info.settle_power_state();
+            let _ = self
+                .event_tx
+                .send(AdapterEvent::AdapterPropertyChanged(adapter_info.clone()));
+        }
+        result
+    }
+
+    /// Lifts the rfkill soft block of bluetooth, the adapter stays off until powered.
+    /// A hard block can only be lifted by the user
+    pub async fn unblock(&self) -> Result<()> {
+        if self.adapter_info.lock().await.hard_blocked {
+            return Err(AdapterErrors::AdapterHardBlocked(self.name.clone()).into());
+        }
+        self.rfkill.set_soft_block(false).await?;
+        // report it right away so a set_powered that follows does not see the old block
+        let mut adapter_info = self.adapter_info.lock().await;
+        if adapter_info.soft_blocked {
+            adapter_info.soft_blocked = false;
+            adapter_info.settle_power_state();
+            let _ = self
+                .event_tx
+                .send(AdapterEvent::AdapterPropertyChanged(adapter_info.clone()));
+        }
+        Ok(())
+    }
+
+    pub async fn set_alias(&self, alias: String) -> Result<()> {
+        self.adapter_handle.set_alias(alias).await
+    }
+
+    pub async fn set_pairable(&self, pairable: bool) -> Result<()> {
+        self.adapter_handle.set_pairable(pairable).await
+    }
+
+    pub async fn set_discoverable(&self, discoverable: bool) -> Result<()> {
+        self.adapter_handle.set_discoverable(discoverable).await
+    }
+
+    pub async fn set_discoverable_timeout(&self, discoverable_timeout: u32) -> Result<()> {
+        self.adapter_handle
+            .set_discoverable_timeout(discoverable_timeout)
+            .await
+    }
+
+    /// Every device the backend knows about, connected devices first
+    pub async fn known_devices(&self) -> Vec<DeviceInfo> {
+        self.known_devices.lock().await.list()
+    }
+
+    /// Known devices together with the sequence number of the last [`DeviceDelta`]
+    pub async fn device_snapshot(&self) -> DeviceSnapshot {
+        self.known_devices.lock().await.snapshot()
+    }
+
+    /// Known devices matching the filter of `query`, sorted and paged
+    pub async fn query_devices(&self, query: &DeviceQuery) -> Result<DevicePage> {
+        self.known_devices.lock().await.query(query)
+    }
+
+    /// Writes the devices matching `query`, or every known device, to an inventory file in
+    /// `format` or the format of the extension of `path`. Returns the number of devices written
+    pub async fn export_devices(
+        &self,
+        path: &Path,
+        format: Option<InventoryFormat>,
+        query: Option<&DeviceQuery>,
+    ) -> Result<usize> {
+        let devices = match query {
+            Some(query) => self.query_devices(query).await?.devices,
+            None => self.known_devices().await,
+        };
+        let inventory = Inventory::new(devices);
+        inventory.write(path, format).await?;
+        Ok(inventory.devices.len())
+    }
+
+    /// Starts recording the advertisements and inquiry results of every discovery to `path`,
+    /// in `format` or the format of its extension, until [`Adapter::stop_capture`]
+    pub async fn start_capture(
+        &self,
+        path: &Path,
+        format: Option<CaptureFormat>,
+    ) -> Result<CaptureInfo> {
+        let mut capture = self.capture.lock().await;
+        if let Some(running) = capture.as_ref() {
+            return Err(BluetoothError::AlreadyExists(format!(
+                "Already capturing to {}",
+                running.info().path.display()
+            )));
+        }
+        let started = Capture::create(path, format).await?;
+        let info = started.info().clone();
+        *capture = Some(started);
+        Ok(info)
+    }
+
+    /// Stops the running capture and closes its file
+    pub async fn stop_capture(&self) -> Result<CaptureInfo> {
+        let capture = self.capture.lock().await.take();
+        match capture {
+            Some(capture) => capture.finish().await,
+            None => Err(BluetoothError::DoesNotExist(String::from("Not capturing"))),
+        }
+    }
+
+    /// The running capture, None when not capturing
+    pub async fn capture_info(&self) -> Option<CaptureInfo> {
+        self.capture
+            .lock()
+            .await
+            .as_ref()
+            .map(|capture| capture.info().clone())
+    }
+
+    /// Replaces the tags of a device, see [`DeviceList::set_tags`]. They are kept for the
+    /// lifetime of the adapter, even when the device is removed and found again
+    pub async fn set_device_tags(&self, address: Address, tags: Vec<String>) -> Result<()> {
+        let mut device_list = self.known_devices.lock().await;
+        if device_list.get(&address).is_none() {
+            return Err(AdapterErrors::DeviceNotFound.into());
+        }
+        if let Some(delta) = device_list.set_tags(address, tags) {
+            let _ = self.event_tx.send(AdapterEvent::DeviceChanged(delta));
+        }
+        Ok(())
+    }
+
+    pub async fn is_discovering(&self) -> bool {
+        self.discovery.lock().await.is_some()
+    }
+
+    /// Stops the running discovery and waits until its session has been torn down
+    pub async fn cancel_discovering(&mut self) {
+        let session = self.discovery.lock().await.take();
+        if let Some(session) = session {
+            let _ = session.cancel_tx.send(());
+            let _ = session.task.await;
+        }
+    }
+
+    /// Starts discovering for the given time, found devices are published as
+    /// [`AdapterEvent::DeviceChanged`]
+    pub async fn discover_devices(&mut self, timeout_in_seconds: u64) -> Result<()> {
+        let mut discovery_guard = self.discovery.lock().await;
+
+        if discovery_guard.is_some() {
+            return Err(AdapterErrors::AdapterIsDiscovering(self.name.clone()).into());
+        }
+
+        if !self.adapter_info.lock().await.is_powered {
+            return Err(AdapterErrors::AdapterNotPoweredOn(self.name.clone()).into());
+        }
+
+        let discovery = self.adapter_handle.discover_devices().await?;
+        let (cancel_tx, cancel_rx) = oneshot::channel();
+        let id = NEXT_DISCOVERY_ID.fetch_add(1, Ordering::Relaxed);
+
+        // subscribed before the discovery task runs, so a capture sees every device found
+        let events = self.event_tx.subscribe();
+        let _ = self
+            .event_tx
+            .send(AdapterEvent::DiscoveryStarted(timeout_in_seconds));
+        let task = tokio::spawn(run_discovery(
+            id,
+            discovery,
+            cancel_rx,
+            Duration::from_secs(timeout_in_seconds),
+            Arc::clone(&self.adapter_handle),
+            Arc::clone(&self.known_devices),
+            Arc::clone(&self.discovery),
+            Arc::clone(&self.capture),
+            events,
+            self.event_tx.clone(),
+        ));
+        *discovery_guard = Some(DiscoverySession {
+            id,
+            cancel_tx,
+            task,
+        });
+
+        Ok(())
+    }
+
+    // Backend of a device, pairing waits for the user and connecting can take a while, so the
+    // device list is not kept locked for them
+    async fn device_backend(&self, address: Address) -> Result<Arc<dyn DeviceBackend>> {
+        match self.known_devices.lock().await.get(&address) {
+            Some(device) => Ok(Arc::clone(device.backend())),
+            None => Err(AdapterErrors::DeviceNotFound.into()),
+        }
+    }
+
+    pub async fn connect_device(&self, address: Address) -> Result<()> {
+        self.device_backend(address).await?.connect().await
+    }
+
+    pub async fn pair_device(&self, address: Address) -> Result<()> {
+        self.device_backend(address).await?.pair().await
+    }
+
+    pub async fn disconnect_device(&self, address: Address) -> Result<()> {
+        self.device_backend(address).await?.disconnect().await
+    }
+
+    /// Makes the adapter the default pairing agent of the system, see [`crate::pairing`].
+    /// Its requests are published as [`AdapterEvent::PairingRequested`]
+    pub async fn register_agent(&self) -> Result<()> {
+        let mut agent = self.agent.lock().await;
+        if agent.is_some() {
+            return Err(BluetoothError::AlreadyExists(String::from(
+                "The pairing agent is registered already",
+            )));
+        }
+        let mut requests = self.adapter_handle.register_agent().await?;
+        let device_list_arc = Arc::clone(&self.known_devices);
+        let pending_arc = Arc::clone(&self.pairing_requests);
+        let event_tx = self.event_tx.clone();
+        *agent = Some(tokio::spawn(async move {
+            while let Some(request) = requests.next().await {
+                let name = device_list_arc
+                    .lock()
+                    .await
+                    .get(&request.address)
+                    .map(|device| device.device_info().display_name().to_string())
+                    .unwrap_or_default();
+                let id = NEXT_PAIRING_REQUEST_ID.fetch_add(1, Ordering::Relaxed);
+                if let Some(answer) = request.answer {
+                    let mut pending = pending_arc.lock().await;
+                    // bluetoothd stopped waiting for the ones nobody answered in time
+                    pending.retain(|_, answer| !answer.is_closed());
+                    pending.insert(id, answer);
+                }
+                let _ = event_tx.send(AdapterEvent::PairingRequested(PairingRequest {
+                    id,
+                    address: request.address.0,
+                    address_string: request.address.to_string(),
+                    name,
+                    kind: request.kind,
+                }));
+            }
+            debug!("pairing agent stopped");
+        }));
+        Ok(())
+    }
+
+    /// Hands pairing back to the agent of the desktop, unanswered requests are rejected
+    pub async fn unregister_agent(&self) -> Result<()> {
+        match self.agent.lock().await.take() {
+            Some(task) => {
+                task.abort();
+                self.pairing_requests.lock().await.clear();
+                Ok(())
+            }
+            None => Err(BluetoothError::DoesNotExist(String::from(
+                "No pairing agent is registered",
+            ))),
+        }
+    }
+
+    pub async fn is_agent_registered(&self) -> bool {
+        self.agent.lock().await.is_some()
+    }
+
+    /// Accepts or rejects a request published as [`AdapterEvent::PairingRequested`]
+    pub async fn answer_pairing_request(&self, id: u64, accept: bool) -> Result<()> {
+        let answer = self.pairing_requests.lock().await.remove(&id);
+        match answer {
+            Some(answer) if answer.send(accept).is_ok() => Ok(()),
+            _ => Err(BluetoothError::DoesNotExist(format!(
+                "Pairing request {} was answered already or timed out",
+                id
+            ))),
+        }
+    }
+
+    // Backend of a device that has `profile`, see device_backend
+    async fn profile_backend(
+        &self,
+        address: Address,
+        profile: Profile,
+    ) -> Result<Arc<dyn DeviceBackend>> {
+        match self.known_devices.lock().await.get(&address) {
+            Some(device) if !device.device_info().profiles.contains(&profile) => Err(
+                BluetoothError::NotSupported(format!("Device has no {} profile", profile)),
+            ),
+            Some(device) => Ok(Arc::clone(device.backend())),
+            None => Err(AdapterErrors::DeviceNotFound.into()),
+        }
+    }
+
+    /// Connects a single profile of a device, connecting the device itself if needed
+    pub async fn connect_profile(&self, address: Address, profile: Profile) -> Result<()> {
+        let backend = self.profile_backend(address, profile).await?;
+        backend.connect_profile(profile.uuid()).await
+    }
+
+    /// Disconnects a single profile of a device, the device stays connected
+    pub async fn disconnect_profile(&self, address: Address, profile: Profile) -> Result<()> {
+        let backend = self.profile_backend(address, profile).await?;
+        backend.disconnect_profile(profile.uuid()).await
+    }
+
+    /// Connects to the PAN service of a device, e.g. to tether through a phone's network
+    /// access point. Returns the network interface bluetoothd created
+    pub async fn connect_network(&self, address: Address, role: PanRole) -> Result<String> {
+        let backend = self.profile_backend(address, role.profile()).await?;
+        backend.connect_network(role).await
+    }
+
+    pub async fn disconnect_network(&self, address: Address) -> Result<()> {
+        let backend = match self.known_devices.lock().await.get(&address) {
+            Some(device) => match &device.device_info().network {
+                Some(network) if network.connected => Arc::clone(device.backend()),
+                _ => {
+                    return Err(BluetoothError::NotAvailable(String::from(
+                        "Device has no network connection",
+                    )))
+                }
+            },
+            None => return Err(AdapterErrors::DeviceNotFound.into()),
+        };
+        backend.disconnect_network().await
+    }
+
+    /// Shares a network with PAN clients as a network access point or group network, bridging
+    /// their connections to the bridge interface of the server
+    pub async fn listen_network(&self, server: NetworkServer) -> Result<()> {
+        if server.role == PanRole::Panu {
+            return Err(BluetoothError::InvalidArguments(String::from(
+                "The adapter can only serve as network access point or group network",
+            )));
+        }
+        if server.bridge.is_empty() {
+            return Err(BluetoothError::InvalidArguments(String::from(
+                "A bridge interface is required",
+            )));
+        }
+        let mut network_servers = self.network_servers.lock().await;
+        if network_servers.contains_key(&server.role) {
+            return Err(BluetoothError::AlreadyExists(format!(
+                "A {} server is registered already",
+                server.role.profile()
+            )));
+        }
+        self.adapter_handle
+            .register_network_server(server.role, server.bridge.clone())
+            .await?;
+        network_servers.insert(server.role, server);
+        Ok(())
+    }
+
+    pub async fn stop_network_server(&self, role: PanRole) -> Result<()> {
+        let mut network_servers = self.network_servers.lock().await;
+        if !network_servers.contains_key(&role) {
+            return Err(BluetoothError::DoesNotExist(format!(
+                "No {} server is registered",
+                role.profile()
+            )));
+        }
+        self.adapter_handle.unregister_network_server(role).await?;
+        network_servers.remove(&role);
+        Ok(())
+    }
+
+    pub async fn network_servers(&self) -> Vec<NetworkServer> {
+        let mut servers: Vec<NetworkServer> = self
+            .network_servers
+            .lock()
+            .await
+            .values()
+            .cloned()
+            .collect();
+        servers.sort_by_key(|server| server.role.as_bluez());
+        servers
+    }
+
+    /// A guess at the clients of the network servers: the connected devices that can take
+    /// the PAN user role, while a server is registered. bluetoothd does not tell which devices
+    /// use a server, and the `bnep` ports it adds to the bridge cannot be traced back to a
+    /// device, so a listed device may not be using the network at all
+    pub async fn possible_network_clients(&self) -> Vec<DeviceInfo> {
+        if self.network_servers.lock().await.is_empty() {
+            return vec![];
+        }
+        self.known_devices()
+            .await
+            .into_iter()
+            .filter(|device| device.is_connected && device.profiles.contains(&Profile::PanUser))
+            .collect()
+    }
+
+    /// Controls the AVRCP media player of a device
+    pub async fn media_command(&self, address: Address, command: MediaCommand) -> Result<()> {
+        let backend = match self.known_devices.lock().await.get(&address) {
+            Some(device) if !device.device_info().supports_media_control() => {
+                return Err(BluetoothError::NotSupported(String::from(
+                    "Device has no AVRCP profile",
+                )))
+            }
+            Some(device) => Arc::clone(device.backend()),
+            None => return Err(AdapterErrors::DeviceNotFound.into()),
+        };
+        // finding the player and calling it are two round trips, the device list stays usable
+        backend.media_command(command).await
+    }
+
+    /// Opens a serial terminal to a device, on the given RFCOMM `channel` or on the one it
+    /// advertises for the serial port profile
+    pub async fn open_serial(
+        &self,
+        address: Address,
+        channel: Option<u8>,
+        options: TerminalOptions,
+    ) -> Result<ChannelInfo> {
+        let (backend, device_info) = match self.known_devices.lock().await.get(&address) {
+            Some(device) if channel.is_none() && !device.device_info().supports_serial_port() => {
+                return Err(BluetoothError::NotSupported(String::from(
+                    "Device has no serial port profile",
+                )))
+            }
+            Some(device) => (Arc::clone(device.backend()), device.device_info().clone()),
+            None => return Err(AdapterErrors::DeviceNotFound.into()),
+        };
+        // connecting can take a while, the device list stays usable in the meantime
+        let stream = backend.open_serial(channel).await?;
+        self.terminals
+            .open(stream, device_info, ChannelKind::Serial, options, None)
+            .await
+    }
+
+    // Accepts the connections of trusted devices as terminal channels of `kind` and rejects
+    // the others, until the requests end or the returned task is aborted
+    fn serve(
+        &self,
+        mut requests: IncomingConnections,
+        kind: ChannelKind,
+        options: TerminalOptions,
+        service: String,
+    ) -> JoinHandle<()> {
+        let device_list_arc = Arc::clone(&self.known_devices);
+        let terminals = self.terminals.clone();
+        tokio::spawn(async move {
+            while let Some(request) = requests.next().await {
+                let address = request.address();
+                let device_info = device_list_arc
+                    .lock()
+                    .await
+                    .get(&address)
+                    .map(|device| device.device_info().clone());
+                match device_info {
+                    Some(device_info) if device_info.is_trusted => {
+                        let mtu = request.mtu();
+                        let opened = match request.accept() {
+                            Ok(stream) => {
+                                terminals
+                                    .open(stream, device_info, kind, options.clone(), mtu)
+                                    .await
+                            }
+                            Err(err) => Err(err),
+                        };
+                        if let Err(err) = opened {
+                            warn!("could not accept {} on {}: {}", address, service, err);
+                        }
+                    }
+                    _ => {
+                        debug!("rejected {} on {}, it is not trusted", address, service);
+                        request.reject();
+                        terminals.rejected(address, service.clone());
+                    }
+                }
+            }
+            debug!("server {} stopped", service);
+        })
+    }
+
+    /// Offers `server` to other devices. Connections from trusted devices are accepted and
+    /// open a [`ChannelKind::SerialServer`] terminal, everything else is rejected and reported
+    /// as [`TerminalEvent::Rejected`]
+    pub async fn listen_serial(&self, server: SerialServer) -> Result<()> {
+        let uuid = server.parsed_uuid()?;
+        let mut serial_servers = self.serial_servers.lock().await;
+        if serial_servers.contains_key(&uuid) {
+            return Err(BluetoothError::AlreadyExists(format!(
+                "A server for {} is registered already",
+                uuid
+            )));
+        }
+        let requests = self
+            .adapter_handle
+            .listen_serial(uuid, server.name.clone(), server.channel)
+            .await?;
+        let task = self.serve(
+            requests,
+            ChannelKind::SerialServer,
+            server.options.clone(),
+            uuid.to_string(),
+        );
+        serial_servers.insert(uuid, ServerRegistration { server, task });
+        Ok(())
+    }
+
+    /// Unregisters the server for `uuid`, channels it opened stay open
+    pub async fn stop_serial_server(&self, uuid: &str) -> Result<()> {
+        let uuid = SerialServer {
+            uuid: uuid.to_string(),
+            ..SerialServer::default()
+        }
+        .parsed_uuid()?;
+        match self.serial_servers.lock().await.remove(&uuid) {
+            Some(registration) => {
+                registration.task.abort();
+                Ok(())
+            }
+            None => Err(BluetoothError::DoesNotExist(format!(
+                "No server registered for {}",
+                uuid
+            ))),
+        }
+    }
+
+    pub async fn serial_servers(&self) -> Vec<SerialServer> {
+        let mut servers: Vec<SerialServer> = self
+            .serial_servers
+            .lock()
+            .await
+            .values()
+            .map(|registration| registration.server.clone())
+            .collect();
+        servers.sort_by(|a, b| a.name.cmp(&b.name));
+        servers
+    }
+
+    /// Opens an LE L2CAP connection-oriented channel to `psm` of a device
+    pub async fn open_l2cap(
+        &self,
+        address: Address,
+        psm: u16,
+        options: TerminalOptions,
+    ) -> Result<ChannelInfo> {
+        if psm == 0 {
+            return Err(BluetoothError::InvalidArguments(String::from(
+                "PSM must not be 0",
+            )));
+        }
+        let (backend, device_info) = match self.known_devices.lock().await.get(&address) {
+            Some(device) => (Arc::clone(device.backend()), device.device_info().clone()),
+            None => return Err(AdapterErrors::DeviceNotFound.into()),
+        };
+        let (stream, mtu) = backend.open_l2cap(psm).await?;
+        self.terminals
+            .open(stream, device_info, ChannelKind::L2cap, options, Some(mtu))
+            .await
+    }
+
+    /// Listens for LE L2CAP connection-oriented channels on the PSM of `server`, with the same
+    /// trust rules as [`Adapter::listen_serial`]. Returns the server with the PSM listened on
+    pub async fn listen_l2cap(&self, server: L2capServer) -> Result<L2capServer> {
+        let mut l2cap_servers = self.l2cap_servers.lock().await;
+        if l2cap_servers.contains_key(&server.psm) {
+            return Err(BluetoothError::AlreadyExists(format!(
+                "A server for PSM {} is registered already",
+                server.psm
+            )));
+        }
+        let (psm, requests) = self.adapter_handle.listen_l2cap(server.psm).await?;
+        let server = L2capServer { psm, ..server };
+        let task = self.serve(
+            requests,
+            ChannelKind::L2capServer,
+            server.options.clone(),
+            format!("PSM {}", psm),
+        );
+        l2cap_servers.insert(
+            psm,
+            ServerRegistration {
+                server: server.clone(),
+                task,
+            },
+        );
+        Ok(server)
+    }
+
+    /// Stops listening on `psm`, channels the server opened stay open
+    pub async fn stop_l2cap_server(&self, psm: u16) -> Result<()> {
+        match self.l2cap_servers.lock().await.remove(&psm) {
+            Some(registration) => {
+                registration.task.abort();
+                Ok(())
+            }
+            None => Err(BluetoothError::DoesNotExist(format!(
+                "No server listening on PSM {}",
+                psm
+            ))),
+        }
+    }
+
+    pub async fn l2cap_servers(&self) -> Vec<L2capServer> {
+        let mut servers: Vec<L2capServer> = self
+            .l2cap_servers
+            .lock()
+            .await
+            .values()
+            .map(|registration| registration.server.clone())
+            .collect();
+        servers.sort_by_key(|server| server.psm);
+        servers
+    }
+
+    /// Sends `data` over an open terminal channel, returns the number of bytes sent
+    pub async fn terminal_send(&self, id: ChannelId, data: &str) -> Result<usize> {
+        self.terminals.send(id, data).await
+    }
+
+    pub async fn close_terminal(&self, id: ChannelId) -> Result<()> {
+        self.terminals.close(id).await
+    }
+
+    pub async fn terminal_channels(&self) -> Vec<ChannelInfo> {
+        self.terminals.channels().await
+    }
+
+    /// Open terminal channels of a device, with their MTU and throughput
+    pub async fn device_channels(&self, address: Address) -> Result<Vec<ChannelInfo>> {
+        if self.known_devices.lock().await.get(&address).is_none() {
+            return Err(AdapterErrors::DeviceNotFound.into());
+        }
+        Ok(self.terminals.device_channels(address).await)
+    }
+
+    /// Data and state changes of the terminal channels, kept apart from [`Adapter::subscribe`]
+    /// so a chatty device does not push device updates out of the buffer
+    pub fn subscribe_terminals(&self) -> impl Stream<Item = TerminalEvent> {
+        self.terminals.subscribe()
+    }
+
+    /// Current A2DP transport of a device, None while nothing is streaming to or from it
+    pub async fn media_transport(&self, address: Address) -> Result<Option<TransportInfo>> {
+        match self.known_devices.lock().await.get(&address) {
+            Some(device) => Ok(device.device_info().transport.clone()),
+            None => Err(AdapterErrors::DeviceNotFound.into()),
+        }
+    }
+
+    /// Sets the absolute volume of a device's A2DP transport, from 0 to [`MAX_VOLUME`]
+    pub async fn set_volume(&self, address: Address, volume: u16) -> Result<()> {
+        if volume > MAX_VOLUME {
+            return Err(BluetoothError::InvalidArguments(format!(
+                "Volume must be at most {}",
+                MAX_VOLUME
+            )));
+        }
+        let backend = match self.known_devices.lock().await.get(&address) {
+            Some(device) if device.device_info().transport.is_none() => {
+                return Err(BluetoothError::NotAvailable(String::from(
+                    "Device has no media transport",
+                )))
+            }
+            Some(device) => Arc::clone(device.backend()),
+            None => return Err(AdapterErrors::DeviceNotFound.into()),
+        };
+        // the transport is looked up before it is set, the device list stays usable meanwhile
+        backend.set_volume(volume).await
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn run_discovery(
+    id: u64,
+    mut discovery: impl Stream<Item = BlueZAdapterEvent> + Unpin,
+    mut cancel_rx: oneshot::Receiver<()>,
+    timeout: Duration,
+    adapter: Arc<dyn AdapterBackend>,
+    device_list_arc: Arc<Mutex<DeviceList>>,
+    discovery_arc: Arc<Mutex<Option<DiscoverySession>>>,
+    capture_arc: Arc<Mutex<Option<Capture>>>,
+    mut events: broadcast::Receiver<AdapterEvent>,
+    tx: broadcast::Sender<AdapterEvent>,
+) {
+    let timer = tokio::time::sleep(timeout);
+    tokio::pin!(timer);
+
+    // found devices also show up on the adapter event stream, where they are added to the
+    // device list and watched, so the discovery stream only has to be kept alive here and
+    // the device changes are only followed for the capture
+    let reason = loop {
+        tokio::select! {
+            // a dropped sender means the adapter itself is gone, stop as well
+            _ = &mut cancel_rx => break DiscoveryStopReason::Cancelled,
+            _ = &mut timer => break DiscoveryStopReason::Timeout,
+            evt = discovery.next() => {
+                if evt.is_none() {
+                    break DiscoveryStopReason::Ended;
+                }
+            },
+            // found devices and their advertisements while a capture is running
+            evt = events.recv() => {
+                if let Ok(AdapterEvent::DeviceChanged(delta)) = evt {
+                    if let Some(capture) = capture_arc.lock().await.as_mut() {
+                        if let Err(err) = capture.record(&delta.event).await {
+                            warn!("could not write to the capture: {}", err);
+                        }
+                    }
+                }
+            },
+        }
+    };
+
+    // dropping the session makes bluer stop the discovery, wait for BlueZ to confirm it
+    drop(discovery);
+    let _ = tokio::time::timeout(DISCOVERY_TEARDOWN_TIMEOUT, async {
+        while let Ok(true) = adapter.is_discovering().await {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    })
+    .await;
+
+    {
+        let mut discovery_guard = discovery_arc.lock().await;
+        if discovery_guard.as_ref().map(|session| session.id) == Some(id) {
+            *discovery_guard = None;
+        }
+    }
+    // the signal strengths are not updated anymore, sorting by them would use stale values
+    for delta in device_list_arc.lock().await.clear_rssi() {
+        let _ = tx.send(AdapterEvent::DeviceChanged(delta));
+    }
+    let _ = tx.send(AdapterEvent::DiscoveryStopped(reason));
+}
+
+/// Power of an adapter including the transitions in between, reported while a
+/// [`Adapter::set_powered`] is in flight
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PowerState {
+    Off,
+    TurningOn,
+    On,
+    TurningOff,
+    /// Off and blocked through rfkill, it cannot be powered until unblocked
+    Blocked,
+}
+
+impl Default for PowerState {
+    fn default() -> Self {
+        PowerState::Off
+    }
+}
+
+/// Serializable state of an adapter
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct AdapterInfo {
+    not_found: bool, //todo use Options for adapter instead of not found
+    pub discovering: bool,
+    pub address: String,
+    pub address_type: String,
+    pub alias: String,
+    pub pairable: bool,
+    pub name: String,
+    pub is_discoverable: bool,
+    pub is_powered: bool,
+    pub power_state: PowerState,
+    pub soft_blocked: bool,
+    pub hard_blocked: bool,
+    pub system_name: String,
+    pub pairable_timeout: u32,
+    pub discoverable_timeout: u32,
+    pub class: u32,
+    /// None when the class is not a valid Class of Device, e.g. for LE only devices
+    pub class_of_device: Option<ClassOfDevice>,
+    pub icon: String,
+    pub active_advertising_instances: u8,
+    pub supported_advertising_instances: u8,
+    pub supported_advertising_system_includes: Vec<String>,
+    pub supported_advertising_features: Vec<String>,
+    pub max_advertisement_length: u8,
+    pub max_scan_response_length: u8,
+    pub min_tx_power: i16,
+    pub max_tx_power: i16,
+    pub uuids: HashMap<String, UuidInfo>,
+}
+
+impl AdapterInfo {
+    pub fn from_properties(properties: Vec<AdapterProperty>) -> Self {
+        let mut adapter_info = AdapterInfo {
+            not_found: false,
+            ..AdapterInfo::default()
+        };
+
+        for property in properties {
+            adapter_info.update_property(property);
+        }
+
+        adapter_info
+    }
+
+    // Power state once no transition is in flight
+    fn settle_power_state(&mut self) {
+        self.power_state = if self.is_powered {
+            PowerState::On
+        } else if self.soft_blocked || self.hard_blocked {
+            PowerState::Blocked
+        } else {
+            PowerState::Off
+        };
+    }
+
+    pub fn update_property(&mut self, property: AdapterProperty) {
+        match property {
+            AdapterProperty::Address(address) => self.address = address.to_string(),
+            AdapterProperty::AddressType(address_type) => {
+                self.address_type = address_type.to_string()
+            }
+            AdapterProperty::SystemName(system_name) => self.system_name = system_name,
+            AdapterProperty::Alias(alias) => self.alias = alias,
+            AdapterProperty::Class(class) => {
+                self.class = class;
+                self.class_of_device = ClassOfDevice::decode(class)
+            }
+            AdapterProperty::Powered(is_powered) => {
+                self.is_powered = is_powered;
+                self.settle_power_state()
+            }
+            AdapterProperty::Discoverable(is_discoverable) => {
+                self.is_discoverable = is_discoverable
+            }
+            AdapterProperty::Pairable(pairable) => self.pairable = pairable,
+            AdapterProperty::PairableTimeout(pairable_timeout) => {
+                self.pairable_timeout = pairable_timeout
+            }
+            AdapterProperty::DiscoverableTimeout(discoverable_timeout) => {
+                self.discoverable_timeout = discoverable_timeout
+            }
+            AdapterProperty::Discovering(scanning) => self.discovering = scanning,
+            AdapterProperty::Uuids(uuids) => self.uuids = uuids::describe(uuids),
+            AdapterProperty::ActiveAdvertisingInstances(active_advertising_instances) => {
+                self.active_advertising_instances = active_advertising_instances
+            }
+            AdapterProperty::SupportedAdvertisingInstances(supported_advertising_instances) => {
+                self.supported_advertising_instances = supported_advertising_instances
+            }
+            AdapterProperty::SupportedAdvertisingSystemIncludes(
+                supported_advertising_system_includes,
+            ) => {
+                let mut features = vec![];
+                for feature in supported_advertising_system_includes {
+                    features.push(feature.to_string())
+                }
+                self.supported_advertising_system_includes = features
+            }
+            AdapterProperty::SupportedAdvertisingSecondaryChannels(_) => (),
+            AdapterProperty::SupportedAdvertisingCapabilities(capabilities) => {
+                self.max_advertisement_length = capabilities.max_advertisement_length;
+                self.max_scan_response_length = capabilities.max_scan_response_length;
+                self.max_tx_power = capabilities.max_tx_power;
+                self.min_tx_power = capabilities.min_tx_power
+            }
+            AdapterProperty::SupportedAdvertisingFeatures(supported_advertising_features) => {
+                let mut features = vec![];
+                for feature in supported_advertising_features {
+                    features.push(feature.to_string())
+                }
+                self.supported_advertising_features = features
+            }
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use bluer::{Address, DeviceProperty, Uuid, UuidExt};
+    use futures::StreamExt;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use super::{AdapterErrors, AdapterEvent, AdapterInfo, DeviceEvent, PowerState};
+    use crate::{
+        backend::simulator::{Operation, Simulator},
+        capture::CaptureFormat,
+        device::DeviceInfo,
+        device_query::{DeviceFilter, DevicePage, DeviceQuery},
+        error::BluetoothError,
+        media::{MediaCommand, MediaPlayerInfo, PlaybackStatus},
+        network::{NetworkInfo, NetworkServer, PanRole},
+        pairing::{PairingRequest, PairingRequestKind},
+        profiles::Profile,
+        terminal::{
+            ChannelKind, L2capServer, SerialServer, TerminalEvent, TerminalOptions,
+            SERIAL_PORT_UUID,
+        },
+        transport::TransportInfo,
+        Client, Result,
+    };
+
+    const HEADSET: Address = Address([0x38, 0x18, 0x4c, 0x10, 0x20, 0x30]);
+    const SERIAL_BOARD: Address = Address([0x00, 0x1b, 0x66, 0x44, 0x55, 0x66]);
+    const PHONE: Address = Address([0xf4, 0x5c, 0x89, 0xa1, 0xb2, 0xc3]);
+
+    async fn next_device_event(
+        events: &mut (impl futures::Stream<Item = AdapterEvent> + Unpin),
+    ) -> DeviceEvent {
+        loop {
+            let event = tokio::time::timeout(Duration::from_secs(1), events.next())
+                .await
+                .expect("no event within a second")
+                .expect("event stream closed");
+            if let AdapterEvent::DeviceChanged(delta) = event {
+                return delta.event;
+            }
+        }
+    }
+
+    // the first update of `address` that is `done`
+    async fn next_device_update(
+        events: &mut (impl futures::Stream<Item = AdapterEvent> + Unpin),
+        address: Address,
+        done: impl Fn(&DeviceInfo) -> bool,
+    ) {
+        loop {
+            if let DeviceEvent::DeviceUpdated { device, .. } = next_device_event(events).await {
+                if device.address == address.0 && done(&device) {
+                    return;
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn loads_known_devices() -> Result<()> {
+        let client = Client::simulated(&Simulator::demo()).await?;
+        assert_eq!(client.adapter_names(), &vec![String::from("sim0")]);
+        assert!(client.adapter().adapter_info().await.is_powered);
+        assert_eq!(client.adapter().known_devices().await.len(), 3);
+        Ok(())
+    }
+
+    async fn next_pairing_request(
+        events: &mut (impl futures::Stream<Item = AdapterEvent> + Unpin),
+    ) -> PairingRequest {
+        loop {
+            let event = tokio::time::timeout(Duration::from_secs(1), events.next())
+                .await
+                .expect("no pairing request within a second")
+                .expect("event stream closed");
+            if let AdapterEvent::PairingRequested(request) = event {
+                return request;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn discovery_finds_nearby_devices() -> Result<()> {
+        let simulator = Simulator::demo();
+        let mut client = Client::simulated(&simulator).await?;
+        let mut events = Box::pin(client.adapter().subscribe());
+
+        client.adapter_mut().discover_devices(10).await?;
+        loop {
+            if let DeviceEvent::DeviceAdded { device } = next_device_event(&mut events).await {
+                if device.address == SERIAL_BOARD.0 {
+                    break;
+                }
+            }
+        }
+
+        client.adapter_mut().cancel_discovering().await;
+        assert!(!client.adapter().is_discovering().await);
+        assert!(!client.adapter().adapter_info().await.discovering);
+        let devices = client.adapter().known_devices().await;
+        assert_eq!(devices.len(), 5);
+        assert!(devices.iter().all(|device| device.rssi.is_none()));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn discovery_is_captured_while_recording() -> Result<()> {
+        let simulator = Simulator::demo();
+        let mut client = Client::simulated(&simulator).await?;
+        let path = std::env::temp_dir().join(format!(
+            "saiyan-blue-capture-{}.btsnoop",
+            std::process::id()
+        ));
+        let adapter = client.adapter().clone();
+        adapter.start_capture(&path, None).await?;
+        assert!(matches!(
+            adapter.start_capture(&path, None).await,
+            Err(BluetoothError::AlreadyExists(_))
+        ));
+
+        client.adapter_mut().discover_devices(10).await?;
+        // both nearby devices, one advertising over LE and one answering the inquiry
+        tokio::time::timeout(Duration::from_secs(1), async {
+            while adapter.capture_info().await.map_or(0, |info| info.records) < 2 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("nearby devices were not captured within a second");
+        client.adapter_mut().cancel_discovering().await;
+
+        let info = adapter.stop_capture().await?;
+        assert_eq!(info.format, CaptureFormat::Btsnoop);
+        assert!(adapter.capture_info().await.is_none());
+        assert!(matches!(
+            adapter.stop_capture().await,
+            Err(BluetoothError::DoesNotExist(_))
+        ));
+        let written = tokio::fs::read(&path).await?;
+        let _ = tokio::fs::remove_file(&path).await;
+        assert_eq!(&written[..8], b"btsnoop\0");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn property_changes_are_published() -> Result<()> {
+        let simulator = Simulator::demo();
+        let client = Client::simulated(&simulator).await?;
+        let mut events = Box::pin(client.adapter().subscribe());
+
+        simulator.set_device_property(HEADSET, DeviceProperty::BatteryPercentage(42));
+        match next_device_event(&mut events).await {
+            DeviceEvent::DeviceUpdated { device, patch } => {
+                assert_eq!(device.battery_percentage, 42);
+                assert_eq!(patch.changes["battery_percentage"], 42);
+            }
+            event => panic!("unexpected event {:?}", event),
+        }
+
+        simulator.remove_device(HEADSET);
+        assert!(matches!(
+            next_device_event(&mut events).await,
+            DeviceEvent::DeviceRemoved { .. }
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn injected_failures_are_returned_once() -> Result<()> {
+        let simulator = Simulator::demo();
+        let client = Client::simulated(&simulator).await?;
+
+        simulator.fail_next(Operation::Connect, || {
+            BluetoothError::ConnectionAttemptFailed(String::from("Page Timeout"))
+        });
+        assert!(matches!(
+            client.adapter().connect_device(HEADSET).await,
+            Err(BluetoothError::ConnectionAttemptFailed(_))
+        ));
+
+        let mut events = Box::pin(client.adapter().subscribe());
+        client.adapter().connect_device(HEADSET).await?;
+        match next_device_event(&mut events).await {
+            DeviceEvent::DeviceUpdated { device, .. } => assert!(device.is_connected),
+            event => panic!("unexpected event {:?}", event),
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn profiles_connect_one_at_a_time() -> Result<()> {
+        let simulator = Simulator::demo();
+        let client = Client::simulated(&simulator).await?;
+        let adapter = client.adapter();
+        let headset = adapter
+            .known_devices()
+            .await
+            .into_iter()
+            .find(|device| device.address == HEADSET.0)
+            .unwrap();
+        assert_eq!(
+            headset.profiles,
+            vec![
+                Profile::A2dpSink,
+                Profile::HfpHandsFree,
+                Profile::HspHeadset
+            ]
+        );
+
+        assert!(matches!(
+            adapter.connect_profile(HEADSET, Profile::SerialPort).await,
+            Err(BluetoothError::NotSupported(_))
+        ));
+        let mut events = Box::pin(adapter.subscribe());
+        adapter.connect_profile(HEADSET, Profile::A2dpSink).await?;
+        match next_device_event(&mut events).await {
+            DeviceEvent::DeviceUpdated { device, .. } => assert!(device.is_connected),
+            event => panic!("unexpected event {:?}", event),
+        }
+        adapter
+            .disconnect_profile(HEADSET, Profile::A2dpSink)
+            .await?;
+        Ok(())
+    }
+
+    // the device also reports being connected, in no particular order with the network
+    async fn next_network_change(
+        events: &mut (impl futures::Stream<Item = AdapterEvent> + Unpin),
+    ) -> NetworkInfo {
+        loop {
+            if let DeviceEvent::DeviceUpdated { device, patch } = next_device_event(events).await {
+                if patch.changes.contains_key("network") {
+                    return device.network.expect("network went away");
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn phones_share_their_network() -> Result<()> {
+        let client = Client::simulated(&Simulator::demo()).await?;
+        let adapter = client.adapter();
+        let mut events = Box::pin(adapter.subscribe());
+
+        assert!(matches!(
+            adapter.connect_network(HEADSET, PanRole::Nap).await,
+            Err(BluetoothError::NotSupported(_))
+        ));
+        assert_eq!(adapter.connect_network(PHONE, PanRole::Nap).await?, "bnep0");
+        let network = next_network_change(&mut events).await;
+        assert!(network.connected);
+        assert_eq!(network.interface.as_deref(), Some("bnep0"));
+        assert_eq!(network.role, Some(PanRole::Nap));
+
+        adapter.disconnect_network(PHONE).await?;
+        assert!(!next_network_change(&mut events).await.connected);
+        assert!(matches!(
+            adapter.disconnect_network(PHONE).await,
+            Err(BluetoothError::NotAvailable(_))
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn network_servers_list_connected_pan_users() -> Result<()> {
+        let client = Client::simulated(&Simulator::demo()).await?;
+        let adapter = client.adapter();
+        let nap = NetworkServer {
+            role: PanRole::Nap,
+            bridge: String::from("pan0"),
+        };
+
+        assert!(matches!(
+            adapter
+                .listen_network(NetworkServer {
+                    role: PanRole::Panu,
+                    bridge: String::from("pan0"),
+                })
+                .await,
+            Err(BluetoothError::InvalidArguments(_))
+        ));
+        adapter.listen_network(nap.clone()).await?;
+        assert!(matches!(
+            adapter.listen_network(nap.clone()).await,
+            Err(BluetoothError::AlreadyExists(_))
+        ));
+        assert_eq!(adapter.network_servers().await, vec![nap]);
+        assert!(adapter.possible_network_clients().await.is_empty());
+
+        let mut events = Box::pin(adapter.subscribe());
+        adapter.connect_device(PHONE).await?;
+        next_device_event(&mut events).await;
+        let clients = adapter.possible_network_clients().await;
+        assert_eq!(clients.len(), 1);
+        assert_eq!(clients[0].address, PHONE.0);
+
+        adapter.stop_network_server(PanRole::Nap).await?;
+        assert!(adapter.possible_network_clients().await.is_empty());
+        assert!(matches!(
+            adapter.stop_network_server(PanRole::Nap).await,
+            Err(BluetoothError::DoesNotExist(_))
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn queries_devices_by_tags() -> Result<()> {
+        let client = Client::simulated(&Simulator::demo()).await?;
+        let adapter = client.adapter();
+        let names = |page: DevicePage| -> Vec<String> {
+            page.devices
+                .into_iter()
+                .map(|device| device.alias)
+                .collect()
+        };
+
+        // connected first, then by name
+        let listed: Vec<String> = adapter
+            .known_devices()
+            .await
+            .into_iter()
+            .map(|device| device.alias)
+            .collect();
+        assert_eq!(listed, ["MX Keys", "Pixel 7", "WH-1000XM4"]);
+
+        let mut events = Box::pin(adapter.subscribe());
+        adapter
+            .set_device_tags(HEADSET, vec![String::from(" travel "), String::from("")])
+            .await?;
+        match next_device_event(&mut events).await {
+            DeviceEvent::DeviceUpdated { device, .. } => assert_eq!(device.tags, ["travel"]),
+            event => panic!("unexpected event {:?}", event),
+        }
+        let travel = DeviceQuery {
+            filter: DeviceFilter {
+                tags: vec![String::from("travel")],
+                ..DeviceFilter::default()
+            },
+            ..DeviceQuery::default()
+        };
+        assert_eq!(names(adapter.query_devices(&travel).await?), ["WH-1000XM4"]);
+
+        let trusted_audio = DeviceQuery {
+            filter: DeviceFilter {
+                trusted: Some(true),
+                uuid: Some(String::from("0x110b")),
+                ..DeviceFilter::default()
+            },
+            ..DeviceQuery::default()
+        };
+        assert_eq!(
+            names(adapter.query_devices(&trusted_audio).await?),
+            ["WH-1000XM4"]
+        );
+
+        assert!(matches!(
+            adapter.set_device_tags(SERIAL_BOARD, vec![]).await,
+            Err(BluetoothError::Adapter(AdapterErrors::DeviceNotFound))
+        ));
+        Ok(())
+    }
+
+    async fn next_adapter_info(
+        events: &mut (impl futures::Stream<Item = AdapterEvent> + Unpin),
+        until: impl Fn(&AdapterInfo) -> bool,
+    ) -> AdapterInfo {
+        loop {
+            let event = tokio::time::timeout(Duration::from_secs(1), events.next())
+                .await
+                .expect("no adapter change within a second")
+                .expect("event stream closed");
+            if let AdapterEvent::AdapterPropertyChanged(info) = event {
+                if until(&info) {
+                    return info;
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn rfkill_blocks_keep_the_adapter_off() -> Result<()> {
+        let simulator = Simulator::demo();
+        let client = Client::simulated(&simulator).await?;
+        let mut events = Box::pin(client.adapter().subscribe());
+
+        simulator.set_rfkill_block(true, false);
+        let info = next_adapter_info(&mut events, |info| {
+            info.soft_blocked && info.power_state == PowerState::Blocked
+        })
+        .await;
+        assert!(!info.is_powered && !info.hard_blocked);
+        assert!(matches!(
+            client.adapter().set_powered(true).await,
+            Err(BluetoothError::Adapter(AdapterErrors::AdapterSoftBlocked(
+                _
+            )))
+        ));
+
+        client.adapter().unblock().await?;
+        next_adapter_info(&mut events, |info| info.power_state == PowerState::Off).await;
+        client.adapter().set_powered(true).await?;
+        next_adapter_info(&mut events, |info| info.power_state == PowerState::On).await;
+
+        simulator.set_rfkill_block(false, true);
+        next_adapter_info(&mut events, |info| info.power_state == PowerState::Blocked).await;
+        assert!(matches!(
+            client.adapter().unblock().await,
+            Err(BluetoothError::Adapter(AdapterErrors::AdapterHardBlocked(
+                _
+            )))
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn serial_terminals_echo() -> Result<()> {
+        let client = Client::simulated(&Simulator::demo()).await?;
+        let adapter = client.adapter();
+        let mut events = Box::pin(adapter.subscribe_terminals());
+
+        assert!(matches!(
+            adapter
+                .open_serial(HEADSET, None, TerminalOptions::default())
+                .await,
+            Err(BluetoothError::NotSupported(_))
+        ));
+        let channel = adapter
+            .open_serial(HEADSET, Some(1), TerminalOptions::default())
+            .await?;
+        adapter.terminal_send(channel.id, "hello").await?;
+        let mut echo = String::new();
+        while echo != "hello\n" {
+            match tokio::time::timeout(Duration::from_secs(1), events.next()).await {
+                Ok(Some(TerminalEvent::Received { data, .. })) => echo.push_str(&data),
+                Ok(Some(_)) => (),
+                _ => panic!("no echo within a second"),
+            }
+        }
+        adapter.close_terminal(channel.id).await?;
+        assert!(adapter.terminal_channels().await.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn serial_servers_accept_trusted_devices_only() -> Result<()> {
+        let simulator = Simulator::demo();
+        let client = Client::simulated(&simulator).await?;
+        let adapter = client.adapter();
+        let mut events = Box::pin(adapter.subscribe_terminals());
+        let spp = Uuid::from_u16(SERIAL_PORT_UUID);
+        let server = SerialServer {
+            name: String::from("Console"),
+            ..SerialServer::default()
+        };
+
+        adapter.listen_serial(server.clone()).await?;
+        assert!(matches!(
+            adapter.listen_serial(server).await,
+            Err(BluetoothError::AlreadyExists(_))
+        ));
+
+        let mut remote = simulator.connect_to_server(HEADSET, spp)?;
+        let id = loop {
+            match tokio::time::timeout(Duration::from_secs(1), events.next()).await {
+                Ok(Some(TerminalEvent::Opened { channel, device })) => {
+                    assert_eq!(device.address, HEADSET.0);
+                    break channel.id;
+                }
+                Ok(Some(_)) => (),
+                _ => panic!("connection was not accepted"),
+            }
+        };
+        adapter.terminal_send(id, "hi").await?;
+        let mut received = [0; 3];
+        remote.read_exact(&mut received).await?;
+        assert_eq!(&received, b"hi\n");
+        remote.write_all(b"ok").await?;
+
+        let mut rejected = simulator.connect_to_server(PHONE, spp)?;
+        loop {
+            match tokio::time::timeout(Duration::from_secs(1), events.next()).await {
+                Ok(Some(TerminalEvent::Rejected { address, .. })) => {
+                    assert_eq!(address, PHONE.to_string());
+                    break;
+                }
+                Ok(Some(_)) => (),
+                _ => panic!("connection was not rejected"),
+            }
+        }
+        assert_eq!(rejected.read(&mut [0; 1]).await?, 0);
+
+        adapter.stop_serial_server("").await?;
+        assert!(adapter.serial_servers().await.is_empty());
+        assert_eq!(adapter.terminal_channels().await.len(), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn l2cap_channels_report_mtu_and_throughput() -> Result<()> {
+        let simulator = Simulator::demo();
+        let client = Client::simulated(&simulator).await?;
+        let adapter = client.adapter();
+        let mut events = Box::pin(adapter.subscribe_terminals());
+
+        let channel = adapter
+            .open_l2cap(HEADSET, 0x81, TerminalOptions::default())
+            .await?;
+        assert_eq!(channel.kind, ChannelKind::L2cap);
+        assert_eq!(channel.mtu.map(|mtu| mtu.receive), Some(512));
+        adapter.terminal_send(channel.id, "data").await?;
+        let mut echo = String::new();
+        while echo != "data\n" {
+            match tokio::time::timeout(Duration::from_secs(1), events.next()).await {
+                Ok(Some(TerminalEvent::Received { data, .. })) => echo.push_str(&data),
+                Ok(Some(_)) => (),
+                _ => panic!("no echo within a second"),
+            }
+        }
+        let channels = adapter.device_channels(HEADSET).await?;
+        assert_eq!(channels.len(), 1);
+        assert!(channels[0].send_rate > 0 && channels[0].receive_rate > 0);
+        assert!(adapter.device_channels(PHONE).await?.is_empty());
+
+        let server = adapter.listen_l2cap(L2capServer::default()).await?;
+        assert_eq!(server.psm, 0x80);
+        let _remote = simulator.connect_to_l2cap_server(HEADSET, server.psm)?;
+        loop {
+            match tokio::time::timeout(Duration::from_secs(1), events.next()).await {
+                Ok(Some(TerminalEvent::Opened { channel, .. })) => {
+                    assert_eq!(channel.kind, ChannelKind::L2capServer);
+                    assert!(channel.mtu.is_some());
+                    break;
+                }
+                Ok(Some(_)) => (),
+                _ => panic!("connection was not accepted"),
+            }
+        }
+        adapter.stop_l2cap_server(server.psm).await?;
+        assert!(adapter.l2cap_servers().await.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn agent_requests_are_published_and_answered() -> Result<()> {
+        let simulator = Simulator::demo();
+        let client = Client::simulated(&simulator).await?;
+        let adapter = client.adapter().clone();
+        assert!(simulator
+            .request_pairing(PHONE, PairingRequestKind::Authorization)
+            .is_err());
+
+        let mut events = Box::pin(adapter.subscribe());
+        adapter.register_agent().await?;
+        assert!(matches!(
+            adapter.register_agent().await,
+            Err(BluetoothError::AlreadyExists(_))
+        ));
+        let answer = simulator
+            .request_pairing(PHONE, PairingRequestKind::Confirmation { passkey: 123456 })?;
+        let request = next_pairing_request(&mut events).await;
+        assert_eq!(request.name, "Pixel 7");
+        adapter.answer_pairing_request(request.id, true).await?;
+        assert_eq!(answer.await, Ok(true));
+        assert!(matches!(
+            adapter.answer_pairing_request(request.id, true).await,
+            Err(BluetoothError::DoesNotExist(_))
+        ));
+
+        // requests left unanswered are rejected once the agent goes away
+        let answer = simulator.request_pairing(PHONE, PairingRequestKind::Authorization)?;
+        next_pairing_request(&mut events).await;
+        adapter.unregister_agent().await?;
+        assert!(answer.await.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn media_players_and_transports_follow_the_device() -> Result<()> {
+        let simulator = Simulator::demo();
+        let client = Client::simulated(&simulator).await?;
+        let adapter = client.adapter().clone();
+        let mut events = Box::pin(adapter.subscribe());
+
+        let player = MediaPlayerInfo {
+            name: String::from("Music"),
+            ..MediaPlayerInfo::default()
+        };
+        simulator.set_media_player(HEADSET, Some(player));
+        adapter.media_command(HEADSET, MediaCommand::Play).await?;
+        next_device_update(&mut events, HEADSET, |device| {
+            device.media_player.as_ref().map(|player| player.status)
+                == Some(PlaybackStatus::Playing)
+        })
+        .await;
+
+        let transport = TransportInfo {
+            volume: Some(64),
+            ..TransportInfo::default()
+        };
+        simulator.set_media_transport(HEADSET, Some(transport));
+        // the volume is only set on transports the adapter knows about
+        next_device_update(&mut events, HEADSET, |device| device.transport.is_some()).await;
+        adapter.set_volume(HEADSET, 100).await?;
+        next_device_update(&mut events, HEADSET, |device| {
+            device
+                .transport
+                .as_ref()
+                .and_then(|transport| transport.volume)
+                == Some(100)
+        })
+        .await;
+
+        simulator.set_media_player(HEADSET, None);
+        simulator.set_media_transport(HEADSET, None);
+        next_device_update(&mut events, HEADSET, |device| {
+            device.media_player.is_none() && device.transport.is_none()
+        })
+        .await;
+        assert_eq!(adapter.media_transport(HEADSET).await?, None);
+        assert!(matches!(
+            adapter.set_volume(HEADSET, 100).await,
+            Err(BluetoothError::NotAvailable(_))
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,193 @@
+//! Operations [`Adapter`](crate::adapter::Adapter) and
+//! [`DeviceList`](crate::device_list::DeviceList) need from the bluetooth stack.
+//!
+//! [`bluez`] talks to bluetoothd through bluer, [`simulator`] keeps everything in memory so
+//! the app can be demoed and tested without hardware. Both report state with bluer's
+//! property and event types so the rest of the crate does not care which one is in use.
+
+use std::{fmt::Debug, sync::Arc};
+
+use bluer::{AdapterEvent, AdapterProperty, Address, DeviceEvent, DeviceProperty, Uuid};
+use futures::{
+    future::{self, BoxFuture},
+    stream::BoxStream,
+    FutureExt,
+};
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::{
+    error::{BluetoothError, Result},
+    media::{MediaCommand, MediaPlayerEvent},
+    network::{NetworkEvent, PanRole},
+    pairing::AgentRequests,
+    terminal::ChannelMtu,
+    transport::MediaTransportEvent,
+};
+
+pub mod bluez;
+pub mod simulator;
+
+pub type BackendFuture<'a, T> = BoxFuture<'a, Result<T>>;
+pub type BackendStream<T> = BoxStream<'static, T>;
+
+/// Bidirectional byte stream of a socket to a device, see [`crate::terminal`]
+pub trait ByteStream: AsyncRead + AsyncWrite + Send + Unpin {}
+
+impl<T: AsyncRead + AsyncWrite + Send + Unpin> ByteStream for T {}
+
+pub type BoxByteStream = Box<dyn ByteStream>;
+
+/// Connection a device opened to a service the adapter offers, it has to be accepted or
+/// rejected
+pub trait IncomingConnection: Send {
+    fn address(&self) -> Address;
+
+    /// Negotiated MTU, for channels that have one
+    fn mtu(&self) -> Option<ChannelMtu> {
+        None
+    }
+
+    fn accept(self: Box<Self>) -> Result<BoxByteStream>;
+
+    fn reject(self: Box<Self>);
+}
+
+pub type IncomingConnections = BackendStream<Box<dyn IncomingConnection>>;
+
+/// A local bluetooth adapter
+pub trait AdapterBackend: Send + Sync + Debug {
+    fn name(&self) -> &str;
+
+    fn all_properties(&self) -> BackendFuture<'_, Vec<AdapterProperty>>;
+
+    fn device_addresses(&self) -> BackendFuture<'_, Vec<Address>>;
+
+    fn device(&self, address: Address) -> Result<Arc<dyn DeviceBackend>>;
+
+    /// Device additions, removals and adapter property changes, until the adapter goes away
+    fn events(&self) -> BackendFuture<'_, BackendStream<AdapterEvent>>;
+
+    /// Discovers devices for as long as the returned stream is alive
+    fn discover_devices(&self) -> BackendFuture<'_, BackendStream<AdapterEvent>>;
+
+    fn is_discovering(&self) -> BackendFuture<'_, bool>;
+
+    fn set_powered(&self, powered: bool) -> BackendFuture<'_, ()>;
+
+    fn set_alias(&self, alias: String) -> BackendFuture<'_, ()>;
+
+    fn set_pairable(&self, pairable: bool) -> BackendFuture<'_, ()>;
+
+    fn set_discoverable(&self, discoverable: bool) -> BackendFuture<'_, ()>;
+
+    fn set_discoverable_timeout(&self, discoverable_timeout: u32) -> BackendFuture<'_, ()>;
+
+    /// AVRCP media players of the adapter's devices, starting with the ones that exist already
+    fn media_player_events(&self) -> BackendFuture<'_, BackendStream<MediaPlayerEvent>> {
+        not_supported("media players")
+    }
+
+    /// A2DP media transports of the adapter's devices, starting with the ones that exist already
+    fn media_transport_events(&self) -> BackendFuture<'_, BackendStream<MediaTransportEvent>> {
+        not_supported("media transports")
+    }
+
+    /// PAN connections of the adapter's devices, starting with the ones that exist already
+    fn network_events(&self) -> BackendFuture<'_, BackendStream<NetworkEvent>> {
+        not_supported("network connections")
+    }
+
+    /// Offers a PAN service in `role`, bridging the connections of clients to `bridge`
+    fn register_network_server(&self, _role: PanRole, _bridge: String) -> BackendFuture<'_, ()> {
+        not_supported("network servers")
+    }
+
+    fn unregister_network_server(&self, _role: PanRole) -> BackendFuture<'_, ()> {
+        not_supported("network servers")
+    }
+
+    /// Offers an RFCOMM service with `uuid` for as long as the returned stream is alive
+    fn listen_serial(
+        &self,
+        _uuid: Uuid,
+        _name: String,
+        _channel: Option<u16>,
+    ) -> BackendFuture<'_, IncomingConnections> {
+        not_supported("serial servers")
+    }
+
+    /// Registers the default pairing agent for as long as the returned stream is alive, see
+    /// [`crate::pairing`]
+    fn register_agent(&self) -> BackendFuture<'_, AgentRequests> {
+        not_supported("pairing agents")
+    }
+
+    /// Listens for LE L2CAP connection-oriented channels on `psm`, or on a free dynamic PSM
+    /// when it is 0, for as long as the returned stream is alive. Returns the PSM listened on
+    fn listen_l2cap(&self, _psm: u16) -> BackendFuture<'_, (u16, IncomingConnections)> {
+        not_supported("L2CAP servers")
+    }
+}
+
+/// A remote device known to an adapter
+pub trait DeviceBackend: Send + Sync + Debug {
+    fn address(&self) -> Address;
+
+    fn adapter_name(&self) -> &str;
+
+    fn all_properties(&self) -> BackendFuture<'_, Vec<DeviceProperty>>;
+
+    /// Property changes of the device, until it is removed
+    fn events(&self) -> BackendFuture<'_, BackendStream<DeviceEvent>>;
+
+    fn connect(&self) -> BackendFuture<'_, ()>;
+
+    fn disconnect(&self) -> BackendFuture<'_, ()>;
+
+    fn pair(&self) -> BackendFuture<'_, ()>;
+
+    /// Connects the profile with the service class `uuid`, connecting the device if needed
+    fn connect_profile(&self, _uuid: Uuid) -> BackendFuture<'_, ()> {
+        not_supported("profile connections")
+    }
+
+    /// Disconnects the profile with the service class `uuid`, leaving the other profiles be
+    fn disconnect_profile(&self, _uuid: Uuid) -> BackendFuture<'_, ()> {
+        not_supported("profile connections")
+    }
+
+    fn media_command(&self, _command: MediaCommand) -> BackendFuture<'_, ()> {
+        not_supported("media players")
+    }
+
+    /// Opens an RFCOMM connection to the serial port profile of the device, on `channel` or
+    /// on the channel the device advertises for it when None
+    fn open_serial(&self, _channel: Option<u8>) -> BackendFuture<'_, BoxByteStream> {
+        not_supported("serial connections")
+    }
+
+    /// Opens an LE L2CAP connection-oriented channel to `psm` of the device
+    fn open_l2cap(&self, _psm: u16) -> BackendFuture<'_, (BoxByteStream, ChannelMtu)> {
+        not_supported("L2CAP channels")
+    }
+
+    /// Connects to the PAN service of the device in `role`, returns the network interface
+    /// that was created for it
+    fn connect_network(&self, _role: PanRole) -> BackendFuture<'_, String> {
+        not_supported("network connections")
+    }
+
+    fn disconnect_network(&self) -> BackendFuture<'_, ()> {
+        not_supported("network connections")
+    }
+
+    /// Sets the absolute volume of the device's media transport
+    fn set_volume(&self, _volume: u16) -> BackendFuture<'_, ()> {
+        not_supported("media transports")
+    }
+}
+
+fn not_supported<'a, T: Send + 'a>(feature: &str) -> BackendFuture<'a, T> {
+    let details = format!("{} are not supported by this backend", feature);
+    future::ready(Err(BluetoothError::NotSupported(details))).boxed()
+}
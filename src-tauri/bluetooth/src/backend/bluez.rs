@@ -0,0 +1,228 @@
+use std::sync::Arc;
+
+use bluer::{
+    Adapter as BlueZAdapter, AdapterEvent, AdapterProperty, Address, Device as BlueZDevice,
+    DeviceEvent, DeviceProperty, Uuid,
+};
+use futures::{FutureExt, StreamExt};
+
+use super::{
+    AdapterBackend, BackendFuture, BackendStream, BoxByteStream, DeviceBackend, IncomingConnections,
+};
+use crate::{
+    error::Result,
+    media::{MediaCommand, MediaPlayerEvent},
+    network::{NetworkEvent, PanRole},
+    pairing::AgentRequests,
+    terminal::ChannelMtu,
+    transport::MediaTransportEvent,
+};
+use bus::SystemBus;
+
+mod agent;
+mod bus;
+mod l2cap;
+mod media;
+mod network;
+mod serial;
+mod transport;
+
+/// Backend talking to bluetoothd over D-Bus
+#[derive(Debug, Clone)]
+pub struct BlueZAdapterBackend {
+    adapter: BlueZAdapter,
+    // for the BlueZ interfaces bluer does not cover
+    bus: SystemBus,
+}
+
+impl BlueZAdapterBackend {
+    pub fn new(adapter: BlueZAdapter) -> Self {
+        Self {
+            adapter,
+            bus: SystemBus::default(),
+        }
+    }
+}
+
+impl AdapterBackend for BlueZAdapterBackend {
+    fn name(&self) -> &str {
+        self.adapter.name()
+    }
+
+    fn all_properties(&self) -> BackendFuture<'_, Vec<AdapterProperty>> {
+        async move { Ok(self.adapter.all_properties().await?) }.boxed()
+    }
+
+    fn device_addresses(&self) -> BackendFuture<'_, Vec<Address>> {
+        async move { Ok(self.adapter.device_addresses().await?) }.boxed()
+    }
+
+    fn device(&self, address: Address) -> Result<Arc<dyn DeviceBackend>> {
+        Ok(Arc::new(BlueZDeviceBackend {
+            device: self.adapter.device(address)?,
+            bus: self.bus.clone(),
+        }))
+    }
+
+    fn events(&self) -> BackendFuture<'_, BackendStream<AdapterEvent>> {
+        async move { Ok(self.adapter.events().await?.boxed()) }.boxed()
+    }
+
+    fn discover_devices(&self) -> BackendFuture<'_, BackendStream<AdapterEvent>> {
+        async move { Ok(self.adapter.discover_devices().await?.boxed()) }.boxed()
+    }
+
+    fn is_discovering(&self) -> BackendFuture<'_, bool> {
+        async move { Ok(self.adapter.is_discovering().await?) }.boxed()
+    }
+
+    fn set_powered(&self, powered: bool) -> BackendFuture<'_, ()> {
+        async move { Ok(self.adapter.set_powered(powered).await?) }.boxed()
+    }
+
+    fn set_alias(&self, alias: String) -> BackendFuture<'_, ()> {
+        async move { Ok(self.adapter.set_alias(alias).await?) }.boxed()
+    }
+
+    fn set_pairable(&self, pairable: bool) -> BackendFuture<'_, ()> {
+        async move { Ok(self.adapter.set_pairable(pairable).await?) }.boxed()
+    }
+
+    fn set_discoverable(&self, discoverable: bool) -> BackendFuture<'_, ()> {
+        async move { Ok(self.adapter.set_discoverable(discoverable).await?) }.boxed()
+    }
+
+    fn set_discoverable_timeout(&self, discoverable_timeout: u32) -> BackendFuture<'_, ()> {
+        async move {
+            Ok(self
+                .adapter
+                .set_discoverable_timeout(discoverable_timeout)
+                .await?)
+        }
+        .boxed()
+    }
+
+    fn media_player_events(&self) -> BackendFuture<'_, BackendStream<MediaPlayerEvent>> {
+        media::player_events(&self.bus, self.adapter.name()).boxed()
+    }
+
+    fn media_transport_events(&self) -> BackendFuture<'_, BackendStream<MediaTransportEvent>> {
+        transport::transport_events(&self.bus, self.adapter.name()).boxed()
+    }
+
+    fn network_events(&self) -> BackendFuture<'_, BackendStream<NetworkEvent>> {
+        network::network_events(&self.bus, self.adapter.name()).boxed()
+    }
+
+    fn register_network_server(&self, role: PanRole, bridge: String) -> BackendFuture<'_, ()> {
+        network::register_network_server(&self.bus, self.adapter.name(), role, bridge).boxed()
+    }
+
+    fn unregister_network_server(&self, role: PanRole) -> BackendFuture<'_, ()> {
+        network::unregister_network_server(&self.bus, self.adapter.name(), role).boxed()
+    }
+
+    fn listen_serial(
+        &self,
+        uuid: Uuid,
+        name: String,
+        channel: Option<u16>,
+    ) -> BackendFuture<'_, IncomingConnections> {
+        serial::listen_serial(uuid, name, channel).boxed()
+    }
+
+    fn register_agent(&self) -> BackendFuture<'_, AgentRequests> {
+        agent::register_agent(self.adapter.name().to_string()).boxed()
+    }
+
+    fn listen_l2cap(&self, psm: u16) -> BackendFuture<'_, (u16, IncomingConnections)> {
+        l2cap::listen_l2cap(&self.adapter, psm).boxed()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BlueZDeviceBackend {
+    device: BlueZDevice,
+    bus: SystemBus,
+}
+
+impl DeviceBackend for BlueZDeviceBackend {
+    fn address(&self) -> Address {
+        self.device.address()
+    }
+
+    fn adapter_name(&self) -> &str {
+        self.device.adapter_name()
+    }
+
+    fn all_properties(&self) -> BackendFuture<'_, Vec<DeviceProperty>> {
+        async move { Ok(self.device.all_properties().await?) }.boxed()
+    }
+
+    fn events(&self) -> BackendFuture<'_, BackendStream<DeviceEvent>> {
+        async move { Ok(self.device.events().await?.boxed()) }.boxed()
+    }
+
+    fn connect(&self) -> BackendFuture<'_, ()> {
+        async move { Ok(self.device.connect().await?) }.boxed()
+    }
+
+    fn disconnect(&self) -> BackendFuture<'_, ()> {
+        async move { Ok(self.device.disconnect().await?) }.boxed()
+    }
+
+    fn pair(&self) -> BackendFuture<'_, ()> {
+        async move { Ok(self.device.pair().await?) }.boxed()
+    }
+
+    fn connect_profile(&self, uuid: Uuid) -> BackendFuture<'_, ()> {
+        async move { Ok(self.device.connect_profile(&uuid).await?) }.boxed()
+    }
+
+    fn disconnect_profile(&self, uuid: Uuid) -> BackendFuture<'_, ()> {
+        async move { Ok(self.device.disconnect_profile(&uuid).await?) }.boxed()
+    }
+
+    fn media_command(&self, command: MediaCommand) -> BackendFuture<'_, ()> {
+        media::media_command(
+            &self.bus,
+            self.device.adapter_name(),
+            self.device.address(),
+            command,
+        )
+        .boxed()
+    }
+
+    fn open_serial(&self, channel: Option<u8>) -> BackendFuture<'_, BoxByteStream> {
+        serial::open_serial(&self.device, channel).boxed()
+    }
+
+    fn open_l2cap(&self, psm: u16) -> BackendFuture<'_, (BoxByteStream, ChannelMtu)> {
+        l2cap::open_l2cap(&self.device, psm).boxed()
+    }
+
+    fn connect_network(&self, role: PanRole) -> BackendFuture<'_, String> {
+        network::connect_network(
+            &self.bus,
+            self.device.adapter_name(),
+            self.device.address(),
+            role,
+        )
+        .boxed()
+    }
+
+    fn disconnect_network(&self) -> BackendFuture<'_, ()> {
+        network::disconnect_network(&self.bus, self.device.adapter_name(), self.device.address())
+            .boxed()
+    }
+
+    fn set_volume(&self, volume: u16) -> BackendFuture<'_, ()> {
+        transport::set_volume(
+            &self.bus,
+            self.device.adapter_name(),
+            self.device.address(),
+            volume,
+        )
+        .boxed()
+    }
+}
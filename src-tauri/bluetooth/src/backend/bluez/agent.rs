@@ -0,0 +1,133 @@
+use bluer::{
+    agent::{
+        Agent, AuthorizeService, DisplayPasskey, DisplayPinCode, ReqError, ReqResult,
+        RequestAuthorization, RequestConfirmation,
+    },
+    Address, Session,
+};
+use futures::{future::BoxFuture, FutureExt, StreamExt};
+use tokio::sync::{mpsc, oneshot};
+use tokio_stream::wrappers::UnboundedReceiverStream;
+
+use crate::{
+    error::Result,
+    pairing::{AgentRequest, AgentRequests, PairingRequestKind},
+};
+
+// Hands the request to the adapter and waits for its answer. Requests of other adapters and
+// requests the adapter drops are rejected
+fn ask(
+    requests: &mpsc::UnboundedSender<AgentRequest>,
+    adapter_name: &str,
+    adapter: String,
+    address: Address,
+    kind: PairingRequestKind,
+) -> BoxFuture<'static, ReqResult<()>> {
+    if adapter != adapter_name {
+        return async { Err(ReqError::Rejected) }.boxed();
+    }
+    let (answer_tx, answer_rx) = oneshot::channel();
+    let sent = requests
+        .send(AgentRequest {
+            address,
+            kind,
+            answer: Some(answer_tx),
+        })
+        .is_ok();
+    async move {
+        match answer_rx.await {
+            Ok(true) if sent => Ok(()),
+            _ => Err(ReqError::Rejected),
+        }
+    }
+    .boxed()
+}
+
+// Passes on a passkey or PIN code to type on the device, bluetoothd does not wait for anything
+fn show(
+    requests: &mpsc::UnboundedSender<AgentRequest>,
+    address: Address,
+    kind: PairingRequestKind,
+) -> BoxFuture<'static, ReqResult<()>> {
+    let _ = requests.send(AgentRequest {
+        address,
+        kind,
+        answer: None,
+    });
+    async { Ok(()) }.boxed()
+}
+
+/// Registers the default agent of the session. Devices that want a PIN code or passkey typed
+/// in on this side are rejected, the app has nowhere to type it
+pub async fn register_agent(adapter_name: String) -> Result<AgentRequests> {
+    let (requests_tx, requests_rx) = mpsc::unbounded_channel();
+    let confirmations = (requests_tx.clone(), adapter_name.clone());
+    let authorizations = (requests_tx.clone(), adapter_name.clone());
+    let services = (requests_tx.clone(), adapter_name);
+    let passkeys = requests_tx.clone();
+    let pin_codes = requests_tx;
+    let agent = Agent {
+        request_default: true,
+        request_confirmation: Some(Box::new(move |request: RequestConfirmation| {
+            let (requests, adapter_name) = &confirmations;
+            ask(
+                requests,
+                adapter_name,
+                request.adapter,
+                request.device,
+                PairingRequestKind::Confirmation {
+                    passkey: request.passkey,
+                },
+            )
+        })),
+        request_authorization: Some(Box::new(move |request: RequestAuthorization| {
+            let (requests, adapter_name) = &authorizations;
+            ask(
+                requests,
+                adapter_name,
+                request.adapter,
+                request.device,
+                PairingRequestKind::Authorization,
+            )
+        })),
+        authorize_service: Some(Box::new(move |request: AuthorizeService| {
+            let (requests, adapter_name) = &services;
+            ask(
+                requests,
+                adapter_name,
+                request.adapter,
+                request.device,
+                PairingRequestKind::service(request.service),
+            )
+        })),
+        display_passkey: Some(Box::new(move |request: DisplayPasskey| {
+            show(
+                &passkeys,
+                request.device,
+                PairingRequestKind::DisplayPasskey {
+                    passkey: request.passkey,
+                },
+            )
+        })),
+        display_pin_code: Some(Box::new(move |request: DisplayPinCode| {
+            show(
+                &pin_codes,
+                request.device,
+                PairingRequestKind::DisplayPinCode {
+                    pin_code: request.pincode,
+                },
+            )
+        })),
+        ..Agent::default()
+    };
+
+    let session = Session::new().await?;
+    let handle = session.register_agent(agent).await?;
+    Ok(UnboundedReceiverStream::new(requests_rx)
+        .map(move |request| {
+            // the agent is unregistered once the handle and the session go away
+            let _registration = (&session, &handle);
+            request
+        })
+        .boxed())
+}
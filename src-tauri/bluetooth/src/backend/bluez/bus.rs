@@ -0,0 +1,257 @@
+use std::{collections::HashMap, fmt, str::FromStr, sync::Arc, time::Duration};
+
+use bluer::Address;
+use dbus::{
+    arg::PropMap,
+    message::MatchRule,
+    nonblock::{
+        stdintf::org_freedesktop_dbus::{ObjectManager, Properties},
+        Proxy, SyncConnection,
+    },
+    Message, Path,
+};
+use futures::StreamExt;
+use log::{debug, warn};
+use tokio::sync::{mpsc, OnceCell};
+use tokio_stream::wrappers::UnboundedReceiverStream;
+
+use crate::{backend::BackendStream, error::Result};
+
+pub const BLUEZ: &str = "org.bluez";
+const DBUS_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// System bus connection for the BlueZ interfaces bluer does not cover, opened on first use
+/// and shared by the adapter and all of its devices
+#[derive(Clone, Default)]
+pub struct SystemBus {
+    connection: Arc<OnceCell<Arc<SyncConnection>>>,
+}
+
+impl fmt::Debug for SystemBus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SystemBus")
+            .field("connected", &self.connection.initialized())
+            .finish()
+    }
+}
+
+impl SystemBus {
+    pub async fn connection(&self) -> Result<Arc<SyncConnection>> {
+        let connection = self
+            .connection
+            .get_or_try_init(|| async {
+                let (resource, connection) = dbus_tokio::connection::new_system_sync()?;
+                tokio::spawn(async move {
+                    let err = resource.await;
+                    warn!("lost the system bus connection: {}", err);
+                });
+                Ok::<_, dbus::Error>(connection)
+            })
+            .await?;
+        Ok(Arc::clone(connection))
+    }
+
+    pub async fn proxy(&self, path: String) -> Result<Proxy<'static, Arc<SyncConnection>>> {
+        Ok(Proxy::new(
+            BLUEZ,
+            path,
+            DBUS_TIMEOUT,
+            self.connection().await?,
+        ))
+    }
+}
+
+pub fn adapter_path(adapter_name: &str) -> String {
+    format!("/org/bluez/{}", adapter_name)
+}
+
+pub fn device_path(adapter_name: &str, address: Address) -> String {
+    format!(
+        "{}/dev_{}",
+        adapter_path(adapter_name),
+        address.to_string().replace(':', "_")
+    )
+}
+
+/// Address of the device an object path like `/org/bluez/hci0/dev_AA_BB_CC_DD_EE_FF/player0`
+/// belongs to
+pub fn address_from_path(path: &str) -> Option<Address> {
+    let device = path.split('/').find_map(|part| part.strip_prefix("dev_"))?;
+    Address::from_str(&device.replace('_', ":")).ok()
+}
+
+/// Path of the first object below `prefix` that implements `interface`
+pub async fn find_object(bus: &SystemBus, prefix: &str, interface: &str) -> Result<Option<String>> {
+    let objects = bus
+        .proxy(String::from("/"))
+        .await?
+        .get_managed_objects()
+        .await?;
+    Ok(objects
+        .into_iter()
+        .find(|(path, interfaces)| path.starts_with(prefix) && interfaces.contains_key(interface))
+        .map(|(path, _)| path.to_string()))
+}
+
+/// Objects below the adapter that implement `interface`, as the `parse`d properties of the
+/// device they belong to whenever they appear or change. When a device has several, like two
+/// players, removing one reports the properties of another and only removing the last one
+/// reports None. Starts with the objects that exist already
+pub async fn watch_interface<T: Send + 'static>(
+    bus: &SystemBus,
+    adapter_name: &str,
+    interface: &'static str,
+    parse: fn(&PropMap) -> T,
+) -> Result<BackendStream<(Address, Option<T>)>> {
+    let connection = bus.connection().await?;
+    let prefix = format!("{}/", adapter_path(adapter_name));
+
+    // subscribe before reading the current objects so no change falls in between
+    let changed_rule =
+        MatchRule::new_signal("org.freedesktop.DBus.Properties", "PropertiesChanged")
+            .with_namespaced_path(adapter_path(adapter_name));
+    let (changed_match, changed) = connection.add_match(changed_rule).await?.msg_stream();
+    let added_rule = MatchRule::new_signal("org.freedesktop.DBus.ObjectManager", "InterfacesAdded");
+    let (added_match, added) = connection.add_match(added_rule).await?.msg_stream();
+    let removed_rule =
+        MatchRule::new_signal("org.freedesktop.DBus.ObjectManager", "InterfacesRemoved");
+    let (removed_match, removed) = connection.add_match(removed_rule).await?.msg_stream();
+
+    let (tx, rx) = mpsc::unbounded_channel();
+    // devices of the objects that implement the interface, by object path
+    let mut watched: HashMap<String, Address> = HashMap::new();
+    let objects = bus
+        .proxy(String::from("/"))
+        .await?
+        .get_managed_objects()
+        .await?;
+    for (path, interfaces) in objects {
+        if !path.starts_with(&prefix) {
+            continue;
+        }
+        if let (Some(properties), Some(address)) =
+            (interfaces.get(interface), address_from_path(&path))
+        {
+            watched.insert(path.to_string(), address);
+            let _ = tx.send((address, Some(parse(properties))));
+        }
+    }
+
+    let bus = bus.clone();
+    tokio::spawn(async move {
+        // the matches stay registered for as long as they are alive
+        let _matches = (changed_match, added_match, removed_match);
+        let (mut changed, mut added, mut removed) =
+            (Box::pin(changed), Box::pin(added), Box::pin(removed));
+
+        loop {
+            let (path, gone) = tokio::select! {
+                Some(message) = changed.next() => {
+                    match changed_path(&message, interface) {
+                        Some(path) => (path, false),
+                        None => continue,
+                    }
+                }
+                Some(message) = added.next() => {
+                    match message.read2::<Path, HashMap<String, PropMap>>() {
+                        Ok((path, interfaces)) if interfaces.contains_key(interface) => {
+                            (path.to_string(), false)
+                        }
+                        _ => continue,
+                    }
+                }
+                Some(message) = removed.next() => {
+                    match message.read2::<Path, Vec<String>>() {
+                        Ok((path, interfaces)) if interfaces.iter().any(|name| name == interface) => {
+                            (path.to_string(), true)
+                        }
+                        _ => continue,
+                    }
+                }
+                else => break,
+            };
+
+            let address = match address_from_path(&path) {
+                Some(address) if path.starts_with(&prefix) => address,
+                _ => continue,
+            };
+            // changes only carry the changed properties, read them all again
+            let properties = if gone {
+                watched.remove(&path);
+                match other_object(&watched, address) {
+                    Some(other) => read_properties(&bus, &other, interface).await,
+                    None => None,
+                }
+            } else {
+                watched.insert(path.clone(), address);
+                read_properties(&bus, &path, interface).await
+            };
+            if tx.send((address, properties.as_ref().map(parse))).is_err() {
+                break;
+            }
+        }
+        debug!("{} events of {} closed", interface, prefix);
+    });
+
+    Ok(UnboundedReceiverStream::new(rx).boxed())
+}
+
+// Path of an object of the device that is still there
+fn other_object(watched: &HashMap<String, Address>, address: Address) -> Option<String> {
+    watched
+        .iter()
+        .find(|(_, device)| **device == address)
+        .map(|(path, _)| path.clone())
+}
+
+fn changed_path(message: &Message, interface: &str) -> Option<String> {
+    let changed_interface: String = message.get1()?;
+    if changed_interface != interface {
+        return None;
+    }
+    Some(message.path()?.to_string())
+}
+
+async fn read_properties(bus: &SystemBus, path: &str, interface: &str) -> Option<PropMap> {
+    let proxy = bus.proxy(path.to_string()).await.ok()?;
+    proxy.get_all(interface).await.ok()
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use bluer::Address;
+
+    use super::{address_from_path, device_path, other_object};
+
+    #[test]
+    fn device_paths_round_trip() {
+        let address = Address::new([0x38, 0x18, 0x4c, 0x10, 0x20, 0x30]);
+        let path = device_path("hci0", address);
+        assert_eq!(path, "/org/bluez/hci0/dev_38_18_4C_10_20_30");
+        assert_eq!(address_from_path(&path), Some(address));
+        assert_eq!(
+            address_from_path(&format!("{}/player0", path)),
+            Some(address)
+        );
+        assert_eq!(address_from_path("/org/bluez/hci0"), None);
+    }
+
+    #[test]
+    fn devices_keep_their_state_until_their_last_object_goes_away() {
+        let headset = Address::new([0x38, 0x18, 0x4c, 0x10, 0x20, 0x30]);
+        let phone = Address::new([0xf4, 0x5c, 0x89, 0xa1, 0xb2, 0xc3]);
+        let headset_path = device_path("hci0", headset);
+        let mut watched = HashMap::new();
+        watched.insert(format!("{}/player0", headset_path), headset);
+        watched.insert(format!("{}/player0", device_path("hci0", phone)), phone);
+
+        assert_eq!(
+            other_object(&watched, headset),
+            Some(format!("{}/player0", headset_path))
+        );
+        watched.remove(&format!("{}/player0", headset_path));
+        assert_eq!(other_object(&watched, headset), None);
+    }
+}
@@ -0,0 +1,83 @@
+use bluer::{
+    l2cap::{SocketAddr, Stream, StreamListener},
+    Address, AddressType, Device,
+};
+use futures::StreamExt;
+use log::debug;
+
+use crate::{
+    backend::{BackendStream, BoxByteStream, IncomingConnection},
+    error::Result,
+    terminal::ChannelMtu,
+};
+
+fn negotiated_mtu(stream: &Stream) -> Result<ChannelMtu> {
+    let socket = stream.as_ref();
+    Ok(ChannelMtu {
+        send: socket.send_mtu()?,
+        receive: socket.recv_mtu()?,
+    })
+}
+
+/// Connects to `psm` of an LE device, the MTU is the one negotiated during the connection
+pub async fn open_l2cap(device: &Device, psm: u16) -> Result<(BoxByteStream, ChannelMtu)> {
+    let address_type = device.address_type().await?;
+    let stream = Stream::connect(SocketAddr::new(device.address(), address_type, psm)).await?;
+    let mtu = negotiated_mtu(&stream)?;
+    Ok((Box::new(stream), mtu))
+}
+
+// the kernel accepts LE connections on its own, rejecting one closes it right away
+struct AcceptedStream {
+    address: Address,
+    stream: Stream,
+    mtu: Option<ChannelMtu>,
+}
+
+impl IncomingConnection for AcceptedStream {
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    fn mtu(&self) -> Option<ChannelMtu> {
+        self.mtu
+    }
+
+    fn accept(self: Box<Self>) -> Result<BoxByteStream> {
+        Ok(Box::new(self.stream))
+    }
+
+    fn reject(self: Box<Self>) {}
+}
+
+/// Listens on `psm` of the adapter, or on a free dynamic PSM when it is 0. Returns the PSM
+/// listened on and the connections until the stream is dropped
+pub async fn listen_l2cap(
+    adapter: &bluer::Adapter,
+    psm: u16,
+) -> Result<(u16, BackendStream<Box<dyn IncomingConnection>>)> {
+    let address = adapter.address().await?;
+    let listener =
+        StreamListener::bind(SocketAddr::new(address, AddressType::LePublic, psm)).await?;
+    let psm = listener.as_ref().local_addr()?.psm;
+    let connections = futures::stream::unfold(listener, |listener| async move {
+        match listener.accept().await {
+            Ok((stream, peer)) => {
+                let connection = AcceptedStream {
+                    address: peer.addr,
+                    mtu: negotiated_mtu(&stream).ok(),
+                    stream,
+                };
+                Some((
+                    Box::new(connection) as Box<dyn IncomingConnection>,
+                    listener,
+                ))
+            }
+            Err(err) => {
+                debug!("stopped accepting L2CAP connections: {}", err);
+                None
+            }
+        }
+    });
+    Ok((psm, connections.boxed()))
+}
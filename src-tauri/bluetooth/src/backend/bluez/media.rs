@@ -0,0 +1,103 @@
+use bluer::Address;
+use dbus::{
+    arg::{PropMap, RefArg},
+    nonblock::stdintf::org_freedesktop_dbus::Properties,
+    Path,
+};
+use futures::StreamExt;
+
+use super::bus::{device_path, watch_interface, SystemBus};
+use crate::{
+    backend::BackendStream,
+    error::{BluetoothError, Result},
+    media::{MediaCommand, MediaPlayerEvent, MediaPlayerInfo, PlaybackStatus, TrackInfo},
+};
+
+const MEDIA_PLAYER_INTERFACE: &str = "org.bluez.MediaPlayer1";
+const MEDIA_CONTROL_INTERFACE: &str = "org.bluez.MediaControl1";
+
+fn track_info(track: &dyn RefArg) -> TrackInfo {
+    let mut info = TrackInfo::default();
+    let mut items = match track.as_iter() {
+        Some(items) => items,
+        None => return info,
+    };
+    // dictionaries iterate as key, value, key, value...
+    while let (Some(key), Some(value)) = (items.next(), items.next()) {
+        let text = || value.as_str().unwrap_or_default().to_string();
+        let number = || value.as_u64().unwrap_or_default() as u32;
+        match key.as_str() {
+            Some("Title") => info.title = text(),
+            Some("Artist") => info.artist = text(),
+            Some("Album") => info.album = text(),
+            Some("Genre") => info.genre = text(),
+            Some("TrackNumber") => info.track_number = number(),
+            Some("NumberOfTracks") => info.number_of_tracks = number(),
+            Some("Duration") => info.duration = number(),
+            _ => (),
+        }
+    }
+    info
+}
+
+fn player_info(properties: &PropMap) -> MediaPlayerInfo {
+    let text = |name: &str| {
+        properties
+            .get(name)
+            .and_then(|value| value.0.as_str())
+            .unwrap_or_default()
+            .to_string()
+    };
+    MediaPlayerInfo {
+        name: text("Name"),
+        status: PlaybackStatus::from_bluez(&text("Status")),
+        position: properties
+            .get("Position")
+            .and_then(|value| value.0.as_u64())
+            .unwrap_or_default() as u32,
+        track: properties
+            .get("Track")
+            .map(|track| track_info(&*track.0))
+            .unwrap_or_default(),
+        repeat: text("Repeat"),
+        shuffle: text("Shuffle"),
+    }
+}
+
+/// Media players of the devices of the adapter, starting with the ones that exist already
+pub async fn player_events(
+    bus: &SystemBus,
+    adapter_name: &str,
+) -> Result<BackendStream<MediaPlayerEvent>> {
+    let events = watch_interface(bus, adapter_name, MEDIA_PLAYER_INTERFACE, player_info).await?;
+    Ok(events
+        .map(|(address, player)| MediaPlayerEvent { address, player })
+        .boxed())
+}
+
+pub async fn media_command(
+    bus: &SystemBus,
+    adapter_name: &str,
+    address: Address,
+    command: MediaCommand,
+) -> Result<()> {
+    let device = bus.proxy(device_path(adapter_name, address)).await?;
+    let player: Path<'static> = device
+        .get(MEDIA_CONTROL_INTERFACE, "Player")
+        .await
+        .map_err(|_| BluetoothError::NotAvailable(String::from("Device has no media player")))?;
+    let method = match command {
+        MediaCommand::Play => "Play",
+        MediaCommand::Pause => "Pause",
+        MediaCommand::Stop => "Stop",
+        MediaCommand::Next => "Next",
+        MediaCommand::Previous => "Previous",
+        MediaCommand::SeekForward => "FastForward",
+        MediaCommand::SeekBackward => "Rewind",
+    };
+    bus.proxy(player.to_string())
+        .await?
+        .method_call::<(), _, _, _>(MEDIA_PLAYER_INTERFACE, method, ())
+        .await?;
+    Ok(())
+}
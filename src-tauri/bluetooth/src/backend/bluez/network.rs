@@ -0,0 +1,104 @@
+use bluer::Address;
+use dbus::arg::{PropMap, RefArg};
+use futures::StreamExt;
+
+use super::bus::{adapter_path, device_path, watch_interface, SystemBus};
+use crate::{
+    backend::BackendStream,
+    error::Result,
+    network::{NetworkEvent, NetworkInfo, PanRole},
+};
+
+const NETWORK_INTERFACE: &str = "org.bluez.Network1";
+const NETWORK_SERVER_INTERFACE: &str = "org.bluez.NetworkServer1";
+
+fn network_info(properties: &PropMap) -> NetworkInfo {
+    let text = |name: &str| {
+        properties
+            .get(name)
+            .and_then(|value| value.0.as_str())
+            .filter(|text| !text.is_empty())
+            .map(String::from)
+    };
+    let connected = properties
+        .get("Connected")
+        .and_then(|value| value.0.as_u64())
+        .unwrap_or_default()
+        != 0;
+    NetworkInfo {
+        connected,
+        // bluetoothd keeps the last interface and role around after disconnecting
+        interface: text("Interface").filter(|_| connected),
+        role: text("UUID")
+            .and_then(|uuid| PanRole::from_bluez(&uuid))
+            .filter(|_| connected),
+    }
+}
+
+/// PAN connections of the devices of the adapter, starting with the ones that exist already
+pub async fn network_events(
+    bus: &SystemBus,
+    adapter_name: &str,
+) -> Result<BackendStream<NetworkEvent>> {
+    let events = watch_interface(bus, adapter_name, NETWORK_INTERFACE, network_info).await?;
+    Ok(events
+        .map(|(address, network)| NetworkEvent { address, network })
+        .boxed())
+}
+
+/// Connects to the PAN service of the device, returns the interface bluetoothd created
+pub async fn connect_network(
+    bus: &SystemBus,
+    adapter_name: &str,
+    address: Address,
+    role: PanRole,
+) -> Result<String> {
+    let (interface,): (String,) = bus
+        .proxy(device_path(adapter_name, address))
+        .await?
+        .method_call(NETWORK_INTERFACE, "Connect", (role.as_bluez(),))
+        .await?;
+    Ok(interface)
+}
+
+pub async fn disconnect_network(
+    bus: &SystemBus,
+    adapter_name: &str,
+    address: Address,
+) -> Result<()> {
+    bus.proxy(device_path(adapter_name, address))
+        .await?
+        .method_call::<(), _, _, _>(NETWORK_INTERFACE, "Disconnect", ())
+        .await?;
+    Ok(())
+}
+
+/// bluetoothd unregisters the server on its own once the bus connection goes away
+pub async fn register_network_server(
+    bus: &SystemBus,
+    adapter_name: &str,
+    role: PanRole,
+    bridge: String,
+) -> Result<()> {
+    bus.proxy(adapter_path(adapter_name))
+        .await?
+        .method_call::<(), _, _, _>(
+            NETWORK_SERVER_INTERFACE,
+            "Register",
+            (role.as_bluez(), bridge),
+        )
+        .await?;
+    Ok(())
+}
+
+pub async fn unregister_network_server(
+    bus: &SystemBus,
+    adapter_name: &str,
+    role: PanRole,
+) -> Result<()> {
+    bus.proxy(adapter_path(adapter_name))
+        .await?
+        .method_call::<(), _, _, _>(NETWORK_SERVER_INTERFACE, "Unregister", (role.as_bluez(),))
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use bluer::{
+    agent::ReqError,
+    rfcomm::{ConnectRequest, Profile, Role, SocketAddr, Stream},
+    Address, Device, Session, Uuid, UuidExt,
+};
+use futures::StreamExt;
+
+use crate::{
+    backend::{BoxByteStream, IncomingConnection, IncomingConnections},
+    error::{BluetoothError, Result},
+    terminal::SERIAL_PORT_UUID,
+};
+
+/// Connects straight to `channel`, or lets bluetoothd look up the serial port channel of the
+/// device and hand the connection to a client profile registered for the duration of the call
+pub async fn open_serial(device: &Device, channel: Option<u8>) -> Result<BoxByteStream> {
+    if let Some(channel) = channel {
+        let stream = Stream::connect(SocketAddr::new(device.address(), channel)).await?;
+        return Ok(Box::new(stream));
+    }
+
+    let uuid = Uuid::from_u16(SERIAL_PORT_UUID);
+    let session = Session::new().await?;
+    let mut profile = session
+        .register_profile(Profile {
+            uuid,
+            role: Some(Role::Client),
+            require_authentication: Some(false),
+            require_authorization: Some(false),
+            auto_connect: Some(false),
+            ..Profile::default()
+        })
+        .await?;
+
+    // bluetoothd only answers the connect call once the profile accepted the connection
+    let connect = device.connect_profile(&uuid);
+    tokio::pin!(connect);
+    let request = tokio::select! {
+        result = &mut connect => {
+            result?;
+            return Err(BluetoothError::NotAvailable(String::from(
+                "bluetoothd connected the serial port without handing it over",
+            )));
+        }
+        request = profile.next() => request.ok_or_else(|| {
+            BluetoothError::NotAvailable(String::from("Serial port profile was unregistered"))
+        })?,
+    };
+    let stream = request.accept()?;
+    connect.await?;
+    Ok(Box::new(stream))
+}
+
+struct IncomingRequest(ConnectRequest);
+
+impl IncomingConnection for IncomingRequest {
+    fn address(&self) -> Address {
+        self.0.device()
+    }
+
+    fn accept(self: Box<Self>) -> Result<BoxByteStream> {
+        Ok(Box::new(self.0.accept()?))
+    }
+
+    fn reject(self: Box<Self>) {
+        self.0.reject(ReqError::Rejected)
+    }
+}
+
+/// Registers a server profile, bluetoothd hands every connection to it over as a request
+pub async fn listen_serial(
+    uuid: Uuid,
+    name: String,
+    channel: Option<u16>,
+) -> Result<IncomingConnections> {
+    let session = Session::new().await?;
+    let profile = session
+        .register_profile(Profile {
+            uuid,
+            name: Some(name),
+            role: Some(Role::Server),
+            channel,
+            // the app has no agent to ask, the adapter checks the trust flag instead
+            require_authorization: Some(false),
+            ..Profile::default()
+        })
+        .await?;
+    Ok(profile
+        .map(move |request| {
+            // the profile is unregistered once the session goes away
+            let _session = &session;
+            Box::new(IncomingRequest(request)) as Box<dyn IncomingConnection>
+        })
+        .boxed())
+}
@@ -0,0 +1,74 @@
+use bluer::Address;
+use dbus::{
+    arg::{PropMap, RefArg, Variant},
+    nonblock::stdintf::org_freedesktop_dbus::Properties,
+};
+use futures::StreamExt;
+
+use super::bus::{device_path, find_object, watch_interface, SystemBus};
+use crate::{
+    backend::BackendStream,
+    error::{BluetoothError, Result},
+    transport::{CodecInfo, MediaTransportEvent, TransportInfo, TransportState},
+};
+
+const MEDIA_TRANSPORT_INTERFACE: &str = "org.bluez.MediaTransport1";
+
+fn transport_info(properties: &PropMap) -> TransportInfo {
+    let number = |name: &str| properties.get(name).and_then(|value| value.0.as_u64());
+    let text = |name: &str| {
+        properties
+            .get(name)
+            .and_then(|value| value.0.as_str())
+            .unwrap_or_default()
+            .to_string()
+    };
+    let configuration: Vec<u8> = properties
+        .get("Configuration")
+        .and_then(|value| value.0.as_iter())
+        .map(|bytes| {
+            bytes
+                .filter_map(|byte| byte.as_u64())
+                .map(|byte| byte as u8)
+                .collect()
+        })
+        .unwrap_or_default();
+    TransportInfo {
+        uuid: text("UUID"),
+        state: TransportState::from_bluez(&text("State")),
+        volume: number("Volume").map(|volume| volume as u16),
+        delay: number("Delay").map(|delay| delay as u16),
+        codec: CodecInfo::decode(number("Codec").unwrap_or_default() as u8, &configuration),
+    }
+}
+
+/// Media transports of the devices of the adapter, starting with the ones that exist already
+pub async fn transport_events(
+    bus: &SystemBus,
+    adapter_name: &str,
+) -> Result<BackendStream<MediaTransportEvent>> {
+    let events =
+        watch_interface(bus, adapter_name, MEDIA_TRANSPORT_INTERFACE, transport_info).await?;
+    Ok(events
+        .map(|(address, transport)| MediaTransportEvent { address, transport })
+        .boxed())
+}
+
+pub async fn set_volume(
+    bus: &SystemBus,
+    adapter_name: &str,
+    address: Address,
+    volume: u16,
+) -> Result<()> {
+    let prefix = format!("{}/", device_path(adapter_name, address));
+    let path = find_object(bus, &prefix, MEDIA_TRANSPORT_INTERFACE)
+        .await?
+        .ok_or_else(|| {
+            BluetoothError::NotAvailable(String::from("Device has no media transport"))
+        })?;
+    bus.proxy(path)
+        .await?
+        .set(MEDIA_TRANSPORT_INTERFACE, "Volume", Variant(volume))
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,1127 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    mem::discriminant,
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
+
+use bluer::{
+    AdapterEvent, AdapterProperty, Address, AddressType, DeviceEvent, DeviceProperty, Uuid, UuidExt,
+};
+use futures::{future, FutureExt, StreamExt};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt, DuplexStream},
+    sync::{broadcast, mpsc, oneshot},
+};
+use tokio_stream::wrappers::{BroadcastStream, UnboundedReceiverStream};
+
+use super::{
+    AdapterBackend, BackendFuture, BackendStream, BoxByteStream, DeviceBackend, IncomingConnection,
+    IncomingConnections,
+};
+use crate::{
+    error::{BluetoothError, Result},
+    media::{MediaCommand, MediaPlayerEvent, MediaPlayerInfo, PlaybackStatus},
+    network::{NetworkEvent, NetworkInfo, PanRole},
+    pairing::{AgentRequest, AgentRequests, PairingRequestKind},
+    rfkill::{FakeRfkill, RfkillSource, RfkillType},
+    terminal::ChannelMtu,
+    transport::{MediaTransportEvent, TransportInfo},
+};
+
+// index of the rfkill entry of the simulated adapter
+const RFKILL_IDX: u32 = 0;
+
+const EVENT_CHANNEL_CAPACITY: usize = 256;
+const SERIAL_BUFFER_SIZE: usize = 1024;
+const L2CAP_MTU: u16 = 512;
+// the range the kernel hands out dynamic LE PSMs from
+const DYNAMIC_PSMS: std::ops::RangeInclusive<u16> = 0x80..=0xff;
+
+/// Backend operations a failure can be injected into with [`Simulator::fail_next`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Operation {
+    SetPowered,
+    SetAlias,
+    SetPairable,
+    SetDiscoverable,
+    SetDiscoverableTimeout,
+    DiscoverDevices,
+    Connect,
+    Disconnect,
+    Pair,
+    ConnectProfile,
+    DisconnectProfile,
+    ConnectNetwork,
+    DisconnectNetwork,
+    OpenSerial,
+    OpenL2cap,
+    MediaCommand,
+    SetVolume,
+}
+
+type Failure = Box<dyn FnOnce() -> BluetoothError + Send>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Service {
+    Serial(Uuid),
+    L2cap(u16),
+}
+
+struct SimulatedDevice {
+    properties: Vec<DeviceProperty>,
+    events: broadcast::Sender<DeviceEvent>,
+}
+
+impl SimulatedDevice {
+    fn new(address: Address, properties: Vec<DeviceProperty>) -> Self {
+        let (events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        let mut device = Self {
+            properties: vec![],
+            events,
+        };
+        device.set(DeviceProperty::Alias(address.to_string()));
+        for property in properties {
+            device.set(property);
+        }
+        device
+    }
+
+    fn set(&mut self, property: DeviceProperty) {
+        upsert(&mut self.properties, property.clone());
+        let _ = self.events.send(DeviceEvent::PropertyChanged(property));
+    }
+
+    fn has_uuid(&self, uuid: &Uuid) -> bool {
+        self.properties.iter().any(
+            |property| matches!(property, DeviceProperty::Uuids(uuids) if uuids.contains(uuid)),
+        )
+    }
+}
+
+struct State {
+    name: String,
+    properties: Vec<AdapterProperty>,
+    devices: HashMap<Address, SimulatedDevice>,
+    // in range but only known to the adapter once a discovery runs
+    nearby: HashMap<Address, Vec<DeviceProperty>>,
+    failures: HashMap<Operation, Failure>,
+    discovery_sessions: usize,
+    servers: HashMap<Service, mpsc::UnboundedSender<Box<dyn IncomingConnection>>>,
+    // devices a PAN connection was made to, connected or not
+    networks: HashMap<Address, NetworkInfo>,
+    // bridge of every registered PAN server
+    network_servers: HashMap<PanRole, String>,
+    agent: Option<mpsc::UnboundedSender<AgentRequest>>,
+    players: HashMap<Address, MediaPlayerInfo>,
+    transports: HashMap<Address, TransportInfo>,
+}
+
+impl State {
+    fn is_powered(&self) -> bool {
+        self.properties
+            .iter()
+            .any(|property| matches!(property, AdapterProperty::Powered(true)))
+    }
+}
+
+struct Shared {
+    state: Mutex<State>,
+    events: broadcast::Sender<AdapterEvent>,
+    network_events: broadcast::Sender<NetworkEvent>,
+    player_events: broadcast::Sender<MediaPlayerEvent>,
+    transport_events: broadcast::Sender<MediaTransportEvent>,
+    rfkill: FakeRfkill,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn check(&self, state: &mut State, operation: Operation) -> Result<()> {
+        match state.failures.remove(&operation) {
+            Some(failure) => Err(failure()),
+            None => Ok(()),
+        }
+    }
+
+    fn set_adapter_property(&self, state: &mut State, property: AdapterProperty) {
+        if let AdapterProperty::Powered(false) = property {
+            for device in state.devices.values_mut() {
+                device.set(DeviceProperty::Connected(false));
+            }
+        }
+        upsert(&mut state.properties, property.clone());
+        let _ = self.events.send(AdapterEvent::PropertyChanged(property));
+    }
+
+    fn add_device(&self, state: &mut State, address: Address, properties: Vec<DeviceProperty>) {
+        state
+            .devices
+            .insert(address, SimulatedDevice::new(address, properties));
+        let _ = self.events.send(AdapterEvent::DeviceAdded(address));
+    }
+}
+
+// replaces the property of the same kind, or adds it when there is none yet
+fn upsert<P>(properties: &mut Vec<P>, property: P) {
+    match properties
+        .iter_mut()
+        .find(|existing| discriminant(*existing) == discriminant(&property))
+    {
+        Some(existing) => *existing = property,
+        None => properties.push(property),
+    }
+}
+
+/// In-memory bluetooth stack. Cloning gives another handle to the same simulation, use
+/// [`Simulator::adapter`] as the backend and the other methods to script what happens
+#[derive(Clone)]
+pub struct Simulator {
+    shared: Arc<Shared>,
+}
+
+impl fmt::Debug for Simulator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.shared.lock();
+        f.debug_struct("Simulator")
+            .field("name", &state.name)
+            .field("devices", &state.devices.keys().collect::<Vec<_>>())
+            .field("nearby", &state.nearby.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl Simulator {
+    /// A powered adapter without any devices
+    pub fn new(name: &str, address: Address) -> Self {
+        let (events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        let (network_events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        let (player_events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        let (transport_events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        let properties = vec![
+            AdapterProperty::Address(address),
+            AdapterProperty::AddressType(AddressType::LePublic),
+            AdapterProperty::Name(String::from("saiyan-blue-simulator")),
+            AdapterProperty::SystemName(String::from("saiyan-blue-simulator")),
+            AdapterProperty::Alias(String::from("Simulated adapter")),
+            AdapterProperty::Class(0x6c010c),
+            AdapterProperty::Powered(true),
+            AdapterProperty::Discoverable(false),
+            AdapterProperty::DiscoverableTimeout(180),
+            AdapterProperty::Pairable(true),
+            AdapterProperty::PairableTimeout(0),
+            AdapterProperty::Discovering(false),
+        ];
+        let rfkill = FakeRfkill::new();
+        rfkill.set_entry(RFKILL_IDX, RfkillType::Bluetooth, false, false);
+        Self {
+            shared: Arc::new(Shared {
+                state: Mutex::new(State {
+                    name: name.to_string(),
+                    properties,
+                    devices: HashMap::new(),
+                    nearby: HashMap::new(),
+                    failures: HashMap::new(),
+                    discovery_sessions: 0,
+                    servers: HashMap::new(),
+                    networks: HashMap::new(),
+                    network_servers: HashMap::new(),
+                    agent: None,
+                    players: HashMap::new(),
+                    transports: HashMap::new(),
+                }),
+                events,
+                network_events,
+                player_events,
+                transport_events,
+                rfkill,
+            }),
+        }
+    }
+
+    /// Simulation with a few paired and a few nearby devices, used by the `--simulate` mode
+    pub fn demo() -> Self {
+        let simulator = Self::new("sim0", Address::new([0x00, 0x1a, 0x7d, 0xda, 0x71, 0x01]));
+        let uuids = |uuids: &[u16]| -> HashSet<Uuid> {
+            uuids.iter().map(|uuid| Uuid::from_u16(*uuid)).collect()
+        };
+
+        simulator.add_device(
+            Address::new([0x38, 0x18, 0x4c, 0x10, 0x20, 0x30]),
+            vec![
+                DeviceProperty::Name(String::from("WH-1000XM4")),
+                DeviceProperty::Alias(String::from("WH-1000XM4")),
+                DeviceProperty::AddressType(AddressType::LePublic),
+                DeviceProperty::Class(0x240404),
+                DeviceProperty::Uuids(uuids(&[0x110b, 0x110c, 0x110e, 0x111e, 0x1108])),
+                DeviceProperty::Paired(true),
+                DeviceProperty::Trusted(true),
+                DeviceProperty::BatteryPercentage(80),
+            ],
+        );
+        simulator.add_device(
+            Address::new([0xf4, 0x5c, 0x89, 0xa1, 0xb2, 0xc3]),
+            vec![
+                DeviceProperty::Name(String::from("Pixel 7")),
+                DeviceProperty::Alias(String::from("Pixel 7")),
+                DeviceProperty::AddressType(AddressType::LePublic),
+                DeviceProperty::Class(0x5a020c),
+                DeviceProperty::Uuids(uuids(&[
+                    0x1105, 0x110a, 0x110c, 0x1112, 0x1115, 0x1116, 0x111f,
+                ])),
+                DeviceProperty::Paired(true),
+            ],
+        );
+        simulator.add_device(
+            Address::new([0xdc, 0x2c, 0x26, 0x01, 0x02, 0x03]),
+            vec![
+                DeviceProperty::Name(String::from("MX Keys")),
+                DeviceProperty::Alias(String::from("MX Keys")),
+                DeviceProperty::AddressType(AddressType::LeRandom),
+                DeviceProperty::Class(0x000540),
+                DeviceProperty::Uuids(uuids(&[0x1812, 0x180f, 0x180a])),
+                DeviceProperty::Paired(true),
+                DeviceProperty::Trusted(true),
+                DeviceProperty::Connected(true),
+                DeviceProperty::BatteryPercentage(55),
+            ],
+        );
+        simulator.add_nearby_device(
+            Address::new([0xc8, 0x2b, 0x96, 0x11, 0x22, 0x33]),
+            vec![
+                DeviceProperty::Name(String::from("nRF UART")),
+                DeviceProperty::AddressType(AddressType::LeRandom),
+                DeviceProperty::Rssi(-61),
+            ],
+        );
+        simulator.add_nearby_device(
+            Address::new([0x00, 0x1b, 0x66, 0x44, 0x55, 0x66]),
+            vec![
+                DeviceProperty::Name(String::from("Serial Board")),
+                DeviceProperty::AddressType(AddressType::LePublic),
+                DeviceProperty::Class(0x001f00),
+                DeviceProperty::Uuids(uuids(&[0x1101])),
+                DeviceProperty::Rssi(-74),
+            ],
+        );
+        simulator
+    }
+
+    /// Keeps the demo simulation alive by draining batteries, moving signal strengths and
+    /// connecting and disconnecting the phone now and then
+    pub fn spawn_demo_script(&self) {
+        let simulator = self.clone();
+        let phone = Address::new([0xf4, 0x5c, 0x89, 0xa1, 0xb2, 0xc3]);
+        tokio::spawn(async move {
+            let mut tick: u64 = 0;
+            loop {
+                tokio::time::sleep(Duration::from_secs(3)).await;
+                tick += 1;
+                if tick % 10 == 0 {
+                    simulator
+                        .set_device_property(phone, DeviceProperty::Connected(tick % 20 == 10));
+                }
+                for (index, address) in simulator.device_addresses().into_iter().enumerate() {
+                    let wobble = ((tick + index as u64) % 7) as i16;
+                    simulator.set_device_property(address, DeviceProperty::Rssi(-50 - wobble * 4));
+                    if tick % 20 == 0 {
+                        if let Some(DeviceProperty::BatteryPercentage(percentage)) = simulator
+                            .device_property(address, |property| {
+                                matches!(property, DeviceProperty::BatteryPercentage(_))
+                            })
+                        {
+                            simulator.set_device_property(
+                                address,
+                                DeviceProperty::BatteryPercentage(percentage.saturating_sub(1)),
+                            );
+                        }
+                    }
+                }
+            }
+        });
+    }
+
+    /// Backend to hand to [`Adapter::new`](crate::adapter::Adapter::new)
+    pub fn adapter(&self) -> Arc<dyn AdapterBackend> {
+        Arc::new(SimulatedAdapter {
+            name: self.shared.lock().name.clone(),
+            simulator: self.clone(),
+        })
+    }
+
+    /// Adds a device the adapter knows about, like a paired one
+    pub fn add_device(&self, address: Address, properties: Vec<DeviceProperty>) {
+        let mut state = self.shared.lock();
+        state.nearby.remove(&address);
+        self.shared.add_device(&mut state, address, properties);
+    }
+
+    /// Adds a device in range that is found by the next discovery
+    pub fn add_nearby_device(&self, address: Address, properties: Vec<DeviceProperty>) {
+        let mut state = self.shared.lock();
+        if state.discovery_sessions > 0 {
+            self.shared.add_device(&mut state, address, properties);
+        } else {
+            state.nearby.insert(address, properties);
+        }
+    }
+
+    pub fn remove_device(&self, address: Address) {
+        let mut state = self.shared.lock();
+        state.nearby.remove(&address);
+        if state.devices.remove(&address).is_some() {
+            let _ = self
+                .shared
+                .events
+                .send(AdapterEvent::DeviceRemoved(address));
+        }
+    }
+
+    pub fn device_addresses(&self) -> Vec<Address> {
+        self.shared.lock().devices.keys().copied().collect()
+    }
+
+    fn device_property(
+        &self,
+        address: Address,
+        predicate: impl Fn(&DeviceProperty) -> bool,
+    ) -> Option<DeviceProperty> {
+        let state = self.shared.lock();
+        let device = state.devices.get(&address)?;
+        device.properties.iter().find(|p| predicate(p)).cloned()
+    }
+
+    pub fn set_device_property(&self, address: Address, property: DeviceProperty) {
+        if let Some(device) = self.shared.lock().devices.get_mut(&address) {
+            device.set(property);
+        }
+    }
+
+    pub fn set_adapter_property(&self, property: AdapterProperty) {
+        let mut state = self.shared.lock();
+        self.shared.set_adapter_property(&mut state, property);
+    }
+
+    /// rfkill entries of the simulated adapter, to hand to
+    /// [`Adapter::new`](crate::adapter::Adapter::new)
+    pub fn rfkill(&self) -> Arc<dyn RfkillSource> {
+        Arc::new(self.shared.rfkill.clone())
+    }
+
+    /// Flips the simulated kill switches, a block powers the adapter off like bluetoothd does
+    pub fn set_rfkill_block(&self, soft: bool, hard: bool) {
+        self.shared
+            .rfkill
+            .set_entry(RFKILL_IDX, RfkillType::Bluetooth, soft, hard);
+        if soft || hard {
+            self.set_adapter_property(AdapterProperty::Powered(false));
+        }
+    }
+
+    /// Lets `address` connect to the serial server registered for `uuid`, returns the end of
+    /// the remote device. It reads end of file right away when the connection is rejected
+    pub fn connect_to_server(&self, address: Address, uuid: Uuid) -> Result<DuplexStream> {
+        self.connect_to(address, Service::Serial(uuid), None)
+    }
+
+    /// Same as [`Simulator::connect_to_server`] for the L2CAP server listening on `psm`
+    pub fn connect_to_l2cap_server(&self, address: Address, psm: u16) -> Result<DuplexStream> {
+        let mtu = ChannelMtu {
+            send: L2CAP_MTU,
+            receive: L2CAP_MTU,
+        };
+        self.connect_to(address, Service::L2cap(psm), Some(mtu))
+    }
+
+    fn connect_to(
+        &self,
+        address: Address,
+        service: Service,
+        mtu: Option<ChannelMtu>,
+    ) -> Result<DuplexStream> {
+        let mut state = self.shared.lock();
+        state.servers.retain(|_, requests| !requests.is_closed());
+        let requests = state.servers.get(&service).ok_or_else(|| {
+            BluetoothError::DoesNotExist(format!("No server registered for {:?}", service))
+        })?;
+        let (local, remote) = tokio::io::duplex(SERIAL_BUFFER_SIZE);
+        requests
+            .send(Box::new(SimulatedConnection {
+                address,
+                local,
+                mtu,
+            }))
+            .map_err(|_| BluetoothError::NotAvailable(String::from("Server went away")))?;
+        Ok(remote)
+    }
+
+    fn listen(&self, service: Service) -> Result<IncomingConnections> {
+        let mut state = self.shared.lock();
+        state.servers.retain(|_, requests| !requests.is_closed());
+        if state.servers.contains_key(&service) {
+            return Err(BluetoothError::AlreadyExists(String::from(
+                "Already Exists",
+            )));
+        }
+        let (tx, rx) = mpsc::unbounded_channel();
+        state.servers.insert(service, tx);
+        Ok(UnboundedReceiverStream::new(rx).boxed())
+    }
+
+    fn register_agent(&self) -> Result<AgentRequests> {
+        let mut state = self.shared.lock();
+        if state
+            .agent
+            .as_ref()
+            .map_or(false, |agent| !agent.is_closed())
+        {
+            return Err(BluetoothError::AlreadyExists(String::from(
+                "Already Exists",
+            )));
+        }
+        let (tx, rx) = mpsc::unbounded_channel();
+        state.agent = Some(tx);
+        Ok(UnboundedReceiverStream::new(rx).boxed())
+    }
+
+    /// Lets `address` ask the registered agent, like bluetoothd does while pairing. The
+    /// receiver gets the answer, or an error when the request was dropped unanswered
+    pub fn request_pairing(
+        &self,
+        address: Address,
+        kind: PairingRequestKind,
+    ) -> Result<oneshot::Receiver<bool>> {
+        let state = self.shared.lock();
+        let agent = state
+            .agent
+            .as_ref()
+            .ok_or_else(|| BluetoothError::DoesNotExist(String::from("No agent registered")))?;
+        let (answer_tx, answer_rx) = oneshot::channel();
+        agent
+            .send(AgentRequest {
+                address,
+                kind,
+                answer: Some(answer_tx),
+            })
+            .map_err(|_| BluetoothError::DoesNotExist(String::from("No agent registered")))?;
+        Ok(answer_rx)
+    }
+
+    /// Gives `address` an AVRCP media player, or removes it when `player` is None
+    pub fn set_media_player(&self, address: Address, player: Option<MediaPlayerInfo>) {
+        let mut state = self.shared.lock();
+        match &player {
+            Some(player) => state.players.insert(address, player.clone()),
+            None => state.players.remove(&address),
+        };
+        let _ = self
+            .shared
+            .player_events
+            .send(MediaPlayerEvent { address, player });
+    }
+
+    /// Gives `address` an A2DP transport, or removes it when `transport` is None
+    pub fn set_media_transport(&self, address: Address, transport: Option<TransportInfo>) {
+        let mut state = self.shared.lock();
+        match &transport {
+            Some(transport) => state.transports.insert(address, transport.clone()),
+            None => state.transports.remove(&address),
+        };
+        let _ = self
+            .shared
+            .transport_events
+            .send(MediaTransportEvent { address, transport });
+    }
+
+    // the player follows the command right away, a real one reports back a moment later
+    fn media_command(&self, address: Address, command: MediaCommand) -> Result<()> {
+        let mut state = self.shared.lock();
+        self.shared.check(&mut state, Operation::MediaCommand)?;
+        let player = state
+            .players
+            .get_mut(&address)
+            .ok_or_else(|| BluetoothError::DoesNotExist(String::from("No player found")))?;
+        match command {
+            MediaCommand::Play => player.status = PlaybackStatus::Playing,
+            MediaCommand::Pause => player.status = PlaybackStatus::Paused,
+            MediaCommand::Stop => {
+                player.status = PlaybackStatus::Stopped;
+                player.position = 0;
+            }
+            MediaCommand::Next => {
+                player.track.track_number += 1;
+                player.position = 0;
+            }
+            MediaCommand::Previous => {
+                player.track.track_number = player.track.track_number.saturating_sub(1);
+                player.position = 0;
+            }
+            MediaCommand::SeekForward => player.status = PlaybackStatus::ForwardSeek,
+            MediaCommand::SeekBackward => player.status = PlaybackStatus::ReverseSeek,
+        }
+        let _ = self.shared.player_events.send(MediaPlayerEvent {
+            address,
+            player: Some(player.clone()),
+        });
+        Ok(())
+    }
+
+    fn set_volume(&self, address: Address, volume: u16) -> Result<()> {
+        let mut state = self.shared.lock();
+        self.shared.check(&mut state, Operation::SetVolume)?;
+        let transport = state
+            .transports
+            .get_mut(&address)
+            .ok_or_else(|| BluetoothError::DoesNotExist(String::from("No transport found")))?;
+        if transport.volume.is_none() {
+            return Err(BluetoothError::NotSupported(String::from(
+                "Operation is not supported",
+            )));
+        }
+        transport.volume = Some(volume);
+        let _ = self.shared.transport_events.send(MediaTransportEvent {
+            address,
+            transport: Some(transport.clone()),
+        });
+        Ok(())
+    }
+
+    fn listen_l2cap(&self, psm: u16) -> Result<(u16, IncomingConnections)> {
+        let psm = if psm == 0 {
+            let state = self.shared.lock();
+            DYNAMIC_PSMS
+                .find(|psm| match state.servers.get(&Service::L2cap(*psm)) {
+                    Some(requests) => requests.is_closed(),
+                    None => true,
+                })
+                .ok_or_else(|| BluetoothError::Failed(String::from("No free PSM")))?
+        } else {
+            psm
+        };
+        Ok((psm, self.listen(Service::L2cap(psm))?))
+    }
+
+    /// Makes the next call of `operation` fail with the error returned by `failure`
+    pub fn fail_next(
+        &self,
+        operation: Operation,
+        failure: impl FnOnce() -> BluetoothError + Send + 'static,
+    ) {
+        self.shared
+            .lock()
+            .failures
+            .insert(operation, Box::new(failure));
+    }
+
+    fn device_operation(
+        &self,
+        address: Address,
+        operation: Operation,
+        property: DeviceProperty,
+    ) -> Result<()> {
+        let mut state = self.shared.lock();
+        self.shared.check(&mut state, operation)?;
+        if !state.is_powered() {
+            return Err(BluetoothError::NotReady(String::from("Resource Not Ready")));
+        }
+        match state.devices.get_mut(&address) {
+            Some(device) => {
+                device.set(property);
+                Ok(())
+            }
+            None => Err(BluetoothError::DoesNotExist(String::from("Does Not Exist"))),
+        }
+    }
+
+    // like bluetoothd, only profiles among the device's UUIDs can be connected, connecting
+    // one connects the device while disconnecting one leaves the device connected
+    fn profile_operation(&self, address: Address, operation: Operation, uuid: Uuid) -> Result<()> {
+        let mut state = self.shared.lock();
+        self.shared.check(&mut state, operation)?;
+        if !state.is_powered() {
+            return Err(BluetoothError::NotReady(String::from("Resource Not Ready")));
+        }
+        let device = state
+            .devices
+            .get_mut(&address)
+            .ok_or_else(|| BluetoothError::DoesNotExist(String::from("Does Not Exist")))?;
+        if !device.has_uuid(&uuid) {
+            return Err(BluetoothError::InvalidArguments(String::from(
+                "Invalid arguments in method call",
+            )));
+        }
+        if operation == Operation::ConnectProfile {
+            device.set(DeviceProperty::Connected(true));
+        }
+        Ok(())
+    }
+
+    // hands out bnep interfaces like the kernel, the lowest free number first
+    fn connect_network(&self, address: Address, role: PanRole) -> Result<String> {
+        let mut state = self.shared.lock();
+        self.shared.check(&mut state, Operation::ConnectNetwork)?;
+        if !state.is_powered() {
+            return Err(BluetoothError::NotReady(String::from("Resource Not Ready")));
+        }
+        let device = state
+            .devices
+            .get(&address)
+            .ok_or_else(|| BluetoothError::DoesNotExist(String::from("Does Not Exist")))?;
+        if !device.has_uuid(&role.profile().uuid()) {
+            return Err(BluetoothError::NotSupported(String::from(
+                "Operation is not supported",
+            )));
+        }
+        if state
+            .networks
+            .get(&address)
+            .map_or(false, |network| network.connected)
+        {
+            return Err(BluetoothError::AlreadyConnected(String::from(
+                "Already Connected",
+            )));
+        }
+        let interface = (0..)
+            .map(|index| format!("bnep{}", index))
+            .find(|interface| {
+                !state
+                    .networks
+                    .values()
+                    .any(|network| network.interface.as_ref() == Some(interface))
+            })
+            .unwrap();
+        let network = NetworkInfo {
+            connected: true,
+            interface: Some(interface.clone()),
+            role: Some(role),
+        };
+        state.networks.insert(address, network.clone());
+        if let Some(device) = state.devices.get_mut(&address) {
+            device.set(DeviceProperty::Connected(true));
+        }
+        let _ = self.shared.network_events.send(NetworkEvent {
+            address,
+            network: Some(network),
+        });
+        Ok(interface)
+    }
+
+    fn disconnect_network(&self, address: Address) -> Result<()> {
+        let mut state = self.shared.lock();
+        self.shared
+            .check(&mut state, Operation::DisconnectNetwork)?;
+        match state.networks.get_mut(&address) {
+            Some(network) if network.connected => {
+                *network = NetworkInfo::default();
+                let _ = self.shared.network_events.send(NetworkEvent {
+                    address,
+                    network: Some(network.clone()),
+                });
+                Ok(())
+            }
+            _ => Err(BluetoothError::Failed(String::from("Not Connected"))),
+        }
+    }
+
+    fn register_network_server(&self, role: PanRole, bridge: String) -> Result<()> {
+        let mut state = self.shared.lock();
+        if state.network_servers.contains_key(&role) {
+            return Err(BluetoothError::AlreadyExists(String::from(
+                "Already Exists",
+            )));
+        }
+        state.network_servers.insert(role, bridge);
+        Ok(())
+    }
+
+    fn unregister_network_server(&self, role: PanRole) -> Result<()> {
+        match self.shared.lock().network_servers.remove(&role) {
+            Some(_) => Ok(()),
+            None => Err(BluetoothError::DoesNotExist(String::from("Does Not Exist"))),
+        }
+    }
+
+    fn set_property(&self, operation: Operation, property: AdapterProperty) -> Result<()> {
+        let mut state = self.shared.lock();
+        self.shared.check(&mut state, operation)?;
+        if let AdapterProperty::Powered(true) = property {
+            if self.shared.rfkill.is_blocked() {
+                return Err(BluetoothError::Failed(String::from(
+                    "Blocked through rfkill",
+                )));
+            }
+        }
+        self.shared.set_adapter_property(&mut state, property);
+        Ok(())
+    }
+
+    fn start_discovery(&self) -> Result<BackendStream<AdapterEvent>> {
+        let mut state = self.shared.lock();
+        self.shared.check(&mut state, Operation::DiscoverDevices)?;
+        if !state.is_powered() {
+            return Err(BluetoothError::NotReady(String::from("Resource Not Ready")));
+        }
+
+        let events = BroadcastStream::new(self.shared.events.subscribe());
+        state.discovery_sessions += 1;
+        if state.discovery_sessions == 1 {
+            self.shared
+                .set_adapter_property(&mut state, AdapterProperty::Discovering(true));
+            let nearby = std::mem::take(&mut state.nearby);
+            for (address, properties) in nearby {
+                self.shared.add_device(&mut state, address, properties);
+            }
+        }
+
+        // stops the discovery once the stream is dropped, like bluer does
+        let guard = DiscoveryGuard {
+            shared: Arc::clone(&self.shared),
+        };
+        Ok(events
+            .filter_map(move |event| {
+                let _ = &guard;
+                future::ready(match event {
+                    Ok(event @ AdapterEvent::DeviceAdded(_))
+                    | Ok(event @ AdapterEvent::DeviceRemoved(_)) => Some(event),
+                    _ => None,
+                })
+            })
+            .boxed())
+    }
+}
+
+struct DiscoveryGuard {
+    shared: Arc<Shared>,
+}
+
+impl Drop for DiscoveryGuard {
+    fn drop(&mut self) {
+        let mut state = self.shared.lock();
+        state.discovery_sessions = state.discovery_sessions.saturating_sub(1);
+        if state.discovery_sessions == 0 {
+            self.shared
+                .set_adapter_property(&mut state, AdapterProperty::Discovering(false));
+        }
+    }
+}
+
+struct SimulatedConnection {
+    address: Address,
+    local: DuplexStream,
+    mtu: Option<ChannelMtu>,
+}
+
+impl IncomingConnection for SimulatedConnection {
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    fn mtu(&self) -> Option<ChannelMtu> {
+        self.mtu
+    }
+
+    fn accept(self: Box<Self>) -> Result<BoxByteStream> {
+        Ok(Box::new(self.local))
+    }
+
+    fn reject(self: Box<Self>) {}
+}
+
+#[derive(Debug)]
+struct SimulatedAdapter {
+    name: String,
+    simulator: Simulator,
+}
+
+impl AdapterBackend for SimulatedAdapter {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn all_properties(&self) -> BackendFuture<'_, Vec<AdapterProperty>> {
+        future::ready(Ok(self.simulator.shared.lock().properties.clone())).boxed()
+    }
+
+    fn device_addresses(&self) -> BackendFuture<'_, Vec<Address>> {
+        future::ready(Ok(self.simulator.device_addresses())).boxed()
+    }
+
+    fn device(&self, address: Address) -> Result<Arc<dyn DeviceBackend>> {
+        Ok(Arc::new(SimulatedDeviceBackend {
+            simulator: self.simulator.clone(),
+            adapter_name: self.name.clone(),
+            address,
+        }))
+    }
+
+    fn events(&self) -> BackendFuture<'_, BackendStream<AdapterEvent>> {
+        let events = BroadcastStream::new(self.simulator.shared.events.subscribe())
+            .filter_map(|event| future::ready(event.ok()))
+            .boxed();
+        future::ready(Ok(events)).boxed()
+    }
+
+    fn discover_devices(&self) -> BackendFuture<'_, BackendStream<AdapterEvent>> {
+        future::ready(self.simulator.start_discovery()).boxed()
+    }
+
+    fn is_discovering(&self) -> BackendFuture<'_, bool> {
+        future::ready(Ok(self.simulator.shared.lock().discovery_sessions > 0)).boxed()
+    }
+
+    fn set_powered(&self, powered: bool) -> BackendFuture<'_, ()> {
+        future::ready(
+            self.simulator
+                .set_property(Operation::SetPowered, AdapterProperty::Powered(powered)),
+        )
+        .boxed()
+    }
+
+    fn set_alias(&self, alias: String) -> BackendFuture<'_, ()> {
+        future::ready(
+            self.simulator
+                .set_property(Operation::SetAlias, AdapterProperty::Alias(alias)),
+        )
+        .boxed()
+    }
+
+    fn set_pairable(&self, pairable: bool) -> BackendFuture<'_, ()> {
+        future::ready(
+            self.simulator
+                .set_property(Operation::SetPairable, AdapterProperty::Pairable(pairable)),
+        )
+        .boxed()
+    }
+
+    fn set_discoverable(&self, discoverable: bool) -> BackendFuture<'_, ()> {
+        future::ready(self.simulator.set_property(
+            Operation::SetDiscoverable,
+            AdapterProperty::Discoverable(discoverable),
+        ))
+        .boxed()
+    }
+
+    fn set_discoverable_timeout(&self, discoverable_timeout: u32) -> BackendFuture<'_, ()> {
+        future::ready(self.simulator.set_property(
+            Operation::SetDiscoverableTimeout,
+            AdapterProperty::DiscoverableTimeout(discoverable_timeout),
+        ))
+        .boxed()
+    }
+
+    fn media_player_events(&self) -> BackendFuture<'_, BackendStream<MediaPlayerEvent>> {
+        let changes = BroadcastStream::new(self.simulator.shared.player_events.subscribe())
+            .filter_map(|event| future::ready(event.ok()));
+        let current: Vec<MediaPlayerEvent> = self
+            .simulator
+            .shared
+            .lock()
+            .players
+            .iter()
+            .map(|(address, player)| MediaPlayerEvent {
+                address: *address,
+                player: Some(player.clone()),
+            })
+            .collect();
+        future::ready(Ok(futures::stream::iter(current).chain(changes).boxed())).boxed()
+    }
+
+    fn media_transport_events(&self) -> BackendFuture<'_, BackendStream<MediaTransportEvent>> {
+        let changes = BroadcastStream::new(self.simulator.shared.transport_events.subscribe())
+            .filter_map(|event| future::ready(event.ok()));
+        let current: Vec<MediaTransportEvent> = self
+            .simulator
+            .shared
+            .lock()
+            .transports
+            .iter()
+            .map(|(address, transport)| MediaTransportEvent {
+                address: *address,
+                transport: Some(transport.clone()),
+            })
+            .collect();
+        future::ready(Ok(futures::stream::iter(current).chain(changes).boxed())).boxed()
+    }
+
+    fn network_events(&self) -> BackendFuture<'_, BackendStream<NetworkEvent>> {
+        // subscribe before reading the current networks so no change falls in between
+        let changes = BroadcastStream::new(self.simulator.shared.network_events.subscribe())
+            .filter_map(|event| future::ready(event.ok()));
+        let current: Vec<NetworkEvent> = self
+            .simulator
+            .shared
+            .lock()
+            .networks
+            .iter()
+            .map(|(address, network)| NetworkEvent {
+                address: *address,
+                network: Some(network.clone()),
+            })
+            .collect();
+        future::ready(Ok(futures::stream::iter(current).chain(changes).boxed())).boxed()
+    }
+
+    fn register_network_server(&self, role: PanRole, bridge: String) -> BackendFuture<'_, ()> {
+        future::ready(self.simulator.register_network_server(role, bridge)).boxed()
+    }
+
+    fn unregister_network_server(&self, role: PanRole) -> BackendFuture<'_, ()> {
+        future::ready(self.simulator.unregister_network_server(role)).boxed()
+    }
+
+    fn listen_serial(
+        &self,
+        uuid: Uuid,
+        _name: String,
+        _channel: Option<u16>,
+    ) -> BackendFuture<'_, IncomingConnections> {
+        future::ready(self.simulator.listen(Service::Serial(uuid))).boxed()
+    }
+
+    fn register_agent(&self) -> BackendFuture<'_, AgentRequests> {
+        future::ready(self.simulator.register_agent()).boxed()
+    }
+
+    fn listen_l2cap(&self, psm: u16) -> BackendFuture<'_, (u16, IncomingConnections)> {
+        future::ready(self.simulator.listen_l2cap(psm)).boxed()
+    }
+}
+
+#[derive(Debug)]
+struct SimulatedDeviceBackend {
+    simulator: Simulator,
+    adapter_name: String,
+    address: Address,
+}
+
+impl DeviceBackend for SimulatedDeviceBackend {
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    fn adapter_name(&self) -> &str {
+        &self.adapter_name
+    }
+
+    fn all_properties(&self) -> BackendFuture<'_, Vec<DeviceProperty>> {
+        let state = self.simulator.shared.lock();
+        let properties = match state.devices.get(&self.address) {
+            Some(device) => Ok(device.properties.clone()),
+            None => Err(BluetoothError::DoesNotExist(String::from("Does Not Exist"))),
+        };
+        future::ready(properties).boxed()
+    }
+
+    fn events(&self) -> BackendFuture<'_, BackendStream<DeviceEvent>> {
+        let state = self.simulator.shared.lock();
+        let events = match state.devices.get(&self.address) {
+            Some(device) => Ok(BroadcastStream::new(device.events.subscribe())
+                .filter_map(|event| future::ready(event.ok()))
+                .boxed()),
+            None => Err(BluetoothError::DoesNotExist(String::from("Does Not Exist"))),
+        };
+        future::ready(events).boxed()
+    }
+
+    fn connect(&self) -> BackendFuture<'_, ()> {
+        future::ready(self.simulator.device_operation(
+            self.address,
+            Operation::Connect,
+            DeviceProperty::Connected(true),
+        ))
+        .boxed()
+    }
+
+    fn disconnect(&self) -> BackendFuture<'_, ()> {
+        future::ready(self.simulator.device_operation(
+            self.address,
+            Operation::Disconnect,
+            DeviceProperty::Connected(false),
+        ))
+        .boxed()
+    }
+
+    fn pair(&self) -> BackendFuture<'_, ()> {
+        future::ready(self.simulator.device_operation(
+            self.address,
+            Operation::Pair,
+            DeviceProperty::Paired(true),
+        ))
+        .boxed()
+    }
+
+    fn connect_profile(&self, uuid: Uuid) -> BackendFuture<'_, ()> {
+        future::ready(self.simulator.profile_operation(
+            self.address,
+            Operation::ConnectProfile,
+            uuid,
+        ))
+        .boxed()
+    }
+
+    fn disconnect_profile(&self, uuid: Uuid) -> BackendFuture<'_, ()> {
+        future::ready(self.simulator.profile_operation(
+            self.address,
+            Operation::DisconnectProfile,
+            uuid,
+        ))
+        .boxed()
+    }
+
+    fn connect_network(&self, role: PanRole) -> BackendFuture<'_, String> {
+        future::ready(self.simulator.connect_network(self.address, role)).boxed()
+    }
+
+    fn disconnect_network(&self) -> BackendFuture<'_, ()> {
+        future::ready(self.simulator.disconnect_network(self.address)).boxed()
+    }
+
+    fn media_command(&self, command: MediaCommand) -> BackendFuture<'_, ()> {
+        future::ready(self.simulator.media_command(self.address, command)).boxed()
+    }
+
+    fn set_volume(&self, volume: u16) -> BackendFuture<'_, ()> {
+        future::ready(self.simulator.set_volume(self.address, volume)).boxed()
+    }
+
+    fn open_serial(&self, _channel: Option<u8>) -> BackendFuture<'_, BoxByteStream> {
+        future::ready(self.echo(Operation::OpenSerial)).boxed()
+    }
+
+    fn open_l2cap(&self, _psm: u16) -> BackendFuture<'_, (BoxByteStream, ChannelMtu)> {
+        let mtu = ChannelMtu {
+            send: L2CAP_MTU,
+            receive: L2CAP_MTU,
+        };
+        future::ready(self.echo(Operation::OpenL2cap).map(|stream| (stream, mtu))).boxed()
+    }
+}
+
+impl SimulatedDeviceBackend {
+    // every device answers with an echo of what it receives
+    fn echo(&self, operation: Operation) -> Result<BoxByteStream> {
+        let mut state = self.simulator.shared.lock();
+        self.simulator.shared.check(&mut state, operation)?;
+        if !state.devices.contains_key(&self.address) {
+            return Err(BluetoothError::DoesNotExist(String::from("Does Not Exist")));
+        }
+        if !state.is_powered() {
+            return Err(BluetoothError::NotReady(String::from("Resource Not Ready")));
+        }
+        let (local, mut remote) = tokio::io::duplex(SERIAL_BUFFER_SIZE);
+        tokio::spawn(async move {
+            let mut buffer = [0; SERIAL_BUFFER_SIZE];
+            while let Ok(read) = remote.read(&mut buffer).await {
+                if read == 0 || remote.write_all(&buffer[..read]).await.is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Box::new(local))
+    }
+}
@@ -0,0 +1,413 @@
+//! Recording of discovered advertisements and inquiry results to btsnoop or pcap files that
+//! Wireshark opens.
+//!
+//! bluetoothd does not hand out the raw HCI traffic, so every report is rebuilt from what it
+//! publishes about the device: LE devices as an HCI LE Extended Advertising Report and BR/EDR
+//! devices as an HCI Extended Inquiry Result, each carrying the name, service UUIDs and
+//! manufacturer data known at that moment. Both file formats hold H4 framed HCI packets.
+
+use std::{
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bluer::{Uuid, UuidExt};
+use serde::{Deserialize, Serialize};
+use tokio::{
+    fs::File,
+    io::{AsyncWriteExt, BufWriter},
+};
+
+use crate::{
+    adapter::DeviceEvent,
+    device::DeviceInfo,
+    error::{BluetoothError, Result},
+};
+
+/// File format of a capture
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptureFormat {
+    /// btsnoop version 1 with the HCI UART (H4) datalink, as written by Android and btmon
+    Btsnoop,
+    /// pcap with the `LINKTYPE_BLUETOOTH_HCI_H4_WITH_PHDR` link type
+    Pcap,
+}
+
+impl CaptureFormat {
+    /// The format matching the extension of `path`: `.pcap`, or `.btsnoop`, `.log` and `.cfa`
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_lowercase().as_str() {
+            "btsnoop" | "log" | "cfa" => Some(CaptureFormat::Btsnoop),
+            "pcap" => Some(CaptureFormat::Pcap),
+            _ => None,
+        }
+    }
+}
+
+/// Serializable state of a running or finished capture
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CaptureInfo {
+    pub path: PathBuf,
+    pub format: CaptureFormat,
+    /// Seconds since the Unix epoch
+    pub started_at: u64,
+    /// Reports written so far
+    pub records: u64,
+}
+
+/// A capture file reports are appended to
+#[derive(Debug)]
+pub struct Capture {
+    file: BufWriter<File>,
+    info: CaptureInfo,
+}
+
+// microseconds between 0000-01-01 and the Unix epoch, the btsnoop timestamp origin
+const BTSNOOP_EPOCH_DELTA: u64 = 0x00dc_ddb3_0f2f_8000;
+// HCI UART (H4) datalink of btsnoop
+const BTSNOOP_DATALINK_H4: u32 = 1002;
+// btsnoop packet flags: received and an event
+const BTSNOOP_FLAGS_RECEIVED_EVENT: u32 = 0b11;
+const LINKTYPE_BLUETOOTH_HCI_H4_WITH_PHDR: u32 = 201;
+// pcap pseudo header direction, in network byte order
+const PCAP_DIRECTION_RECEIVED: u32 = 1;
+
+const H4_EVENT: u8 = 0x04;
+const HCI_EXTENDED_INQUIRY_RESULT: u8 = 0x2f;
+const HCI_LE_META: u8 = 0x3e;
+const HCI_LE_EXTENDED_ADVERTISING_REPORT: u8 = 0x0d;
+// the most AD structures fit into an extended advertising report event of 255 bytes
+const MAX_ADVERTISING_DATA: usize = 229;
+const EIR_DATA_LENGTH: usize = 240;
+// RSSI and TX power value for "not available"
+const NOT_AVAILABLE: u8 = 0x7f;
+
+const AD_INCOMPLETE_UUID16: u8 = 0x02;
+const AD_INCOMPLETE_UUID128: u8 = 0x06;
+const AD_COMPLETE_NAME: u8 = 0x09;
+const AD_CLASS_OF_DEVICE: u8 = 0x0d;
+const AD_MANUFACTURER_DATA: u8 = 0xff;
+
+// Fields of DeviceInfo that change with an advertisement or inquiry result
+const REPORTED_FIELDS: [&str; 5] = ["rssi", "name", "uuids", "manufacturer_data", "class"];
+
+fn now() -> std::time::Duration {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+}
+
+impl Capture {
+    /// Creates or truncates `path` and writes the file header, in `format` or the format of
+    /// the extension of `path`
+    pub async fn create(path: &Path, format: Option<CaptureFormat>) -> Result<Self> {
+        let format = format
+            .or_else(|| CaptureFormat::from_path(path))
+            .ok_or_else(|| {
+                BluetoothError::InvalidArguments(format!(
+                    "{} is neither a .btsnoop nor a .pcap file",
+                    path.display()
+                ))
+            })?;
+        let mut file = BufWriter::new(File::create(path).await?);
+        file.write_all(&file_header(format)).await?;
+        Ok(Self {
+            file,
+            info: CaptureInfo {
+                path: path.to_path_buf(),
+                format,
+                started_at: now().as_secs(),
+                records: 0,
+            },
+        })
+    }
+
+    pub fn info(&self) -> &CaptureInfo {
+        &self.info
+    }
+
+    /// Writes a report for devices that were found or heard again, other events are skipped.
+    /// Devices without a signal strength were not heard over the air and are skipped as well
+    pub async fn record(&mut self, event: &DeviceEvent) -> Result<()> {
+        let device = match event {
+            DeviceEvent::DeviceAdded { device } => device,
+            DeviceEvent::DeviceUpdated { device, patch }
+                if REPORTED_FIELDS
+                    .iter()
+                    .any(|field| patch.changes.contains_key(*field)) =>
+            {
+                device
+            }
+            _ => return Ok(()),
+        };
+        if device.rssi.is_none() {
+            return Ok(());
+        }
+        let packet = hci_event(device);
+        let bytes = record(self.info.format, &packet, now());
+        self.file.write_all(&bytes).await?;
+        // a capture that is never stopped, e.g. when the app is killed, stays readable
+        self.file.flush().await?;
+        self.info.records += 1;
+        Ok(())
+    }
+
+    /// Flushes and closes the file
+    pub async fn finish(mut self) -> Result<CaptureInfo> {
+        self.file.shutdown().await?;
+        Ok(self.info)
+    }
+}
+
+fn file_header(format: CaptureFormat) -> Vec<u8> {
+    let mut header = Vec::new();
+    match format {
+        CaptureFormat::Btsnoop => {
+            header.extend_from_slice(b"btsnoop\0");
+            header.extend_from_slice(&1u32.to_be_bytes());
+            header.extend_from_slice(&BTSNOOP_DATALINK_H4.to_be_bytes());
+        }
+        CaptureFormat::Pcap => {
+            header.extend_from_slice(&0xa1b2_c3d4u32.to_le_bytes());
+            header.extend_from_slice(&2u16.to_le_bytes());
+            header.extend_from_slice(&4u16.to_le_bytes());
+            // time zone and timestamp accuracy
+            header.extend_from_slice(&0i32.to_le_bytes());
+            header.extend_from_slice(&0u32.to_le_bytes());
+            // snapshot length
+            header.extend_from_slice(&65535u32.to_le_bytes());
+            header.extend_from_slice(&LINKTYPE_BLUETOOTH_HCI_H4_WITH_PHDR.to_le_bytes());
+        }
+    }
+    header
+}
+
+// One packet record with its timestamp, `since_epoch` is the time since the Unix epoch
+fn record(format: CaptureFormat, packet: &[u8], since_epoch: std::time::Duration) -> Vec<u8> {
+    let mut record = Vec::new();
+    match format {
+        CaptureFormat::Btsnoop => {
+            let length = packet.len() as u32;
+            let timestamp = since_epoch.as_micros() as u64 + BTSNOOP_EPOCH_DELTA;
+            record.extend_from_slice(&length.to_be_bytes());
+            record.extend_from_slice(&length.to_be_bytes());
+            record.extend_from_slice(&BTSNOOP_FLAGS_RECEIVED_EVENT.to_be_bytes());
+            // cumulative drops
+            record.extend_from_slice(&0u32.to_be_bytes());
+            record.extend_from_slice(&timestamp.to_be_bytes());
+            record.extend_from_slice(packet);
+        }
+        CaptureFormat::Pcap => {
+            let length = packet.len() as u32 + 4;
+            record.extend_from_slice(&(since_epoch.as_secs() as u32).to_le_bytes());
+            record.extend_from_slice(&since_epoch.subsec_micros().to_le_bytes());
+            record.extend_from_slice(&length.to_le_bytes());
+            record.extend_from_slice(&length.to_le_bytes());
+            record.extend_from_slice(&PCAP_DIRECTION_RECEIVED.to_be_bytes());
+            record.extend_from_slice(packet);
+        }
+    }
+    record
+}
+
+fn rssi(device: &DeviceInfo) -> u8 {
+    match device.rssi {
+        Some(rssi) if (-127..=20).contains(&rssi) => rssi as i8 as u8,
+        _ => NOT_AVAILABLE,
+    }
+}
+
+// HCI addresses are little endian, DeviceInfo keeps them in display order
+fn hci_address(device: &DeviceInfo) -> [u8; 6] {
+    let mut address = device.address;
+    address.reverse();
+    address
+}
+
+// BlueZ reports classic and dual-mode devices with a "public" address type like LE devices, only
+// the class of device tells them apart
+fn is_classic(device: &DeviceInfo) -> bool {
+    device.address_type != "random" && device.class != 0 && device.class_of_device.is_some()
+}
+
+// AD structures, the same format is used for EIR data. Structures that do not fit into
+// `limit` bytes anymore are left out
+fn advertising_data(device: &DeviceInfo, limit: usize) -> Vec<u8> {
+    let mut structures: Vec<(u8, Vec<u8>)> = Vec::new();
+    if !device.name.is_empty() {
+        structures.push((AD_COMPLETE_NAME, device.name.as_bytes().to_vec()));
+    }
+    if is_classic(device) {
+        structures.push((AD_CLASS_OF_DEVICE, device.class.to_le_bytes()[..3].to_vec()));
+    }
+    let mut uuids: Vec<Uuid> = device
+        .uuids
+        .keys()
+        .filter_map(|uuid| Uuid::parse_str(uuid).ok())
+        .collect();
+    uuids.sort();
+    // "incomplete" lists, bluetoothd merges the UUIDs of every source it learned them from
+    let uuid16: Vec<u8> = uuids
+        .iter()
+        .filter_map(|uuid| uuid.as_u16())
+        .flat_map(|uuid| uuid.to_le_bytes())
+        .collect();
+    let uuid128: Vec<u8> = uuids
+        .iter()
+        .filter(|uuid| uuid.as_u16().is_none())
+        .flat_map(|uuid| {
+            let mut bytes = *uuid.as_bytes();
+            bytes.reverse();
+            bytes
+        })
+        .collect();
+    if !uuid16.is_empty() {
+        structures.push((AD_INCOMPLETE_UUID16, uuid16));
+    }
+    if !uuid128.is_empty() {
+        structures.push((AD_INCOMPLETE_UUID128, uuid128));
+    }
+    let mut manufacturer_data: Vec<(&u16, &Vec<u8>)> = device.manufacturer_data.iter().collect();
+    manufacturer_data.sort();
+    for (company, data) in manufacturer_data {
+        let mut payload = company.to_le_bytes().to_vec();
+        payload.extend_from_slice(data);
+        structures.push((AD_MANUFACTURER_DATA, payload));
+    }
+
+    let mut data = Vec::new();
+    for (ad_type, payload) in structures {
+        // length byte, type byte and payload
+        if payload.len() > 254 || data.len() + 2 + payload.len() > limit {
+            continue;
+        }
+        data.push(payload.len() as u8 + 1);
+        data.push(ad_type);
+        data.extend_from_slice(&payload);
+    }
+    data
+}
+
+/// H4 framed HCI event reporting the device as it was last heard
+fn hci_event(device: &DeviceInfo) -> Vec<u8> {
+    let mut parameters = Vec::new();
+    let event_code = if is_classic(device) {
+        parameters.push(1); // number of responses
+        parameters.extend_from_slice(&hci_address(device));
+        parameters.push(0x01); // page scan repetition mode R1
+        parameters.push(0x00); // reserved
+        parameters.extend_from_slice(&device.class.to_le_bytes()[..3]);
+        parameters.extend_from_slice(&0u16.to_le_bytes()); // clock offset
+        parameters.push(rssi(device));
+        let mut eir = advertising_data(device, EIR_DATA_LENGTH);
+        eir.resize(EIR_DATA_LENGTH, 0);
+        parameters.extend_from_slice(&eir);
+        HCI_EXTENDED_INQUIRY_RESULT
+    } else {
+        let data = advertising_data(device, MAX_ADVERTISING_DATA);
+        parameters.push(HCI_LE_EXTENDED_ADVERTISING_REPORT);
+        parameters.push(1); // number of reports
+
+        // event type: bluetoothd does not tell the PDU type, so it is synthesized as a
+        // non-connectable, non-scannable extended PDU. Not legacy, since the data can be
+        // longer than the 31 bytes a legacy PDU carries
+        parameters.extend_from_slice(&0u16.to_le_bytes());
+        parameters.push(if device.address_type == "random" {
+            0x01
+        } else {
+            0x00
+        });
+        parameters.extend_from_slice(&hci_address(device));
+        parameters.push(0x01); // primary PHY LE 1M
+        parameters.push(0x00); // no secondary PHY
+        parameters.push(0xff); // no advertising SID
+        parameters.push(NOT_AVAILABLE); // TX power
+        parameters.push(rssi(device));
+        parameters.extend_from_slice(&0u16.to_le_bytes()); // no periodic advertising
+        parameters.push(0x00); // direct address type
+        parameters.extend_from_slice(&[0; 6]); // direct address
+        parameters.push(data.len() as u8);
+        parameters.extend_from_slice(&data);
+        HCI_LE_META
+    };
+    let mut packet = vec![H4_EVENT, event_code, parameters.len() as u8];
+    packet.extend_from_slice(&parameters);
+    packet
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::{file_header, hci_event, record, CaptureFormat};
+    use crate::{class_of_device::ClassOfDevice, device::DeviceInfo};
+
+    fn beacon() -> DeviceInfo {
+        DeviceInfo {
+            name: String::from("nRF UART"),
+            address: [0xc8, 0x2b, 0x96, 0x11, 0x22, 0x33],
+            address_type: String::from("random"),
+            rssi: Some(-61),
+            manufacturer_data: [(0x0059, vec![0x01, 0x02])].into(),
+            ..DeviceInfo::default()
+        }
+    }
+
+    #[test]
+    fn builds_le_extended_advertising_reports() {
+        let packet = hci_event(&beacon());
+        assert_eq!(
+            &packet[..5],
+            &[0x04, 0x3e, packet.len() as u8 - 3, 0x0d, 0x01]
+        );
+        // random address type, then the address in little endian order
+        assert_eq!(packet[7], 0x01);
+        assert_eq!(&packet[8..14], &[0x33, 0x22, 0x11, 0x96, 0x2b, 0xc8]);
+        assert_eq!(packet[18], -61i8 as u8);
+        let data = &packet[29..];
+        assert_eq!(packet[28] as usize, data.len());
+        assert_eq!(&data[..10], b"\x09\x09nRF UART");
+        assert_eq!(&data[10..], &[0x05, 0xff, 0x59, 0x00, 0x01, 0x02]);
+    }
+
+    #[test]
+    fn builds_extended_inquiry_results() {
+        let headset = DeviceInfo {
+            name: String::from("WH-1000XM4"),
+            address_type: String::from("public"),
+            class: 0x240404,
+            class_of_device: ClassOfDevice::decode(0x240404),
+            rssi: Some(-50),
+            ..DeviceInfo::default()
+        };
+        let packet = hci_event(&headset);
+        assert_eq!(&packet[..4], &[0x04, 0x2f, 255, 0x01]);
+        assert_eq!(packet.len(), 3 + 255);
+        assert_eq!(&packet[12..15], &[0x04, 0x04, 0x24]);
+        assert_eq!(packet[17], -50i8 as u8);
+        assert_eq!(&packet[18..20], &[11, 0x09]);
+    }
+
+    #[test]
+    fn writes_file_headers_and_records() {
+        let btsnoop = file_header(CaptureFormat::Btsnoop);
+        assert_eq!(&btsnoop[..8], b"btsnoop\0");
+        assert_eq!(&btsnoop[12..], &1002u32.to_be_bytes());
+        let pcap = file_header(CaptureFormat::Pcap);
+        assert_eq!(pcap.len(), 24);
+        assert_eq!(&pcap[20..], &201u32.to_le_bytes());
+
+        let since_epoch = Duration::from_micros(1_700_000_000_000_123);
+        let btsnoop = record(CaptureFormat::Btsnoop, &[0x04, 0x0e], since_epoch);
+        assert_eq!(btsnoop.len(), 24 + 2);
+        assert_eq!(
+            &btsnoop[16..24],
+            &(1_700_000_000_000_123u64 + 0x00dc_ddb3_0f2f_8000).to_be_bytes()
+        );
+        let pcap = record(CaptureFormat::Pcap, &[0x04, 0x0e], since_epoch);
+        assert_eq!(&pcap[4..8], &123u32.to_le_bytes());
+        assert_eq!(&pcap[8..12], &6u32.to_le_bytes());
+        assert_eq!(&pcap[16..], &[0, 0, 0, 1, 0x04, 0x0e]);
+    }
+}
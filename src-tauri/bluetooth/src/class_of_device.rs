@@ -0,0 +1,518 @@
+//! Decoder for the 24 bit Class of Device of adapters and BR/EDR devices.
+//!
+//! Bits 0-1 are the format type, bits 2-7 the minor class, bits 8-12 the major class and
+//! bits 13-23 the service classes. How the minor class bits are laid out depends on the major
+//! class, see the Assigned Numbers document of the Bluetooth SIG.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+const FORMAT_MASK: u32 = 0x00_0003;
+const MINOR_MASK: u32 = 0x00_00fc;
+const MAJOR_MASK: u32 = 0x00_1f00;
+const CLASS_MASK: u32 = 0xff_ffff;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MajorClass {
+    Miscellaneous,
+    Computer,
+    Phone,
+    NetworkAccessPoint,
+    AudioVideo,
+    Peripheral,
+    Imaging,
+    Wearable,
+    Toy,
+    Health,
+    Uncategorized,
+    Reserved(u8),
+}
+
+impl MajorClass {
+    fn from_bits(bits: u8) -> Self {
+        match bits {
+            0x00 => MajorClass::Miscellaneous,
+            0x01 => MajorClass::Computer,
+            0x02 => MajorClass::Phone,
+            0x03 => MajorClass::NetworkAccessPoint,
+            0x04 => MajorClass::AudioVideo,
+            0x05 => MajorClass::Peripheral,
+            0x06 => MajorClass::Imaging,
+            0x07 => MajorClass::Wearable,
+            0x08 => MajorClass::Toy,
+            0x09 => MajorClass::Health,
+            0x1f => MajorClass::Uncategorized,
+            bits => MajorClass::Reserved(bits),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ComputerClass {
+    Uncategorized,
+    Desktop,
+    Server,
+    Laptop,
+    HandheldPcPda,
+    PalmSizePcPda,
+    Wearable,
+    Tablet,
+    Reserved(u8),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PhoneClass {
+    Uncategorized,
+    Cellular,
+    Cordless,
+    Smartphone,
+    ModemOrVoiceGateway,
+    CommonIsdnAccess,
+    Reserved(u8),
+}
+
+/// How much of the network access point's capacity is in use, bits 5-7 of the minor class
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NetworkLoad {
+    FullyAvailable,
+    Utilized1To17,
+    Utilized17To33,
+    Utilized33To50,
+    Utilized50To67,
+    Utilized67To83,
+    Utilized83To99,
+    NoServiceAvailable,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AudioVideoClass {
+    Uncategorized,
+    WearableHeadset,
+    HandsFree,
+    Microphone,
+    Loudspeaker,
+    Headphones,
+    PortableAudio,
+    CarAudio,
+    SetTopBox,
+    HifiAudio,
+    Vcr,
+    VideoCamera,
+    Camcorder,
+    VideoMonitor,
+    VideoDisplayAndLoudspeaker,
+    VideoConferencing,
+    GamingToy,
+    Reserved(u8),
+}
+
+/// Bits 6-7 of a peripheral's minor class
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PeripheralInput {
+    None,
+    Keyboard,
+    Pointing,
+    KeyboardAndPointing,
+}
+
+/// Bits 2-5 of a peripheral's minor class
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PeripheralKind {
+    Uncategorized,
+    Joystick,
+    Gamepad,
+    RemoteControl,
+    SensingDevice,
+    DigitizerTablet,
+    CardReader,
+    DigitalPen,
+    HandheldScanner,
+    HandheldGesturalInput,
+    Reserved(u8),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WearableClass {
+    WristWatch,
+    Pager,
+    Jacket,
+    Helmet,
+    Glasses,
+    Pin,
+    Reserved(u8),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToyClass {
+    Robot,
+    Vehicle,
+    DollActionFigure,
+    Controller,
+    Game,
+    Reserved(u8),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthClass {
+    Undefined,
+    BloodPressureMonitor,
+    Thermometer,
+    WeighingScale,
+    GlucoseMeter,
+    PulseOximeter,
+    HeartRateMonitor,
+    HealthDataDisplay,
+    StepCounter,
+    BodyCompositionAnalyzer,
+    PeakFlowMonitor,
+    MedicationMonitor,
+    KneeProsthesis,
+    AnkleProsthesis,
+    GenericHealthManager,
+    PersonalMobilityDevice,
+    Reserved(u8),
+}
+
+/// Minor class in the layout of its major class
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MinorClass {
+    Computer(ComputerClass),
+    Phone(PhoneClass),
+    NetworkAccessPoint {
+        load: NetworkLoad,
+        /// Bits 2-4, no values are assigned yet
+        sub_class: u8,
+    },
+    AudioVideo(AudioVideoClass),
+    Peripheral {
+        input: PeripheralInput,
+        kind: PeripheralKind,
+    },
+    /// A bitfield, a device can be several of these at once
+    Imaging {
+        display: bool,
+        camera: bool,
+        scanner: bool,
+        printer: bool,
+    },
+    Wearable(WearableClass),
+    Toy(ToyClass),
+    Health(HealthClass),
+    /// Major classes without assigned minor classes, or a minor class that is not assigned
+    Unassigned(u8),
+}
+
+impl MinorClass {
+    // `bits` are the six minor class bits 2-7 shifted down to 0-5
+    fn decode(major: MajorClass, bits: u8) -> Self {
+        match major {
+            MajorClass::Computer => MinorClass::Computer(match bits {
+                0x00 => ComputerClass::Uncategorized,
+                0x01 => ComputerClass::Desktop,
+                0x02 => ComputerClass::Server,
+                0x03 => ComputerClass::Laptop,
+                0x04 => ComputerClass::HandheldPcPda,
+                0x05 => ComputerClass::PalmSizePcPda,
+                0x06 => ComputerClass::Wearable,
+                0x07 => ComputerClass::Tablet,
+                bits => ComputerClass::Reserved(bits),
+            }),
+            MajorClass::Phone => MinorClass::Phone(match bits {
+                0x00 => PhoneClass::Uncategorized,
+                0x01 => PhoneClass::Cellular,
+                0x02 => PhoneClass::Cordless,
+                0x03 => PhoneClass::Smartphone,
+                0x04 => PhoneClass::ModemOrVoiceGateway,
+                0x05 => PhoneClass::CommonIsdnAccess,
+                bits => PhoneClass::Reserved(bits),
+            }),
+            MajorClass::NetworkAccessPoint => MinorClass::NetworkAccessPoint {
+                load: match bits >> 3 {
+                    0 => NetworkLoad::FullyAvailable,
+                    1 => NetworkLoad::Utilized1To17,
+                    2 => NetworkLoad::Utilized17To33,
+                    3 => NetworkLoad::Utilized33To50,
+                    4 => NetworkLoad::Utilized50To67,
+                    5 => NetworkLoad::Utilized67To83,
+                    6 => NetworkLoad::Utilized83To99,
+                    _ => NetworkLoad::NoServiceAvailable,
+                },
+                sub_class: bits & 0x07,
+            },
+            MajorClass::AudioVideo => MinorClass::AudioVideo(match bits {
+                0x00 => AudioVideoClass::Uncategorized,
+                0x01 => AudioVideoClass::WearableHeadset,
+                0x02 => AudioVideoClass::HandsFree,
+                0x04 => AudioVideoClass::Microphone,
+                0x05 => AudioVideoClass::Loudspeaker,
+                0x06 => AudioVideoClass::Headphones,
+                0x07 => AudioVideoClass::PortableAudio,
+                0x08 => AudioVideoClass::CarAudio,
+                0x09 => AudioVideoClass::SetTopBox,
+                0x0a => AudioVideoClass::HifiAudio,
+                0x0b => AudioVideoClass::Vcr,
+                0x0c => AudioVideoClass::VideoCamera,
+                0x0d => AudioVideoClass::Camcorder,
+                0x0e => AudioVideoClass::VideoMonitor,
+                0x0f => AudioVideoClass::VideoDisplayAndLoudspeaker,
+                0x10 => AudioVideoClass::VideoConferencing,
+                0x12 => AudioVideoClass::GamingToy,
+                bits => AudioVideoClass::Reserved(bits),
+            }),
+            MajorClass::Peripheral => MinorClass::Peripheral {
+                input: match bits >> 4 {
+                    0 => PeripheralInput::None,
+                    1 => PeripheralInput::Keyboard,
+                    2 => PeripheralInput::Pointing,
+                    _ => PeripheralInput::KeyboardAndPointing,
+                },
+                kind: match bits & 0x0f {
+                    0x00 => PeripheralKind::Uncategorized,
+                    0x01 => PeripheralKind::Joystick,
+                    0x02 => PeripheralKind::Gamepad,
+                    0x03 => PeripheralKind::RemoteControl,
+                    0x04 => PeripheralKind::SensingDevice,
+                    0x05 => PeripheralKind::DigitizerTablet,
+                    0x06 => PeripheralKind::CardReader,
+                    0x07 => PeripheralKind::DigitalPen,
+                    0x08 => PeripheralKind::HandheldScanner,
+                    0x09 => PeripheralKind::HandheldGesturalInput,
+                    bits => PeripheralKind::Reserved(bits),
+                },
+            },
+            MajorClass::Imaging => MinorClass::Imaging {
+                display: bits & 0x04 != 0,
+                camera: bits & 0x08 != 0,
+                scanner: bits & 0x10 != 0,
+                printer: bits & 0x20 != 0,
+            },
+            MajorClass::Wearable => MinorClass::Wearable(match bits {
+                0x01 => WearableClass::WristWatch,
+                0x02 => WearableClass::Pager,
+                0x03 => WearableClass::Jacket,
+                0x04 => WearableClass::Helmet,
+                0x05 => WearableClass::Glasses,
+                0x06 => WearableClass::Pin,
+                bits => WearableClass::Reserved(bits),
+            }),
+            MajorClass::Toy => MinorClass::Toy(match bits {
+                0x01 => ToyClass::Robot,
+                0x02 => ToyClass::Vehicle,
+                0x03 => ToyClass::DollActionFigure,
+                0x04 => ToyClass::Controller,
+                0x05 => ToyClass::Game,
+                bits => ToyClass::Reserved(bits),
+            }),
+            MajorClass::Health => MinorClass::Health(match bits {
+                0x00 => HealthClass::Undefined,
+                0x01 => HealthClass::BloodPressureMonitor,
+                0x02 => HealthClass::Thermometer,
+                0x03 => HealthClass::WeighingScale,
+                0x04 => HealthClass::GlucoseMeter,
+                0x05 => HealthClass::PulseOximeter,
+                0x06 => HealthClass::HeartRateMonitor,
+                0x07 => HealthClass::HealthDataDisplay,
+                0x08 => HealthClass::StepCounter,
+                0x09 => HealthClass::BodyCompositionAnalyzer,
+                0x0a => HealthClass::PeakFlowMonitor,
+                0x0b => HealthClass::MedicationMonitor,
+                0x0c => HealthClass::KneeProsthesis,
+                0x0d => HealthClass::AnkleProsthesis,
+                0x0e => HealthClass::GenericHealthManager,
+                0x0f => HealthClass::PersonalMobilityDevice,
+                bits => HealthClass::Reserved(bits),
+            }),
+            MajorClass::Miscellaneous | MajorClass::Uncategorized | MajorClass::Reserved(_) => {
+                MinorClass::Unassigned(bits)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ServiceClass {
+    LimitedDiscoverableMode,
+    LeAudio,
+    Positioning,
+    Networking,
+    Rendering,
+    Capturing,
+    ObjectTransfer,
+    Audio,
+    Telephony,
+    Information,
+}
+
+const SERVICE_CLASSES: [(u32, ServiceClass); 10] = [
+    (0x00_2000, ServiceClass::LimitedDiscoverableMode),
+    (0x00_4000, ServiceClass::LeAudio),
+    (0x01_0000, ServiceClass::Positioning),
+    (0x02_0000, ServiceClass::Networking),
+    (0x04_0000, ServiceClass::Rendering),
+    (0x08_0000, ServiceClass::Capturing),
+    (0x10_0000, ServiceClass::ObjectTransfer),
+    (0x20_0000, ServiceClass::Audio),
+    (0x40_0000, ServiceClass::Telephony),
+    (0x80_0000, ServiceClass::Information),
+];
+
+/// A decoded Class of Device
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClassOfDevice {
+    pub major: MajorClass,
+    pub minor: MinorClass,
+    pub services: Vec<ServiceClass>,
+}
+
+impl ClassOfDevice {
+    /// None when `class` is not a valid Class of Device, i.e. it has bits above the 24th or
+    /// a format type other than 0
+    pub fn decode(class: u32) -> Option<Self> {
+        if class & !CLASS_MASK != 0 || class & FORMAT_MASK != 0 {
+            return None;
+        }
+        let major = MajorClass::from_bits(((class & MAJOR_MASK) >> 8) as u8);
+        Some(ClassOfDevice {
+            major,
+            minor: MinorClass::decode(major, ((class & MINOR_MASK) >> 2) as u8),
+            services: SERVICE_CLASSES
+                .iter()
+                .filter(|(bit, _)| class & bit != 0)
+                .map(|(_, service)| *service)
+                .collect(),
+        })
+    }
+}
+
+// snake_case names as serialized, with spaces, for the CLI and logs
+fn name<T: Serialize>(value: &T) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(name)) => name.replace('_', " "),
+        _ => String::from("reserved"),
+    }
+}
+
+impl fmt::Display for ClassOfDevice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", name(&self.major))?;
+        let minor = match self.minor {
+            MinorClass::Computer(class) => name(&class),
+            MinorClass::Phone(class) => name(&class),
+            MinorClass::NetworkAccessPoint { load, .. } => name(&load),
+            MinorClass::AudioVideo(class) => name(&class),
+            MinorClass::Peripheral { input, kind } => match input {
+                PeripheralInput::None => name(&kind),
+                input => format!("{} {}", name(&input), name(&kind)),
+            },
+            MinorClass::Imaging {
+                display,
+                camera,
+                scanner,
+                printer,
+            } => [
+                (display, "display"),
+                (camera, "camera"),
+                (scanner, "scanner"),
+                (printer, "printer"),
+            ]
+            .iter()
+            .filter(|(set, _)| *set)
+            .map(|(_, name)| *name)
+            .collect::<Vec<&str>>()
+            .join(", "),
+            MinorClass::Wearable(class) => name(&class),
+            MinorClass::Toy(class) => name(&class),
+            MinorClass::Health(class) => name(&class),
+            MinorClass::Unassigned(_) => String::new(),
+        };
+        if !minor.is_empty() {
+            write!(f, ": {}", minor)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        AudioVideoClass, ClassOfDevice, MajorClass, MinorClass, PeripheralInput, PeripheralKind,
+        ServiceClass,
+    };
+
+    #[test]
+    fn decodes_audio_video_and_services() {
+        let headset = ClassOfDevice::decode(0x240404).unwrap();
+        assert_eq!(headset.major, MajorClass::AudioVideo);
+        assert_eq!(
+            headset.minor,
+            MinorClass::AudioVideo(AudioVideoClass::WearableHeadset)
+        );
+        assert_eq!(
+            headset.services,
+            vec![ServiceClass::Rendering, ServiceClass::Audio]
+        );
+        assert_eq!(headset.to_string(), "audio video: wearable headset");
+    }
+
+    #[test]
+    fn decodes_peripheral_input_and_kind_apart() {
+        // keyboard, the old 0x1ffc lookup had no entry for it
+        let keyboard = ClassOfDevice::decode(0x000540).unwrap();
+        assert_eq!(
+            keyboard.minor,
+            MinorClass::Peripheral {
+                input: PeripheralInput::Keyboard,
+                kind: PeripheralKind::Uncategorized,
+            }
+        );
+        let combo_gamepad = ClassOfDevice::decode(0x0005c8).unwrap();
+        assert_eq!(
+            combo_gamepad.minor,
+            MinorClass::Peripheral {
+                input: PeripheralInput::KeyboardAndPointing,
+                kind: PeripheralKind::Gamepad,
+            }
+        );
+        assert_eq!(
+            combo_gamepad.to_string(),
+            "peripheral: keyboard and pointing gamepad"
+        );
+    }
+
+    #[test]
+    fn decodes_imaging_as_bitfield() {
+        let scanner_printer = ClassOfDevice::decode(0x000680 | 0x40).unwrap();
+        assert_eq!(
+            scanner_printer.minor,
+            MinorClass::Imaging {
+                display: false,
+                camera: false,
+                scanner: true,
+                printer: true,
+            }
+        );
+        assert_eq!(scanner_printer.to_string(), "imaging: scanner, printer");
+    }
+
+    #[test]
+    fn rejects_invalid_classes() {
+        assert_eq!(ClassOfDevice::decode(0xff000000), None);
+        assert_eq!(ClassOfDevice::decode(0x000101), None);
+        let uncategorized = ClassOfDevice::decode(0x001f00).unwrap();
+        assert_eq!(uncategorized.minor, MinorClass::Unassigned(0));
+        assert_eq!(uncategorized.to_string(), "uncategorized");
+    }
+}
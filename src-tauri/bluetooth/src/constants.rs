@@ -0,0 +1,674 @@
+//! Bluetooth SIG assigned numbers and well-known vendor UUIDs, see [`crate::uuids`] for the
+//! lookup.
+
+use std::collections::HashMap;
+
+use bluer::Uuid;
+use lazy_static::lazy_static;
+
+lazy_static! {
+    /// Protocols, 0x0001 to 0x0100
+    pub static ref PROTOCOL_UUIDS: HashMap<u16, &'static str> = [
+        (0x0001, "SDP"),
+        (0x0002, "UDP"),
+        (0x0003, "RFCOMM"),
+        (0x0004, "TCP"),
+        (0x0005, "TCS-BIN"),
+        (0x0006, "TCS-AT"),
+        (0x0007, "ATT"),
+        (0x0008, "OBEX"),
+        (0x0009, "IP"),
+        (0x000a, "FTP"),
+        (0x000c, "HTTP"),
+        (0x000e, "WSP"),
+        (0x000f, "BNEP"),
+        (0x0010, "UPnP/ESDP"),
+        (0x0011, "HIDP"),
+        (0x0012, "Hardcopy Control Channel"),
+        (0x0014, "Hardcopy Data Channel"),
+        (0x0016, "Hardcopy Notification"),
+        (0x0017, "AVCTP"),
+        (0x0019, "AVDTP"),
+        (0x001b, "CMTP"),
+        (0x001d, "UDI_C-Plane"),
+        (0x001e, "Multi-Channel Adaptation Protocol (MCAP)"),
+        (0x001f, "Multi-Channel Adaptation Protocol (MCAP)"),
+        (0x0100, "L2CAP"),
+    ]
+    .iter()
+    .cloned()
+    .collect();
+    /// Service classes and profiles, mostly BR/EDR ones found through SDP
+    pub static ref SERVICE_CLASS_UUIDS: HashMap<u16, &'static str> = [
+        (0x1000, "ServiceDiscoveryServerServiceClassID"),
+        (0x1001, "BrowseGroupDescriptorServiceClassID"),
+        (0x1002, "Public Browse Group"),
+        (0x1101, "Serial Port"),
+        (0x1102, "LAN Access Using PPP"),
+        (0x1103, "Dialup Networking (DUN)"),
+        (0x1104, "IrMC Sync"),
+        (0x1105, "OBEX Object Push"),
+        (0x1106, "OBEX File Transfer"),
+        (0x1107, "IrMC Sync Command"),
+        (0x1108, "Headset"),
+        (0x1109, "Cordless Telephony"),
+        (0x110a, "Audio Source"),
+        (0x110b, "Audio Sink"),
+        (0x110c, "Remote Control Target"),
+        (0x110d, "Advanced Audio"),
+        (0x110e, "Remote Control"),
+        (0x110f, "Video Conferencing"),
+        (0x1110, "Intercom"),
+        (0x1111, "Fax"),
+        (0x1112, "Headset Audio Gateway"),
+        (0x1113, "WAP"),
+        (0x1114, "WAP Client"),
+        (0x1115, "PANU"),
+        (0x1116, "Network Access Point"),
+        (0x1117, "Group Network"),
+        (0x1118, "DirectPrinting (BPP)"),
+        (0x1119, "ReferencePrinting (BPP)"),
+        (0x111a, "Imaging (BIP)"),
+        (0x111b, "ImagingResponder (BIP)"),
+        (0x111c, "ImagingAutomaticArchive (BIP)"),
+        (0x111d, "ImagingReferencedObjects (BIP)"),
+        (0x111e, "Handsfree"),
+        (0x111f, "Handsfree Audio Gateway"),
+        (0x1120, "DirectPrintingReferenceObjectsService (BPP)"),
+        (0x1121, "ReflectedUI (BPP)"),
+        (0x1122, "Basic Printing (BPP)"),
+        (0x1123, "Printing Status (BPP)"),
+        (0x1124, "Human Interface Device Service (HID)"),
+        (0x1125, "HardcopyCableReplacement (HCR)"),
+        (0x1126, "HCR_Print (HCR)"),
+        (0x1127, "HCR_Scan (HCR)"),
+        (0x1128, "Common ISDN Access (CIP)"),
+        (0x1129, "VideoConferencingGW (VCP)"),
+        (0x112a, "UDI-MT"),
+        (0x112b, "UDI-TA"),
+        (0x112c, "Audio/Video"),
+        (0x112d, "SIM Access (SAP)"),
+        (0x112e, "Phonebook Access (PBAP)"),
+        (0x112f, "Phonebook Access (PBAP) - PCE"),
+        (0x1130, "Phonebook Access (PBAP)"),
+        (0x1131, "Headset"),
+        (0x1132, "Message Access Server"),
+        (0x1133, "Message Notification Server"),
+        (0x1134, "Message Access Profile (MAP)"),
+        (0x1135, "GNSS"),
+        (0x1136, "GNSS Server"),
+        (0x1137, "3D Display"),
+        (0x1138, "3D Glasses"),
+        (0x1139, "3D Synchronization (3DSP)"),
+        (0x113a, "Multi-Profile Specification (MPS) Profile"),
+        (0x113b, "Multi-Profile Specification (MPS) Service"),
+        (0x113c, "Calendar, Task, and Notes (CTN) Access Service"),
+        (0x113d, "Calendar, Task, and Notes (CTN) Notification Service"),
+        (0x113e, "Calendar, Task, and Notes (CTN) Profile"),
+        (0x1200, "PnP Information"),
+        (0x1201, "Generic Networking"),
+        (0x1202, "Generic FileTransfer"),
+        (0x1203, "Generic Audio"),
+        (0x1204, "Generic Telephony"),
+        (0x1303, "Video Source"),
+        (0x1304, "Video Sink"),
+        (0x1305, "Video Distribution"),
+        (0x1400, "HDP"),
+        (0x1401, "HDP Source"),
+        (0x1402, "HDP Sink"),
+        (0x2112, "AppleAgent"),
+    ]
+    .iter()
+    .cloned()
+    .collect();
+    /// GATT services
+    pub static ref GATT_SERVICE_UUIDS: HashMap<u16, &'static str> = [
+        (0x1800, "Generic Access"),
+        (0x1801, "Generic Attribute"),
+        (0x1802, "Immediate Alert"),
+        (0x1803, "Link Loss"),
+        (0x1804, "Tx Power"),
+        (0x1805, "Current Time Service"),
+        (0x1806, "Reference Time Update Service"),
+        (0x1807, "Next DST Change Service"),
+        (0x1808, "Glucose"),
+        (0x1809, "Health Thermometer"),
+        (0x180a, "Device Information"),
+        (0x180d, "Heart Rate"),
+        (0x180e, "Phone Alert Status Service"),
+        (0x180f, "Battery Service"),
+        (0x1810, "Blood Pressure"),
+        (0x1811, "Alert Notification Service"),
+        (0x1812, "Human Interface Device"),
+        (0x1813, "Scan Parameters"),
+        (0x1814, "Running Speed and Cadence"),
+        (0x1815, "Automation IO"),
+        (0x1816, "Cycling Speed and Cadence"),
+        (0x1818, "Cycling Power"),
+        (0x1819, "Location and Navigation"),
+        (0x181a, "Environmental Sensing"),
+        (0x181b, "Body Composition"),
+        (0x181c, "User Data"),
+        (0x181d, "Weight Scale"),
+        (0x181e, "Bond Management"),
+        (0x181f, "Continuous Glucose Monitoring"),
+        (0x1820, "Internet Protocol Support"),
+        (0x1821, "Indoor Positioning"),
+        (0x1822, "Pulse Oximeter"),
+        (0x1823, "HTTP Proxy"),
+        (0x1824, "Transport Discovery"),
+        (0x1825, "Object Transfer"),
+        (0x1826, "Fitness Machine"),
+        (0x1827, "Mesh Provisioning"),
+        (0x1828, "Mesh Proxy"),
+        (0x1829, "Reconnection Configuration"),
+        (0x183a, "Insulin Delivery"),
+        (0x183b, "Binary Sensor"),
+        (0x183c, "Emergency Configuration"),
+        (0x183e, "Physical Activity Monitor"),
+        (0x1843, "Audio Input Control"),
+        (0x1844, "Volume Control"),
+        (0x1845, "Volume Offset Control"),
+        (0x1846, "Coordinated Set Identification"),
+        (0x1847, "Device Time"),
+        (0x1848, "Media Control"),
+        (0x1849, "Generic Media Control"),
+        (0x184a, "Constant Tone Extension"),
+        (0x184b, "Telephone Bearer"),
+        (0x184c, "Generic Telephone Bearer"),
+        (0x184d, "Microphone Control"),
+        (0x184e, "Audio Stream Control"),
+        (0x184f, "Broadcast Audio Scan"),
+        (0x1850, "Published Audio Capabilities"),
+        (0x1851, "Basic Audio Announcement"),
+        (0x1852, "Broadcast Audio Announcement"),
+        (0x1853, "Common Audio"),
+        (0x1854, "Hearing Access"),
+        (0x1855, "Telephony and Media Audio"),
+        (0x1856, "Public Broadcast Announcement"),
+    ]
+    .iter()
+    .cloned()
+    .collect();
+    /// GATT attribute types of the service and characteristic declarations
+    pub static ref DECLARATION_UUIDS: HashMap<u16, &'static str> = [
+        (0x2800, "Primary Service"),
+        (0x2801, "Secondary Service"),
+        (0x2802, "Include"),
+        (0x2803, "Characteristic Declaration"),
+    ]
+    .iter()
+    .cloned()
+    .collect();
+    /// GATT characteristic descriptors
+    pub static ref DESCRIPTOR_UUIDS: HashMap<u16, &'static str> = [
+        (0x2900, "Characteristic Extended Properties"),
+        (0x2901, "Characteristic User Description"),
+        (0x2902, "Client Characteristic Configuration"),
+        (0x2903, "Server Characteristic Configuration"),
+        (0x2904, "Characteristic Presentation Format"),
+        (0x2905, "Characteristic Aggregate Format"),
+        (0x2906, "Valid Range"),
+        (0x2907, "External Report Reference"),
+        (0x2908, "Report Reference"),
+        (0x2909, "Number of Digitals"),
+        (0x290a, "Value Trigger Setting"),
+        (0x290b, "Environmental Sensing Configuration"),
+        (0x290c, "Environmental Sensing Measurement"),
+        (0x290d, "Environmental Sensing Trigger Setting"),
+        (0x290e, "Time Trigger Setting"),
+        (0x290f, "Complete BR-EDR Transport Block Data"),
+        (0x2910, "Observation Schedule"),
+        (0x2911, "Valid Range and Accuracy"),
+    ]
+    .iter()
+    .cloned()
+    .collect();
+    /// GATT characteristics
+    pub static ref CHARACTERISTIC_UUIDS: HashMap<u16, &'static str> = [
+        (0x2a00, "Device Name"),
+        (0x2a01, "Appearance"),
+        (0x2a02, "Peripheral Privacy Flag"),
+        (0x2a03, "Reconnection Address"),
+        (0x2a04, "Peripheral Preferred Connection Parameters"),
+        (0x2a05, "Service Changed"),
+        (0x2a06, "Alert Level"),
+        (0x2a07, "Tx Power Level"),
+        (0x2a08, "Date Time"),
+        (0x2a09, "Day of Week"),
+        (0x2a0a, "Day Date Time"),
+        (0x2a0b, "Exact Time 100"),
+        (0x2a0c, "Exact Time 256"),
+        (0x2a0d, "DST Offset"),
+        (0x2a0e, "Time Zone"),
+        (0x2a0f, "Local Time Information"),
+        (0x2a10, "Secondary Time Zone"),
+        (0x2a11, "Time with DST"),
+        (0x2a12, "Time Accuracy"),
+        (0x2a13, "Time Source"),
+        (0x2a14, "Reference Time Information"),
+        (0x2a15, "Time Broadcast"),
+        (0x2a16, "Time Update Control Point"),
+        (0x2a17, "Time Update State"),
+        (0x2a18, "Glucose Measurement"),
+        (0x2a19, "Battery Level"),
+        (0x2a1a, "Battery Power State"),
+        (0x2a1b, "Battery Level State"),
+        (0x2a1c, "Temperature Measurement"),
+        (0x2a1d, "Temperature Type"),
+        (0x2a1e, "Intermediate Temperature"),
+        (0x2a1f, "Temperature Celsius"),
+        (0x2a20, "Temperature Fahrenheit"),
+        (0x2a21, "Measurement Interval"),
+        (0x2a22, "Boot Keyboard Input Report"),
+        (0x2a23, "System ID"),
+        (0x2a24, "Model Number String"),
+        (0x2a25, "Serial Number String"),
+        (0x2a26, "Firmware Revision String"),
+        (0x2a27, "Hardware Revision String"),
+        (0x2a28, "Software Revision String"),
+        (0x2a29, "Manufacturer Name String"),
+        (0x2a2a, "IEEE 11073-20601 Regulatory Certification Data List"),
+        (0x2a2b, "Current Time"),
+        (0x2a2c, "Magnetic Declination"),
+        (0x2a2f, "Position 2D"),
+        (0x2a30, "Position 3D"),
+        (0x2a31, "Scan Refresh"),
+        (0x2a32, "Boot Keyboard Output Report"),
+        (0x2a33, "Boot Mouse Input Report"),
+        (0x2a34, "Glucose Measurement Context"),
+        (0x2a35, "Blood Pressure Measurement"),
+        (0x2a36, "Intermediate Cuff Pressure"),
+        (0x2a37, "Heart Rate Measurement"),
+        (0x2a38, "Body Sensor Location"),
+        (0x2a39, "Heart Rate Control Point"),
+        (0x2a3a, "Removable"),
+        (0x2a3b, "Service Required"),
+        (0x2a3c, "Scientific Temperature Celsius"),
+        (0x2a3d, "String"),
+        (0x2a3e, "Network Availability"),
+        (0x2a3f, "Alert Status"),
+        (0x2a40, "Ringer Control Point"),
+        (0x2a41, "Ringer Setting"),
+        (0x2a42, "Alert Category ID Bit Mask"),
+        (0x2a43, "Alert Category ID"),
+        (0x2a44, "Alert Notification Control Point"),
+        (0x2a45, "Unread Alert Status"),
+        (0x2a46, "New Alert"),
+        (0x2a47, "Supported New Alert Category"),
+        (0x2a48, "Supported Unread Alert Category"),
+        (0x2a49, "Blood Pressure Feature"),
+        (0x2a4a, "HID Information"),
+        (0x2a4b, "Report Map"),
+        (0x2a4c, "HID Control Point"),
+        (0x2a4d, "Report"),
+        (0x2a4e, "Protocol Mode"),
+        (0x2a4f, "Scan Interval Window"),
+        (0x2a50, "PnP ID"),
+        (0x2a51, "Glucose Feature"),
+        (0x2a52, "Record Access Control Point"),
+        (0x2a53, "RSC Measurement"),
+        (0x2a54, "RSC Feature"),
+        (0x2a55, "SC Control Point"),
+        (0x2a56, "Digital"),
+        (0x2a57, "Digital Output"),
+        (0x2a58, "Analog"),
+        (0x2a59, "Analog Output"),
+        (0x2a5a, "Aggregate"),
+        (0x2a5b, "CSC Measurement"),
+        (0x2a5c, "CSC Feature"),
+        (0x2a5d, "Sensor Location"),
+        (0x2a5e, "PLX Spot-Check Measurement"),
+        (0x2a5f, "PLX Continuous Measurement"),
+        (0x2a60, "PLX Features"),
+        (0x2a62, "Pulse Oximetry Control Point"),
+        (0x2a63, "Cycling Power Measurement"),
+        (0x2a64, "Cycling Power Vector"),
+        (0x2a65, "Cycling Power Feature"),
+        (0x2a66, "Cycling Power Control Point"),
+        (0x2a67, "Location and Speed"),
+        (0x2a68, "Navigation"),
+        (0x2a69, "Position Quality"),
+        (0x2a6a, "LN Feature"),
+        (0x2a6b, "LN Control Point"),
+        (0x2a6c, "Elevation"),
+        (0x2a6d, "Pressure"),
+        (0x2a6e, "Temperature"),
+        (0x2a6f, "Humidity"),
+        (0x2a70, "True Wind Speed"),
+        (0x2a71, "True Wind Direction"),
+        (0x2a72, "Apparent Wind Speed"),
+        (0x2a73, "Apparent Wind Direction"),
+        (0x2a74, "Gust Factor"),
+        (0x2a75, "Pollen Concentration"),
+        (0x2a76, "UV Index"),
+        (0x2a77, "Irradiance"),
+        (0x2a78, "Rainfall"),
+        (0x2a79, "Wind Chill"),
+        (0x2a7a, "Heat Index"),
+        (0x2a7b, "Dew Point"),
+        (0x2a7d, "Descriptor Value Changed"),
+        (0x2a7e, "Aerobic Heart Rate Lower Limit"),
+        (0x2a7f, "Aerobic Threshold"),
+        (0x2a80, "Age"),
+        (0x2a81, "Anaerobic Heart Rate Lower Limit"),
+        (0x2a82, "Anaerobic Heart Rate Upper Limit"),
+        (0x2a83, "Anaerobic Threshold"),
+        (0x2a84, "Aerobic Heart Rate Upper Limit"),
+        (0x2a85, "Date of Birth"),
+        (0x2a86, "Date of Threshold Assessment"),
+        (0x2a87, "Email Address"),
+        (0x2a88, "Fat Burn Heart Rate Lower Limit"),
+        (0x2a89, "Fat Burn Heart Rate Upper Limit"),
+        (0x2a8a, "First Name"),
+        (0x2a8b, "Five Zone Heart Rate Limits"),
+        (0x2a8c, "Gender"),
+        (0x2a8d, "Heart Rate Max"),
+        (0x2a8e, "Height"),
+        (0x2a8f, "Hip Circumference"),
+        (0x2a90, "Last Name"),
+        (0x2a91, "Maximum Recommended Heart Rate"),
+        (0x2a92, "Resting Heart Rate"),
+        (0x2a93, "Sport Type for Aerobic and Anaerobic Thresholds"),
+        (0x2a94, "Three Zone Heart Rate Limits"),
+        (0x2a95, "Two Zone Heart Rate Limits"),
+        (0x2a96, "VO2 Max"),
+        (0x2a97, "Waist Circumference"),
+        (0x2a98, "Weight"),
+        (0x2a99, "Database Change Increment"),
+        (0x2a9a, "User Index"),
+        (0x2a9b, "Body Composition Feature"),
+        (0x2a9c, "Body Composition Measurement"),
+        (0x2a9d, "Weight Measurement"),
+        (0x2a9e, "Weight Scale Feature"),
+        (0x2a9f, "User Control Point"),
+        (0x2aa0, "Magnetic Flux Density - 2D"),
+        (0x2aa1, "Magnetic Flux Density - 3D"),
+        (0x2aa2, "Language"),
+        (0x2aa3, "Barometric Pressure Trend"),
+        (0x2aa4, "Bond Management Control Point"),
+        (0x2aa5, "Bond Management Feature"),
+        (0x2aa6, "Central Address Resolution"),
+        (0x2aa7, "CGM Measurement"),
+        (0x2aa8, "CGM Feature"),
+        (0x2aa9, "CGM Status"),
+        (0x2aaa, "CGM Session Start Time"),
+        (0x2aab, "CGM Session Run Time"),
+        (0x2aac, "CGM Specific Ops Control Point"),
+        (0x2aad, "Indoor Positioning Configuration"),
+        (0x2aae, "Latitude"),
+        (0x2aaf, "Longitude"),
+        (0x2ab0, "Local North Coordinate"),
+        (0x2ab1, "Local East Coordinate"),
+        (0x2ab2, "Floor Number"),
+        (0x2ab3, "Altitude"),
+        (0x2ab4, "Uncertainty"),
+        (0x2ab5, "Location Name"),
+        (0x2ab6, "URI"),
+        (0x2ab7, "HTTP Headers"),
+        (0x2ab8, "HTTP Status Code"),
+        (0x2ab9, "HTTP Entity Body"),
+        (0x2aba, "HTTP Control Point"),
+        (0x2abb, "HTTPS Security"),
+        (0x2abc, "TDS Control Point"),
+        (0x2abd, "OTS Feature"),
+        (0x2abe, "Object Name"),
+        (0x2abf, "Object Type"),
+        (0x2ac0, "Object Size"),
+        (0x2ac1, "Object First-Created"),
+        (0x2ac2, "Object Last-Modified"),
+        (0x2ac3, "Object ID"),
+        (0x2ac4, "Object Properties"),
+        (0x2ac5, "Object Action Control Point"),
+        (0x2ac6, "Object List Control Point"),
+        (0x2ac7, "Object List Filter"),
+        (0x2ac8, "Object Changed"),
+        (0x2ac9, "Resolvable Private Address Only"),
+        (0x2acc, "Fitness Machine Feature"),
+        (0x2acd, "Treadmill Data"),
+        (0x2ace, "Cross Trainer Data"),
+        (0x2acf, "Step Climber Data"),
+        (0x2ad0, "Stair Climber Data"),
+        (0x2ad1, "Rower Data"),
+        (0x2ad2, "Indoor Bike Data"),
+        (0x2ad3, "Training Status"),
+        (0x2ad4, "Supported Speed Range"),
+        (0x2ad5, "Supported Inclination Range"),
+        (0x2ad6, "Supported Resistance Level Range"),
+        (0x2ad7, "Supported Heart Rate Range"),
+        (0x2ad8, "Supported Power Range"),
+        (0x2ad9, "Fitness Machine Control Point"),
+        (0x2ada, "Fitness Machine Status"),
+        (0x2adb, "Mesh Provisioning Data In"),
+        (0x2adc, "Mesh Provisioning Data Out"),
+        (0x2add, "Mesh Proxy Data In"),
+        (0x2ade, "Mesh Proxy Data Out"),
+        (0x2ae0, "Average Current"),
+        (0x2ae1, "Average Voltage"),
+        (0x2ae2, "Boolean"),
+        (0x2ae3, "Chromatic Distance from Planckian"),
+        (0x2ae4, "Chromaticity Coordinates"),
+        (0x2ae5, "Chromaticity in CCT and Duv Values"),
+        (0x2ae6, "Chromaticity Tolerance"),
+        (0x2ae7, "CIE 13.3-1995 Color Rendering Index"),
+        (0x2ae8, "Coefficient"),
+        (0x2ae9, "Correlated Color Temperature"),
+        (0x2aea, "Count 16"),
+        (0x2aeb, "Count 24"),
+        (0x2aec, "Country Code"),
+        (0x2aed, "Date UTC"),
+        (0x2aee, "Electric Current"),
+        (0x2aef, "Electric Current Range"),
+        (0x2af0, "Electric Current Specification"),
+        (0x2af1, "Electric Current Statistics"),
+        (0x2af2, "Energy"),
+        (0x2af3, "Energy in a Period of Day"),
+        (0x2af4, "Event Statistics"),
+        (0x2af5, "Fixed String 16"),
+        (0x2af6, "Fixed String 24"),
+        (0x2af7, "Fixed String 36"),
+        (0x2af8, "Fixed String 8"),
+        (0x2af9, "Generic Level"),
+        (0x2afa, "Global Trade Item Number"),
+        (0x2afb, "Illuminance"),
+        (0x2afc, "Luminous Efficacy"),
+        (0x2afd, "Luminous Energy"),
+        (0x2afe, "Luminous Exposure"),
+        (0x2aff, "Luminous Flux"),
+        (0x2b00, "Luminous Flux Range"),
+        (0x2b01, "Luminous Intensity"),
+        (0x2b02, "Mass Flow"),
+        (0x2b03, "Perceived Lightness"),
+        (0x2b04, "Percentage 8"),
+        (0x2b05, "Power"),
+        (0x2b06, "Power Specification"),
+        (0x2b07, "Relative Runtime in a Current Range"),
+        (0x2b08, "Relative Runtime in a Generic Level Range"),
+        (0x2b09, "Relative Value in a Voltage Range"),
+        (0x2b0a, "Relative Value in an Illuminance Range"),
+        (0x2b0b, "Relative Value in a Period of Day"),
+        (0x2b0c, "Relative Value in a Temperature Range"),
+        (0x2b0d, "Temperature 8"),
+        (0x2b0e, "Temperature 8 in a Period of Day"),
+        (0x2b0f, "Temperature 8 Statistics"),
+        (0x2b10, "Temperature Range"),
+        (0x2b11, "Temperature Statistics"),
+        (0x2b12, "Time Decihour 8"),
+        (0x2b13, "Time Exponential 8"),
+        (0x2b14, "Time Hour 24"),
+        (0x2b15, "Time Millisecond 24"),
+        (0x2b16, "Time Second 16"),
+        (0x2b17, "Time Second 8"),
+        (0x2b18, "Voltage"),
+        (0x2b19, "Voltage Specification"),
+        (0x2b1a, "Voltage Statistics"),
+        (0x2b1b, "Volume Flow"),
+        (0x2b1c, "Chromaticity Coordinate"),
+        (0x2b1d, "RC Feature"),
+        (0x2b1e, "RC Settings"),
+        (0x2b1f, "Reconnection Configuration Control Point"),
+        (0x2b20, "IDD Status Changed"),
+        (0x2b21, "IDD Status"),
+        (0x2b22, "IDD Annunciation Status"),
+        (0x2b23, "IDD Features"),
+        (0x2b24, "IDD Status Reader Control Point"),
+        (0x2b25, "IDD Command Control Point"),
+        (0x2b26, "IDD Command Data"),
+        (0x2b27, "IDD Record Access Control Point"),
+        (0x2b28, "IDD History Data"),
+        (0x2b29, "Client Supported Features"),
+        (0x2b2a, "Database Hash"),
+        (0x2b2b, "BSS Control Point"),
+        (0x2b2c, "BSS Response"),
+        (0x2b2d, "Emergency ID"),
+        (0x2b2e, "Emergency Text"),
+        (0x2b34, "Enhanced Blood Pressure Measurement"),
+        (0x2b35, "Enhanced Intermediate Cuff Pressure"),
+        (0x2b36, "Blood Pressure Record"),
+        (0x2b37, "Registered User"),
+        (0x2b38, "BR-EDR Handover Data"),
+        (0x2b39, "Bluetooth SIG Data"),
+        (0x2b3a, "Server Supported Features"),
+        (0x2b7d, "Volume State"),
+        (0x2b7e, "Volume Control Point"),
+        (0x2b7f, "Volume Flags"),
+        (0x2b84, "Set Identity Resolving Key"),
+        (0x2b85, "Coordinated Set Size"),
+        (0x2b86, "Set Member Lock"),
+        (0x2b87, "Set Member Rank"),
+        (0x2bc3, "Mute"),
+        (0x2bc4, "Sink ASE"),
+        (0x2bc5, "Source ASE"),
+        (0x2bc6, "ASE Control Point"),
+        (0x2bc7, "Broadcast Audio Scan Control Point"),
+        (0x2bc8, "Broadcast Receive State"),
+        (0x2bc9, "Sink PAC"),
+        (0x2bca, "Sink Audio Locations"),
+        (0x2bcb, "Source PAC"),
+        (0x2bcc, "Source Audio Locations"),
+        (0x2bcd, "Available Audio Contexts"),
+        (0x2bce, "Supported Audio Contexts"),
+    ]
+    .iter()
+    .cloned()
+    .collect();
+    /// UUIDs assigned to standards development organizations
+    pub static ref SDO_UUIDS: HashMap<u16, &'static str> = [
+        (0xfffa, "ASTM Remote ID"),
+        (0xfffc, "AirFuel Alliance"),
+        (0xfffd, "Fast IDentity Online Alliance (FIDO)"),
+        (0xfffe, "Alliance for Wireless Power (A4WP)"),
+    ]
+    .iter()
+    .cloned()
+    .collect();
+    /// Companies of the 16 bit UUIDs assigned to SIG members, 0xFD00 to 0xFEFF. Only the
+    /// members whose UUIDs are commonly seen are listed, the others are still told apart as
+    /// member UUIDs by their range, just without a company
+    pub static ref MEMBER_UUIDS: HashMap<u16, &'static str> = [
+        (0xfd5a, "Samsung Electronics Co., Ltd."),
+        (0xfd6f, "Apple, Inc."),
+        (0xfe03, "Amazon.com Services, Inc."),
+        (0xfe07, "Sonos, Inc."),
+        (0xfe0f, "Signify Netherlands B.V."),
+        (0xfe1f, "Garmin International, Inc."),
+        (0xfe25, "Apple, Inc."),
+        (0xfe2c, "Google LLC"),
+        (0xfe59, "Nordic Semiconductor ASA"),
+        (0xfe8f, "CSR"),
+        (0xfe95, "Xiaomi Inc."),
+        (0xfe9a, "Estimote"),
+        (0xfe9f, "Google LLC"),
+        (0xfea0, "Google LLC"),
+        (0xfea6, "GoPro, Inc."),
+        (0xfeaa, "Google LLC"),
+        (0xfeaf, "Nest Labs Inc."),
+        (0xfeb2, "Microsoft Corporation"),
+        (0xfeb8, "Facebook, Inc."),
+        (0xfebe, "Bose Corporation"),
+        (0xfec7, "Apple, Inc."),
+        (0xfec8, "Apple, Inc."),
+        (0xfec9, "Apple, Inc."),
+        (0xfeca, "Apple, Inc."),
+        (0xfecb, "Apple, Inc."),
+        (0xfecc, "Apple, Inc."),
+        (0xfecd, "Apple, Inc."),
+        (0xfece, "Apple, Inc."),
+        (0xfecf, "Apple, Inc."),
+        (0xfed0, "Apple, Inc."),
+        (0xfed1, "Apple, Inc."),
+        (0xfed2, "Apple, Inc."),
+        (0xfed3, "Apple, Inc."),
+        (0xfed4, "Apple, Inc."),
+        (0xfee0, "Anhui Huami Information Technology Co., Ltd."),
+        (0xfee7, "Tencent Holdings Limited"),
+        (0xfeec, "Tile, Inc."),
+        (0xfeed, "Tile, Inc."),
+        (0xfef3, "Google LLC"),
+        (0xfef5, "Dialog Semiconductor GmbH"),
+        (0xfefd, "Gimbal, Inc."),
+        (0xfefe, "GN ReSound A/S"),
+        (0xfeff, "GN Netcom"),
+    ]
+    .iter()
+    .cloned()
+    .collect();
+    /// Well-known 128 bit UUIDs of vendors, with the vendor and the name of the UUID
+    pub static ref VENDOR_UUIDS: HashMap<Uuid, (&'static str, &'static str)> = [
+        ("6e400001-b5a3-f393-e0a9-e50e24dcca9e", "Nordic Semiconductor ASA", "Nordic UART Service"),
+        ("6e400002-b5a3-f393-e0a9-e50e24dcca9e", "Nordic Semiconductor ASA", "Nordic UART RX"),
+        ("6e400003-b5a3-f393-e0a9-e50e24dcca9e", "Nordic Semiconductor ASA", "Nordic UART TX"),
+        ("00001523-1212-efde-1523-785feabcd123", "Nordic Semiconductor ASA", "LED Button Service"),
+        ("00001530-1212-efde-1523-785feabcd123", "Nordic Semiconductor ASA", "Legacy DFU Service"),
+        (
+            "00001531-1212-efde-1523-785feabcd123",
+            "Nordic Semiconductor ASA",
+            "Legacy DFU Control Point",
+        ),
+        ("00001532-1212-efde-1523-785feabcd123", "Nordic Semiconductor ASA", "Legacy DFU Packet"),
+        (
+            "8ec90001-f315-4f60-9fb8-838830daea50",
+            "Nordic Semiconductor ASA",
+            "Secure DFU Control Point",
+        ),
+        ("8ec90002-f315-4f60-9fb8-838830daea50", "Nordic Semiconductor ASA", "Secure DFU Packet"),
+        ("8ec90003-f315-4f60-9fb8-838830daea50", "Nordic Semiconductor ASA", "Buttonless DFU"),
+        (
+            "7905f431-b5ce-4e99-a40f-4b1e122d00d0",
+            "Apple, Inc.",
+            "Apple Notification Center Service",
+        ),
+        ("9fbf120d-6301-42d9-8c58-25e699a21dbd", "Apple, Inc.", "ANCS Notification Source"),
+        ("69d1d8f3-45e1-49a8-9821-9bbdfdaad9d9", "Apple, Inc.", "ANCS Control Point"),
+        ("22eac6e9-24d6-4bb5-be44-b36ace7c7bfb", "Apple, Inc.", "ANCS Data Source"),
+        ("89d3502b-0f36-433a-8ef4-c502ad55f8dc", "Apple, Inc.", "Apple Media Service"),
+        ("9b3c81d8-57b1-4a8a-b8df-0e56f7ca51c2", "Apple, Inc.", "AMS Remote Command"),
+        ("2f7cabce-808d-411f-9a0c-bb92ba96c102", "Apple, Inc.", "AMS Entity Update"),
+        ("c6b2f38c-23ab-46d8-a6ab-a3a870bbd5d7", "Apple, Inc.", "AMS Entity Attribute"),
+        ("d0611e78-bbb4-4591-a5f8-487910ae4366", "Apple, Inc.", "Continuity Service"),
+        ("9fa480e0-4967-4542-9390-d343dc5d04ae", "Apple, Inc.", "Nearby Service"),
+        ("a3c87500-8ed3-4bdf-8a39-a01bebede295", "Google LLC", "Eddystone Configuration Service"),
+        ("fe2c1233-8366-4814-8eb0-01de32100bea", "Google LLC", "Fast Pair Model ID"),
+        ("fe2c1234-8366-4814-8eb0-01de32100bea", "Google LLC", "Fast Pair Key-based Pairing"),
+        ("fe2c1235-8366-4814-8eb0-01de32100bea", "Google LLC", "Fast Pair Passkey"),
+        ("fe2c1236-8366-4814-8eb0-01de32100bea", "Google LLC", "Fast Pair Account Key"),
+        (
+            "49535343-fe7d-4ae5-8fa9-9fafd205e455",
+            "Microchip Technology Inc.",
+            "Transparent UART Service",
+        ),
+        (
+            "f000aa00-0451-4000-b000-000000000000",
+            "Texas Instruments Inc.",
+            "SensorTag IR Temperature Service",
+        ),
+        ("f000ffc0-0451-4000-b000-000000000000", "Texas Instruments Inc.", "OAD Service"),
+        ("1d14d6ee-fd63-4fa1-bfa4-8f47b42119f0", "Silicon Laboratories", "OTA Service"),
+    ]
+    .iter()
+    .map(|(uuid, vendor, name)| {
+        let uuid = Uuid::parse_str(uuid).expect("vendor UUIDs are valid");
+        (uuid, (*vendor, *name))
+    })
+    .collect();
+}
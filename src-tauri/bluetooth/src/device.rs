@@ -0,0 +1,311 @@
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use super::{
+    backend::DeviceBackend,
+    class_of_device::ClassOfDevice,
+    error::Result,
+    media::{MediaCommand, MediaPlayerInfo, AVRCP_UUIDS},
+    network::{NetworkInfo, PanRole},
+    profiles::Profile,
+    transport::TransportInfo,
+    uuids::{self, UuidInfo},
+};
+use bluer::{self, Address, DeviceProperty, Uuid, UuidExt};
+use log::debug;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// A remote device together with its latest known state
+#[derive(Debug, Clone)]
+pub struct Device {
+    address: Address,
+    info: DeviceInfo,
+    device_handle: Arc<dyn DeviceBackend>,
+}
+
+impl Device {
+    pub async fn new(device: Arc<dyn DeviceBackend>) -> Result<Self> {
+        let device_properties = device.all_properties().await?;
+        let mut device_info = DeviceInfo::from_properties(device_properties);
+        device_info.discovered_adapter_name = device.adapter_name().into();
+        device_info.address_string = device.address().to_string();
+        device_info.address = device.address().0;
+        Ok(Self {
+            address: device.address(),
+            info: device_info,
+            device_handle: device,
+        })
+    }
+
+    pub fn address(&self) -> &Address {
+        &self.address
+    }
+
+    pub fn device_info(&self) -> &DeviceInfo {
+        &self.info
+    }
+
+    pub fn update_device_property(&mut self, property: DeviceProperty) -> DevicePatch {
+        let previous = self.info.clone();
+        self.info.update_property(property);
+        DevicePatch::between(&previous, &self.info)
+    }
+
+    pub fn update_media_player(&mut self, player: Option<MediaPlayerInfo>) -> DevicePatch {
+        let previous = self.info.clone();
+        self.info.media_player = player;
+        DevicePatch::between(&previous, &self.info)
+    }
+
+    pub fn update_media_transport(&mut self, transport: Option<TransportInfo>) -> DevicePatch {
+        let previous = self.info.clone();
+        self.info.transport = transport;
+        DevicePatch::between(&previous, &self.info)
+    }
+
+    pub fn update_tags(&mut self, tags: Vec<String>) -> DevicePatch {
+        let previous = self.info.clone();
+        self.info.tags = tags;
+        DevicePatch::between(&previous, &self.info)
+    }
+
+    pub fn update_network(&mut self, network: Option<NetworkInfo>) -> DevicePatch {
+        let previous = self.info.clone();
+        self.info.network = network;
+        DevicePatch::between(&previous, &self.info)
+    }
+
+    /// Forgets the signal strength, BlueZ only reports it while discovering
+    pub fn clear_rssi(&mut self) -> DevicePatch {
+        let previous = self.info.clone();
+        self.info.rssi = None;
+        DevicePatch::between(&previous, &self.info)
+    }
+
+    pub async fn refresh_properties(&mut self) -> Result<&DeviceInfo> {
+        // a device out of range has no RSSI property anymore, keeping the old one would
+        // rank it by a signal it no longer has
+        self.info.rssi = None;
+        for property in self.device_handle.all_properties().await? {
+            self.info.update_property(property);
+        }
+        Ok(&self.info)
+    }
+
+    /// Backend handle of the device, for operations beyond connect, pair and disconnect
+    pub fn backend(&self) -> &Arc<dyn DeviceBackend> {
+        &self.device_handle
+    }
+
+    pub async fn connect(&self) -> Result<()> {
+        self.device_handle.connect().await
+    }
+
+    pub async fn pair(&self) -> Result<()> {
+        self.device_handle.pair().await
+    }
+
+    pub async fn disconnect(&self) -> Result<()> {
+        self.device_handle.disconnect().await
+    }
+
+    pub async fn connect_profile(&self, profile: Profile) -> Result<()> {
+        self.device_handle.connect_profile(profile.uuid()).await
+    }
+
+    pub async fn disconnect_profile(&self, profile: Profile) -> Result<()> {
+        self.device_handle.disconnect_profile(profile.uuid()).await
+    }
+
+    pub async fn media_command(&self, command: MediaCommand) -> Result<()> {
+        self.device_handle.media_command(command).await
+    }
+
+    pub async fn connect_network(&self, role: PanRole) -> Result<String> {
+        self.device_handle.connect_network(role).await
+    }
+
+    pub async fn disconnect_network(&self) -> Result<()> {
+        self.device_handle.disconnect_network().await
+    }
+
+    pub async fn set_volume(&self, volume: u16) -> Result<()> {
+        self.device_handle.set_volume(volume).await
+    }
+}
+
+/// Fields of a [`DeviceInfo`] that changed, keyed by their serialized name
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
+pub struct DevicePatch {
+    pub address: String,
+    pub changes: Map<String, Value>,
+}
+
+impl DevicePatch {
+    pub fn between(previous: &DeviceInfo, current: &DeviceInfo) -> Self {
+        let changes = match (
+            serde_json::to_value(previous).ok(),
+            serde_json::to_value(current).ok(),
+        ) {
+            (Some(Value::Object(previous)), Some(Value::Object(current))) => current
+                .into_iter()
+                .filter(|(field, value)| previous.get(field) != Some(value))
+                .collect(),
+            _ => Map::new(),
+        };
+        DevicePatch {
+            address: current.address_string.clone(),
+            changes,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Folds a newer patch of the same device into this one
+    pub fn merge(&mut self, newer: DevicePatch) {
+        self.changes.extend(newer.changes);
+    }
+}
+
+/// Serializable state of a remote device
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
+pub struct DeviceInfo {
+    pub discovered_adapter_name: String,
+    pub name: String,
+    pub alias: String,
+    pub address: [u8; 6],
+    pub address_string: String,
+    pub address_type: String,
+    pub class: u32,
+    /// None when the class is not a valid Class of Device, e.g. for LE only devices
+    pub class_of_device: Option<ClassOfDevice>,
+    pub uuids: HashMap<String, UuidInfo>,
+    /// Profile roles of the device, see [`Profile::detect`]
+    pub profiles: Vec<Profile>,
+    pub is_paired: bool,
+    pub is_connected: bool,
+    pub is_trusted: bool,
+    pub is_blocked: bool,
+    pub is_wake_allowed: bool,
+    pub is_legacy_pairing: bool,
+    pub battery_percentage: u8,
+    /// Signal strength of the last advertisement or inquiry result, in dBm
+    pub rssi: Option<i16>,
+    /// Seconds since the Unix epoch when the device was last heard, through an
+    /// advertisement or a connection. None when it was not heard since the app started
+    pub last_seen: Option<u64>,
+    /// Labels the user gave the device, sorted
+    pub tags: Vec<String>,
+    /// Payload of the last advertisement keyed by Bluetooth SIG company identifier
+    pub manufacturer_data: HashMap<u16, Vec<u8>>,
+    /// Present while the device exposes an AVRCP media player
+    pub media_player: Option<MediaPlayerInfo>,
+    /// Present while an A2DP stream is configured with the device
+    pub transport: Option<TransportInfo>,
+    /// Present for devices with a PAN service bluetoothd can connect to
+    pub network: Option<NetworkInfo>,
+}
+
+impl DeviceInfo {
+    /// Whether the device advertises one of the AVRCP profiles
+    pub fn supports_media_control(&self) -> bool {
+        self.uuids
+            .keys()
+            .filter_map(|uuid| Uuid::parse_str(uuid).ok()?.as_u16())
+            .any(|uuid| AVRCP_UUIDS.contains(&uuid))
+    }
+
+    /// Whether the device advertises the serial port profile
+    pub fn supports_serial_port(&self) -> bool {
+        self.profiles.contains(&Profile::SerialPort)
+    }
+
+    /// The alias, or the name when there is no alias
+    pub fn display_name(&self) -> &str {
+        if self.alias.is_empty() {
+            &self.name
+        } else {
+            &self.alias
+        }
+    }
+
+    fn seen(&mut self) {
+        self.last_seen = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .ok()
+            .map(|since_epoch| since_epoch.as_secs());
+    }
+
+    fn update_profiles(&mut self) {
+        let uuids: Vec<Uuid> = self
+            .uuids
+            .keys()
+            .filter_map(|uuid| Uuid::parse_str(uuid).ok())
+            .collect();
+        self.profiles = Profile::detect(&uuids, self.class_of_device.as_ref());
+    }
+
+    pub(crate) fn from_properties(device_properties: Vec<DeviceProperty>) -> Self {
+        let mut device_info = DeviceInfo::default();
+        for property in device_properties {
+            device_info.update_property(property);
+        }
+        device_info
+    }
+
+    fn update_property(&mut self, property: DeviceProperty) {
+        match property {
+            DeviceProperty::Name(device_name) => self.name = device_name,
+            DeviceProperty::AddressType(address_type) => {
+                self.address_type = address_type.to_string()
+            }
+            DeviceProperty::Icon(_) => { /* ignored */ }
+            DeviceProperty::Class(class) => {
+                self.class = class;
+                self.class_of_device = ClassOfDevice::decode(class);
+                self.update_profiles();
+            }
+            DeviceProperty::Appearance(_) => { /* ignored */ }
+            DeviceProperty::Uuids(uuids) => {
+                self.uuids = uuids::describe(uuids);
+                self.update_profiles();
+            }
+            DeviceProperty::Paired(paired) => self.is_paired = paired,
+            DeviceProperty::Connected(connected) => {
+                self.is_connected = connected;
+                if connected {
+                    self.seen();
+                }
+            }
+            DeviceProperty::Trusted(trusted) => self.is_trusted = trusted,
+            DeviceProperty::Blocked(blocked) => self.is_blocked = blocked,
+            DeviceProperty::WakeAllowed(wake_allowed) => self.is_wake_allowed = wake_allowed,
+            DeviceProperty::Alias(alias) => self.alias = alias,
+            DeviceProperty::LegacyPairing(legacy_pairing) => {
+                self.is_legacy_pairing = legacy_pairing
+            }
+            DeviceProperty::Modalias(_) => { /* ignored */ }
+            DeviceProperty::Rssi(rssi) => {
+                self.rssi = Some(rssi);
+                self.seen();
+            }
+            DeviceProperty::TxPower(_) => { /* ignored */ }
+            DeviceProperty::ManufacturerData(manufacturer_data) => {
+                self.manufacturer_data = manufacturer_data
+            }
+            DeviceProperty::ServiceData(_) => { /* ignored */ }
+            DeviceProperty::ServicesResolved(_) => { /* ignored */ }
+            DeviceProperty::AdvertisingFlags(_) => { /* ignored */ }
+            DeviceProperty::AdvertisingData(_) => { /* ignored */ }
+            DeviceProperty::BatteryPercentage(percent) => self.battery_percentage = percent,
+            // DeviceProperty is non-exhaustive, properties of newer bluer versions are skipped
+            property => debug!("ignoring device property {:?}", property),
+        }
+    }
+}
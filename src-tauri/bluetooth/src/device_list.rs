@@ -0,0 +1,222 @@
+use std::{collections::HashMap, sync::Arc};
+
+use bluer::{Address, DeviceProperty};
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    adapter::DeviceEvent,
+    backend::AdapterBackend,
+    device::{Device, DeviceInfo, DevicePatch},
+    device_query::{DevicePage, DeviceQuery, SortKey},
+    error::Result,
+    media::MediaPlayerInfo,
+    network::NetworkInfo,
+    transport::TransportInfo,
+};
+
+/// A change to the device list, `seq` increases by one for every change so consumers
+/// can tell when they missed one and need a fresh snapshot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceDelta {
+    pub seq: u64,
+    #[serde(flatten)]
+    pub event: DeviceEvent,
+}
+
+/// Full device list as of the change numbered `seq`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceSnapshot {
+    pub seq: u64,
+    pub devices: Vec<DeviceInfo>,
+}
+
+/// Devices known to an adapter, keyed by address
+pub struct DeviceList {
+    base: HashMap<Address, Device>,
+    adapter_handle: Arc<dyn AdapterBackend>,
+    seq: u64,
+    // kept apart from the devices so tags survive a device being removed and found again
+    tags: HashMap<Address, Vec<String>>,
+}
+
+impl DeviceList {
+    pub async fn new(adapter: Arc<dyn AdapterBackend>) -> Result<Self> {
+        let device_addresses = adapter.device_addresses().await?;
+        let base = futures::future::join_all(device_addresses.into_iter().map(|address| {
+            let adapter = &adapter;
+            async move { Device::new(adapter.device(address)?).await }
+        }))
+        .await
+        .into_iter()
+        .filter_map(|device| match device {
+            Ok(device) => Some((*device.address(), device)),
+            Err(err) => {
+                warn!("could not load device: {}", err);
+                None
+            }
+        })
+        .collect();
+        Ok(Self {
+            base,
+            adapter_handle: adapter,
+            seq: 0,
+            tags: HashMap::new(),
+        })
+    }
+
+    fn next_delta(&mut self, event: DeviceEvent) -> DeviceDelta {
+        self.seq += 1;
+        DeviceDelta {
+            seq: self.seq,
+            event,
+        }
+    }
+
+    /// Adds a new device, or refreshes the properties of one that is already known
+    pub async fn add_device(&mut self, address: Address) -> Result<DeviceDelta> {
+        //self.adapter.adapter_handle.device_addresses()
+        let device = self.base.get_mut(&address);
+        let device_info = if let Some(device) = device {
+            // device property changed
+            device.refresh_properties().await?.clone()
+        } else {
+            // new device found
+            let device_handle = self.adapter_handle.device(address)?;
+            let mut device = Device::new(device_handle).await?;
+            if let Some(tags) = self.tags.get(&address) {
+                device.update_tags(tags.clone());
+            }
+            let device_info = device.device_info().clone();
+            self.base.insert(address, device);
+            device_info
+        };
+        Ok(self.next_delta(DeviceEvent::DeviceAdded {
+            device: device_info,
+        }))
+    }
+
+    pub async fn remove_device(&mut self, address: Address) -> Option<DeviceDelta> {
+        let device = self.base.remove(&address)?;
+        Some(self.next_delta(DeviceEvent::DeviceRemoved {
+            device: device.device_info().clone(),
+        }))
+    }
+
+    /// Returns None when the property change is not visible in [`DeviceInfo`]
+    pub fn update_device(
+        &mut self,
+        address: Address,
+        property: DeviceProperty,
+    ) -> Option<DeviceDelta> {
+        let device = self.base.get_mut(&address)?;
+        let patch = device.update_device_property(property);
+        self.patched(address, patch)
+    }
+
+    /// Returns None when the device is unknown or its player did not change
+    pub fn update_media_player(
+        &mut self,
+        address: Address,
+        player: Option<MediaPlayerInfo>,
+    ) -> Option<DeviceDelta> {
+        let device = self.base.get_mut(&address)?;
+        let patch = device.update_media_player(player);
+        self.patched(address, patch)
+    }
+
+    /// Returns None when the device is unknown or its transport did not change
+    pub fn update_media_transport(
+        &mut self,
+        address: Address,
+        transport: Option<TransportInfo>,
+    ) -> Option<DeviceDelta> {
+        let device = self.base.get_mut(&address)?;
+        let patch = device.update_media_transport(transport);
+        self.patched(address, patch)
+    }
+
+    /// Returns None when the device is unknown or its network did not change
+    pub fn update_network(
+        &mut self,
+        address: Address,
+        network: Option<NetworkInfo>,
+    ) -> Option<DeviceDelta> {
+        let device = self.base.get_mut(&address)?;
+        let patch = device.update_network(network);
+        self.patched(address, patch)
+    }
+
+    /// Forgets the signal strength of every device, once discovery stopped
+    pub fn clear_rssi(&mut self) -> Vec<DeviceDelta> {
+        let mut deltas = vec![];
+        for address in self.addresses() {
+            if let Some(device) = self.base.get_mut(&address) {
+                let patch = device.clear_rssi();
+                deltas.extend(self.patched(address, patch));
+            }
+        }
+        deltas
+    }
+
+    /// Replaces the tags of the device, trimmed, sorted and without duplicates or empty ones.
+    /// Returns None when the device is unknown or its tags did not change
+    pub fn set_tags(&mut self, address: Address, tags: Vec<String>) -> Option<DeviceDelta> {
+        let device = self.base.get_mut(&address)?;
+        let mut tags: Vec<String> = tags
+            .iter()
+            .map(|tag| tag.trim().to_string())
+            .filter(|tag| !tag.is_empty())
+            .collect();
+        tags.sort();
+        tags.dedup();
+        let patch = device.update_tags(tags.clone());
+        if tags.is_empty() {
+            self.tags.remove(&address);
+        } else {
+            self.tags.insert(address, tags);
+        }
+        self.patched(address, patch)
+    }
+
+    fn patched(&mut self, address: Address, patch: DevicePatch) -> Option<DeviceDelta> {
+        if patch.is_empty() {
+            return None;
+        }
+        let device_info = self.base.get(&address)?.device_info().clone();
+        Some(self.next_delta(DeviceEvent::DeviceUpdated {
+            device: device_info,
+            patch,
+        }))
+    }
+
+    pub fn snapshot(&self) -> DeviceSnapshot {
+        DeviceSnapshot {
+            seq: self.seq,
+            devices: self.list(),
+        }
+    }
+
+    pub fn addresses(&self) -> Vec<Address> {
+        self.base.keys().copied().collect()
+    }
+
+    pub fn get(&self, address: &Address) -> Option<&Device> {
+        self.base.get(address)
+    }
+
+    /// Devices connected first, then sorted by name
+    pub fn list(&self) -> Vec<DeviceInfo> {
+        self.query(&DeviceQuery {
+            sort: vec![SortKey::ConnectedFirst, SortKey::Name],
+            ..DeviceQuery::default()
+        })
+        .map(|page| page.devices)
+        .unwrap_or_default()
+    }
+
+    /// Devices matching the filter of the query, sorted and paged
+    pub fn query(&self, query: &DeviceQuery) -> Result<DevicePage> {
+        query.run(self.base.values().map(Device::device_info))
+    }
+}
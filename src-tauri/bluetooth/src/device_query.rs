@@ -0,0 +1,297 @@
+//! Filtering, sorting and paging of the device list.
+//!
+//! A [`DeviceQuery`] runs against the [`DeviceList`](crate::device_list::DeviceList) of the
+//! adapter, so the app does not have to pull every device to show a page of them.
+
+use std::cmp::{Ordering, Reverse};
+
+use bluer::{Uuid, UuidExt};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    class_of_device::MajorClass,
+    device::DeviceInfo,
+    error::{BluetoothError, Result},
+    profiles::Profile,
+};
+
+/// Conditions a device has to meet, unset ones match every device
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeviceFilter {
+    /// Case insensitive part of the name or alias
+    pub text: Option<String>,
+    /// Case insensitive start of the address, like `38:18:4C`
+    pub address_prefix: Option<String>,
+    pub paired: Option<bool>,
+    pub connected: Option<bool>,
+    pub trusted: Option<bool>,
+    pub major_class: Option<MajorClass>,
+    pub profile: Option<Profile>,
+    /// A full UUID or a 16 bit one like `110b` or `0x110b`
+    pub uuid: Option<String>,
+    /// Devices without a known signal strength do not match
+    pub min_rssi: Option<i16>,
+    /// Tags the device has to have, all of them
+    pub tags: Vec<String>,
+}
+
+/// Order of the devices, ties are broken by the next key and finally by the address
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortKey {
+    /// Alias or name, case insensitive, unnamed devices last
+    Name,
+    /// Strongest signal first, devices without a signal strength last
+    Rssi,
+    /// Most recently seen first, devices not seen since the app started last
+    LastSeen,
+    ConnectedFirst,
+}
+
+/// Filter, sort keys and the page of the matching devices to return
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeviceQuery {
+    pub filter: DeviceFilter,
+    pub sort: Vec<SortKey>,
+    /// Matching devices to skip
+    pub offset: usize,
+    /// Most devices to return, all of them when None
+    pub limit: Option<usize>,
+}
+
+impl Default for DeviceQuery {
+    fn default() -> Self {
+        Self {
+            filter: DeviceFilter::default(),
+            sort: vec![SortKey::ConnectedFirst, SortKey::Name],
+            offset: 0,
+            limit: None,
+        }
+    }
+}
+
+/// One page of the devices matching a query
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DevicePage {
+    /// Matching devices on all pages
+    pub total: usize,
+    pub devices: Vec<DeviceInfo>,
+}
+
+fn parse_uuid(uuid: &str) -> Result<Uuid> {
+    let uuid = uuid.trim();
+    let short = uuid.trim_start_matches("0x").trim_start_matches("0X");
+    Uuid::parse_str(uuid)
+        .ok()
+        .or_else(|| u16::from_str_radix(short, 16).ok().map(Uuid::from_u16))
+        .ok_or_else(|| BluetoothError::InvalidArguments(format!("{} is not a UUID", uuid)))
+}
+
+// None sorts after every value, unlike the Ord of Option
+fn none_last<T: Ord>(a: Option<T>, b: Option<T>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+impl SortKey {
+    fn compare(self, a: &DeviceInfo, b: &DeviceInfo) -> Ordering {
+        match self {
+            SortKey::Name => {
+                let name = |device: &DeviceInfo| {
+                    Some(device.display_name().to_lowercase()).filter(|name| !name.is_empty())
+                };
+                none_last(name(a), name(b))
+            }
+            SortKey::Rssi => none_last(a.rssi.map(Reverse), b.rssi.map(Reverse)),
+            SortKey::LastSeen => none_last(a.last_seen.map(Reverse), b.last_seen.map(Reverse)),
+            SortKey::ConnectedFirst => b.is_connected.cmp(&a.is_connected),
+        }
+    }
+}
+
+impl DeviceFilter {
+    fn matches(&self, device: &DeviceInfo, uuid: Option<&Uuid>) -> bool {
+        let flag =
+            |wanted: Option<bool>, actual: bool| wanted.map_or(true, |wanted| wanted == actual);
+        if let Some(text) = &self.text {
+            let text = text.to_lowercase();
+            if !device.name.to_lowercase().contains(&text)
+                && !device.alias.to_lowercase().contains(&text)
+            {
+                return false;
+            }
+        }
+        if let Some(prefix) = &self.address_prefix {
+            if !device
+                .address_string
+                .to_lowercase()
+                .starts_with(&prefix.to_lowercase())
+            {
+                return false;
+            }
+        }
+        if let Some(major_class) = self.major_class {
+            if device.class_of_device.as_ref().map(|class| class.major) != Some(major_class) {
+                return false;
+            }
+        }
+        if let Some(profile) = self.profile {
+            if !device.profiles.contains(&profile) {
+                return false;
+            }
+        }
+        if let Some(uuid) = uuid {
+            if !device.uuids.contains_key(&uuid.to_string()) {
+                return false;
+            }
+        }
+        if let Some(min_rssi) = self.min_rssi {
+            if device.rssi.map_or(true, |rssi| rssi < min_rssi) {
+                return false;
+            }
+        }
+        flag(self.paired, device.is_paired)
+            && flag(self.connected, device.is_connected)
+            && flag(self.trusted, device.is_trusted)
+            && self.tags.iter().all(|tag| device.tags.contains(tag))
+    }
+}
+
+impl DeviceQuery {
+    /// Filters, sorts and pages `devices`
+    pub fn run<'a>(&self, devices: impl IntoIterator<Item = &'a DeviceInfo>) -> Result<DevicePage> {
+        let uuid = self.filter.uuid.as_deref().map(parse_uuid).transpose()?;
+        let mut devices: Vec<&DeviceInfo> = devices
+            .into_iter()
+            .filter(|device| self.filter.matches(device, uuid.as_ref()))
+            .collect();
+        devices.sort_by(|a, b| {
+            self.sort
+                .iter()
+                .fold(Ordering::Equal, |ordering, key| {
+                    ordering.then_with(|| key.compare(a, b))
+                })
+                .then_with(|| a.address.cmp(&b.address))
+        });
+        Ok(DevicePage {
+            total: devices.len(),
+            devices: devices
+                .into_iter()
+                .skip(self.offset)
+                .take(self.limit.unwrap_or(usize::MAX))
+                .cloned()
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{DeviceFilter, DeviceQuery, SortKey};
+    use crate::{device::DeviceInfo, profiles::Profile, BluetoothError};
+
+    fn device(address: u8, alias: &str, rssi: Option<i16>, is_connected: bool) -> DeviceInfo {
+        DeviceInfo {
+            alias: String::from(alias),
+            address: [0, 0, 0, 0, 0, address],
+            address_string: format!("00:00:00:00:00:{:02X}", address),
+            rssi,
+            is_connected,
+            ..DeviceInfo::default()
+        }
+    }
+
+    fn aliases(query: &DeviceQuery, devices: &[DeviceInfo]) -> Vec<String> {
+        query
+            .run(devices)
+            .unwrap()
+            .devices
+            .into_iter()
+            .map(|device| device.alias)
+            .collect()
+    }
+
+    #[test]
+    fn sorts_connected_devices_first_by_default() {
+        let devices = [
+            device(1, "beacon", Some(-80), false),
+            device(2, "Speaker", None, true),
+            device(3, "", Some(-40), false),
+            device(4, "alarm", None, false),
+        ];
+        assert_eq!(
+            aliases(&DeviceQuery::default(), &devices),
+            ["Speaker", "alarm", "beacon", ""]
+        );
+        let by_rssi = DeviceQuery {
+            sort: vec![SortKey::Rssi],
+            ..DeviceQuery::default()
+        };
+        assert_eq!(
+            aliases(&by_rssi, &devices),
+            ["", "beacon", "Speaker", "alarm"]
+        );
+    }
+
+    #[test]
+    fn filters_and_pages() {
+        let mut devices = vec![
+            device(1, "Kitchen speaker", Some(-70), false),
+            device(2, "Office speaker", Some(-50), true),
+            device(3, "Keyboard", Some(-45), true),
+        ];
+        devices[1].profiles = vec![Profile::A2dpSink];
+        devices[1].tags = vec![String::from("office")];
+
+        let speakers = DeviceQuery {
+            filter: DeviceFilter {
+                text: Some(String::from("SPEAKER")),
+                min_rssi: Some(-60),
+                ..DeviceFilter::default()
+            },
+            ..DeviceQuery::default()
+        };
+        assert_eq!(aliases(&speakers, &devices), ["Office speaker"]);
+
+        let tagged = DeviceQuery {
+            filter: DeviceFilter {
+                profile: Some(Profile::A2dpSink),
+                tags: vec![String::from("office")],
+                address_prefix: Some(String::from("00:00:00:00:00:0")),
+                ..DeviceFilter::default()
+            },
+            ..DeviceQuery::default()
+        };
+        assert_eq!(aliases(&tagged, &devices), ["Office speaker"]);
+
+        let second_page = DeviceQuery {
+            offset: 1,
+            limit: Some(1),
+            ..DeviceQuery::default()
+        };
+        let page = second_page.run(&devices).unwrap();
+        assert_eq!(page.total, 3);
+        assert_eq!(page.devices[0].alias, "Office speaker");
+    }
+
+    #[test]
+    fn rejects_invalid_uuids() {
+        let query = DeviceQuery {
+            filter: DeviceFilter {
+                uuid: Some(String::from("headset")),
+                ..DeviceFilter::default()
+            },
+            ..DeviceQuery::default()
+        };
+        assert!(matches!(
+            query.run(&[]),
+            Err(BluetoothError::InvalidArguments(_))
+        ));
+    }
+}
@@ -0,0 +1,94 @@
+use std::sync::Arc;
+
+use bluer::{Address, DeviceEvent as BlueZDeviceEvent};
+use futures::lock::Mutex;
+use tokio::sync::{broadcast, mpsc};
+use tokio_stream::{StreamExt, StreamMap};
+
+use super::{
+    adapter::AdapterEvent,
+    backend::{AdapterBackend, BackendStream},
+    device_list::DeviceList,
+};
+
+type DeviceEventStream = BackendStream<BlueZDeviceEvent>;
+
+#[derive(Debug)]
+enum WatchCommand {
+    Watch(Address),
+    Unwatch(Address),
+}
+
+/// Follows property changes of every known device for the lifetime of the adapter,
+/// independent of discovery, so connection and battery changes of paired devices are
+/// never missed
+#[derive(Debug, Clone)]
+pub struct DeviceWatcher {
+    tx: mpsc::UnboundedSender<WatchCommand>,
+}
+
+impl DeviceWatcher {
+    pub fn spawn(
+        adapter: Arc<dyn AdapterBackend>,
+        device_list: Arc<Mutex<DeviceList>>,
+        event_tx: broadcast::Sender<AdapterEvent>,
+        addresses: impl IntoIterator<Item = Address>,
+    ) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        for address in addresses {
+            let _ = tx.send(WatchCommand::Watch(address));
+        }
+        tokio::spawn(run_watcher(adapter, device_list, event_tx, rx));
+        Self { tx }
+    }
+
+    pub fn watch(&self, address: Address) {
+        let _ = self.tx.send(WatchCommand::Watch(address));
+    }
+
+    pub fn unwatch(&self, address: Address) {
+        let _ = self.tx.send(WatchCommand::Unwatch(address));
+    }
+}
+
+async fn run_watcher(
+    adapter: Arc<dyn AdapterBackend>,
+    device_list: Arc<Mutex<DeviceList>>,
+    event_tx: broadcast::Sender<AdapterEvent>,
+    mut rx: mpsc::UnboundedReceiver<WatchCommand>,
+) {
+    let mut streams: StreamMap<Address, DeviceEventStream> = StreamMap::new();
+
+    loop {
+        tokio::select! {
+            command = rx.recv() => match command {
+                Some(WatchCommand::Watch(address)) => {
+                    if !streams.contains_key(&address) {
+                        if let Some(events) = subscribe(&adapter, address).await {
+                            streams.insert(address, events);
+                        }
+                    }
+                }
+                Some(WatchCommand::Unwatch(address)) => {
+                    streams.remove(&address);
+                }
+                // every DeviceWatcher handle is gone, the adapter was dropped
+                None => break,
+            },
+            Some((address, BlueZDeviceEvent::PropertyChanged(property))) = streams.next(), if !streams.is_empty() => {
+                let delta = device_list.lock().await.update_device(address, property);
+                if let Some(delta) = delta {
+                    let _ = event_tx.send(AdapterEvent::DeviceChanged(delta));
+                }
+            }
+        }
+    }
+}
+
+async fn subscribe(
+    adapter: &Arc<dyn AdapterBackend>,
+    address: Address,
+) -> Option<DeviceEventStream> {
+    let device = adapter.device(address).ok()?;
+    device.events().await.ok()
+}
@@ -0,0 +1,224 @@
+use std::io;
+
+use bluer::{ErrorKind, InternalErrorKind};
+use serde::{ser::SerializeStruct, Serialize, Serializer};
+use thiserror::Error;
+
+use super::adapter::AdapterErrors;
+
+/// Result of every fallible operation of this crate
+pub type Result<T> = std::result::Result<T, BluetoothError>;
+
+/// Error of every fallible operation, serialized as `{ code, message, details }` where
+/// `code` is stable and safe to match on and `message` is meant for users
+#[derive(Error, Debug)]
+pub enum BluetoothError {
+    #[error(transparent)]
+    Adapter(#[from] AdapterErrors),
+    #[error("Bluetooth is not ready, make sure the adapter is powered on")]
+    NotReady(String),
+    #[error("Authentication with the device failed")]
+    AuthenticationFailed(String),
+    #[error("Authentication was canceled")]
+    AuthenticationCanceled(String),
+    #[error("Authentication was rejected by the device")]
+    AuthenticationRejected(String),
+    #[error("Authentication timed out")]
+    AuthenticationTimeout(String),
+    #[error("Another operation is already in progress")]
+    InProgress(String),
+    #[error("Device is already connected")]
+    AlreadyConnected(String),
+    #[error("Device already exists")]
+    AlreadyExists(String),
+    #[error("Could not establish a connection to the device")]
+    ConnectionAttemptFailed(String),
+    #[error("Requested object does not exist")]
+    DoesNotExist(String),
+    #[error("Operation is not available")]
+    NotAvailable(String),
+    #[error("Operation is not supported by the device or adapter")]
+    NotSupported(String),
+    #[error("Invalid arguments")]
+    InvalidArguments(String),
+    #[error("Operation failed")]
+    Failed(String),
+    #[error("Permission denied, the user is not allowed to control bluetooth")]
+    PermissionDenied(String),
+    #[error("Bluetooth service is not running")]
+    ServiceUnavailable(String),
+    #[error("Unexpected error")]
+    Internal(String),
+}
+
+impl BluetoothError {
+    /// Stable identifier of the error kind
+    pub fn code(&self) -> &'static str {
+        match self {
+            BluetoothError::Adapter(error) => match error {
+                AdapterErrors::AdapterNotFound => "ADAPTER_NOT_FOUND",
+                AdapterErrors::AdapterNotPoweredOn(_) => "ADAPTER_NOT_POWERED_ON",
+                AdapterErrors::AdapterIsDiscovering(_) => "ADAPTER_IS_DISCOVERING",
+                AdapterErrors::DeviceNotFound => "DEVICE_NOT_FOUND",
+                AdapterErrors::AdapterSoftBlocked(_) => "ADAPTER_SOFT_BLOCKED",
+                AdapterErrors::AdapterHardBlocked(_) => "ADAPTER_HARD_BLOCKED",
+            },
+            BluetoothError::NotReady(_) => "NOT_READY",
+            BluetoothError::AuthenticationFailed(_) => "AUTHENTICATION_FAILED",
+            BluetoothError::AuthenticationCanceled(_) => "AUTHENTICATION_CANCELED",
+            BluetoothError::AuthenticationRejected(_) => "AUTHENTICATION_REJECTED",
+            BluetoothError::AuthenticationTimeout(_) => "AUTHENTICATION_TIMEOUT",
+            BluetoothError::InProgress(_) => "IN_PROGRESS",
+            BluetoothError::AlreadyConnected(_) => "ALREADY_CONNECTED",
+            BluetoothError::AlreadyExists(_) => "ALREADY_EXISTS",
+            BluetoothError::ConnectionAttemptFailed(_) => "CONNECTION_ATTEMPT_FAILED",
+            BluetoothError::DoesNotExist(_) => "DOES_NOT_EXIST",
+            BluetoothError::NotAvailable(_) => "NOT_AVAILABLE",
+            BluetoothError::NotSupported(_) => "NOT_SUPPORTED",
+            BluetoothError::InvalidArguments(_) => "INVALID_ARGUMENTS",
+            BluetoothError::Failed(_) => "FAILED",
+            BluetoothError::PermissionDenied(_) => "PERMISSION_DENIED",
+            BluetoothError::ServiceUnavailable(_) => "SERVICE_UNAVAILABLE",
+            BluetoothError::Internal(_) => "INTERNAL",
+        }
+    }
+
+    /// Message reported by BlueZ or D-Bus, if any
+    pub fn details(&self) -> Option<&str> {
+        match self {
+            BluetoothError::Adapter(_) => None,
+            BluetoothError::NotReady(details)
+            | BluetoothError::AuthenticationFailed(details)
+            | BluetoothError::AuthenticationCanceled(details)
+            | BluetoothError::AuthenticationRejected(details)
+            | BluetoothError::AuthenticationTimeout(details)
+            | BluetoothError::InProgress(details)
+            | BluetoothError::AlreadyConnected(details)
+            | BluetoothError::AlreadyExists(details)
+            | BluetoothError::ConnectionAttemptFailed(details)
+            | BluetoothError::DoesNotExist(details)
+            | BluetoothError::NotAvailable(details)
+            | BluetoothError::NotSupported(details)
+            | BluetoothError::InvalidArguments(details)
+            | BluetoothError::Failed(details)
+            | BluetoothError::PermissionDenied(details)
+            | BluetoothError::ServiceUnavailable(details)
+            | BluetoothError::Internal(details) => {
+                Some(details.as_str()).filter(|details| !details.is_empty())
+            }
+        }
+    }
+}
+
+impl From<bluer::Error> for BluetoothError {
+    fn from(error: bluer::Error) -> Self {
+        let details = error.message;
+        match error.kind {
+            ErrorKind::NotReady => BluetoothError::NotReady(details),
+            ErrorKind::AuthenticationFailed => BluetoothError::AuthenticationFailed(details),
+            ErrorKind::AuthenticationCanceled => BluetoothError::AuthenticationCanceled(details),
+            ErrorKind::AuthenticationRejected => BluetoothError::AuthenticationRejected(details),
+            ErrorKind::AuthenticationTimeout => BluetoothError::AuthenticationTimeout(details),
+            ErrorKind::InProgress => BluetoothError::InProgress(details),
+            ErrorKind::AlreadyConnected => BluetoothError::AlreadyConnected(details),
+            ErrorKind::AlreadyExists => BluetoothError::AlreadyExists(details),
+            ErrorKind::ConnectionAttemptFailed => BluetoothError::ConnectionAttemptFailed(details),
+            ErrorKind::DoesNotExist | ErrorKind::NotFound => BluetoothError::DoesNotExist(details),
+            ErrorKind::NotAvailable => BluetoothError::NotAvailable(details),
+            ErrorKind::NotSupported => BluetoothError::NotSupported(details),
+            ErrorKind::InvalidArguments => BluetoothError::InvalidArguments(details),
+            ErrorKind::NotAuthorized | ErrorKind::NotPermitted => {
+                BluetoothError::PermissionDenied(details)
+            }
+            ErrorKind::Failed => BluetoothError::Failed(details),
+            ErrorKind::Internal(InternalErrorKind::DBus(name)) => {
+                from_dbus_error_name(&name, details)
+            }
+            kind => BluetoothError::Internal(format!("{:?}: {}", kind, details)),
+        }
+    }
+}
+
+// For the BlueZ interfaces bluer does not cover and that are called over D-Bus directly
+impl From<dbus::Error> for BluetoothError {
+    fn from(error: dbus::Error) -> Self {
+        let details = error.message().unwrap_or_default().to_string();
+        match error.name() {
+            Some(name) => match name.strip_prefix("org.bluez.Error.") {
+                Some(bluez_name) => from_bluez_error_name(bluez_name, details),
+                None => from_dbus_error_name(name, details),
+            },
+            None => BluetoothError::Internal(details),
+        }
+    }
+}
+
+// Socket errors of the RFCOMM and L2CAP channels
+impl From<std::io::Error> for BluetoothError {
+    fn from(error: std::io::Error) -> Self {
+        let details = error.to_string();
+        match error.kind() {
+            io::ErrorKind::ConnectionRefused
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::TimedOut => BluetoothError::ConnectionAttemptFailed(details),
+            io::ErrorKind::NotConnected | io::ErrorKind::BrokenPipe => {
+                BluetoothError::NotAvailable(details)
+            }
+            io::ErrorKind::PermissionDenied => BluetoothError::PermissionDenied(details),
+            io::ErrorKind::NotFound => BluetoothError::DoesNotExist(details),
+            io::ErrorKind::AddrInUse => BluetoothError::AlreadyExists(details),
+            io::ErrorKind::InvalidInput => BluetoothError::InvalidArguments(details),
+            _ => BluetoothError::Failed(details),
+        }
+    }
+}
+
+fn from_bluez_error_name(name: &str, details: String) -> BluetoothError {
+    match name {
+        "NotReady" => BluetoothError::NotReady(details),
+        "AuthenticationFailed" => BluetoothError::AuthenticationFailed(details),
+        "AuthenticationCanceled" => BluetoothError::AuthenticationCanceled(details),
+        "AuthenticationRejected" => BluetoothError::AuthenticationRejected(details),
+        "AuthenticationTimeout" => BluetoothError::AuthenticationTimeout(details),
+        "InProgress" => BluetoothError::InProgress(details),
+        "AlreadyConnected" => BluetoothError::AlreadyConnected(details),
+        "AlreadyExists" => BluetoothError::AlreadyExists(details),
+        "ConnectionAttemptFailed" => BluetoothError::ConnectionAttemptFailed(details),
+        "DoesNotExist" | "NotFound" => BluetoothError::DoesNotExist(details),
+        "NotAvailable" => BluetoothError::NotAvailable(details),
+        "NotSupported" => BluetoothError::NotSupported(details),
+        "InvalidArguments" => BluetoothError::InvalidArguments(details),
+        "NotAuthorized" | "NotPermitted" => BluetoothError::PermissionDenied(details),
+        "Failed" => BluetoothError::Failed(details),
+        _ => BluetoothError::Internal(format!("org.bluez.Error.{}: {}", name, details)),
+    }
+}
+
+// D-Bus errors that are not raised by BlueZ itself, mostly policy denials
+fn from_dbus_error_name(name: &str, details: String) -> BluetoothError {
+    match name {
+        "org.freedesktop.DBus.Error.AccessDenied"
+        | "org.freedesktop.DBus.Error.AuthFailed"
+        | "org.freedesktop.DBus.Error.InteractiveAuthorizationRequired" => {
+            BluetoothError::PermissionDenied(details)
+        }
+        "org.freedesktop.DBus.Error.ServiceUnknown"
+        | "org.freedesktop.DBus.Error.NameHasNoOwner"
+        | "org.freedesktop.DBus.Error.NoServer" => BluetoothError::ServiceUnavailable(details),
+        _ => BluetoothError::Internal(format!("{}: {}", name, details)),
+    }
+}
+
+impl Serialize for BluetoothError {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("BluetoothError", 3)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.serialize_field("details", &self.details())?;
+        state.end()
+    }
+}
@@ -0,0 +1,572 @@
+//! Device inventories, the device list written to a JSON or CSV file and read back for
+//! offline viewing.
+//!
+//! JSON keeps every [`DeviceInfo`] field as serialized for the app. CSV has one row per
+//! device with the decoded class, service names and profiles spelled out for spreadsheets;
+//! reading it back decodes them again from the raw class and UUID columns.
+
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bluer::{Address, DeviceProperty, Uuid};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    device::DeviceInfo,
+    error::{BluetoothError, Result},
+    network::{NetworkInfo, PanRole},
+};
+
+/// File format of an inventory
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InventoryFormat {
+    Json,
+    Csv,
+}
+
+impl InventoryFormat {
+    /// The format matching the extension of `path`, if it is `.json` or `.csv`
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_lowercase().as_str() {
+            "json" => Some(InventoryFormat::Json),
+            "csv" => Some(InventoryFormat::Csv),
+            _ => None,
+        }
+    }
+}
+
+/// Devices as of the time they were exported
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Inventory {
+    /// Seconds since the Unix epoch, None for CSV files which only hold the devices
+    pub exported_at: Option<u64>,
+    pub devices: Vec<DeviceInfo>,
+}
+
+const COLUMNS: [&str; 22] = [
+    "address",
+    "address_type",
+    "adapter",
+    "name",
+    "alias",
+    "class",
+    "class_description",
+    "uuids",
+    "services",
+    "profiles",
+    "paired",
+    "connected",
+    "trusted",
+    "blocked",
+    "wake_allowed",
+    "legacy_pairing",
+    "battery_percentage",
+    "rssi",
+    "last_seen",
+    "tags",
+    "manufacturer_data",
+    "network",
+];
+
+// separates the entries of the list columns, tags containing it are split on import
+const LIST_SEPARATOR: &str = "; ";
+
+// spreadsheet programs run fields starting with these as formulas, so they are written with a
+// leading quote. A leading quote is escaped too, so the import can tell it from an escape
+const FORMULA_PREFIXES: [char; 7] = ['=', '+', '-', '@', '\t', '\r', '\''];
+
+fn format_or_extension(path: &Path, format: Option<InventoryFormat>) -> Result<InventoryFormat> {
+    format
+        .or_else(|| InventoryFormat::from_path(path))
+        .ok_or_else(|| {
+            BluetoothError::InvalidArguments(format!(
+                "{} is neither a .json nor a .csv file",
+                path.display()
+            ))
+        })
+}
+
+impl Inventory {
+    /// Inventory of `devices` exported now
+    pub fn new(devices: Vec<DeviceInfo>) -> Self {
+        Self {
+            exported_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .ok()
+                .map(|since_epoch| since_epoch.as_secs()),
+            devices,
+        }
+    }
+
+    /// Writes the inventory to `path`, in the format of its extension when `format` is None
+    pub async fn write(&self, path: &Path, format: Option<InventoryFormat>) -> Result<()> {
+        let contents = match format_or_extension(path, format)? {
+            InventoryFormat::Json => self.to_json()?,
+            InventoryFormat::Csv => self.to_csv(),
+        };
+        tokio::fs::write(path, contents).await?;
+        Ok(())
+    }
+
+    /// Reads an inventory written by [`Inventory::write`]
+    pub async fn read(path: &Path, format: Option<InventoryFormat>) -> Result<Self> {
+        let format = format_or_extension(path, format)?;
+        let contents = tokio::fs::read_to_string(path).await?;
+        match format {
+            InventoryFormat::Json => Self::from_json(&contents),
+            InventoryFormat::Csv => Self::from_csv(&contents),
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).map_err(|err| BluetoothError::Internal(err.to_string()))
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json).map_err(|err| {
+            BluetoothError::InvalidArguments(format!("not a device inventory: {}", err))
+        })
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv = csv_row(COLUMNS.iter().map(|column| column.to_string()));
+        for device in &self.devices {
+            csv.push_str(&csv_row(device_row(device)));
+        }
+        csv
+    }
+
+    /// Reads the CSV columns by their header, columns that are missing are left at their
+    /// defaults and only `address` is required
+    pub fn from_csv(csv: &str) -> Result<Self> {
+        // spreadsheet programs start the files they save with a byte order mark
+        let rows = parse_csv(csv.strip_prefix('\u{feff}').unwrap_or(csv));
+        let (header, rows) = rows
+            .split_first()
+            .ok_or_else(|| BluetoothError::InvalidArguments(String::from("empty CSV file")))?;
+        let columns: HashMap<&str, usize> = header
+            .iter()
+            .enumerate()
+            .map(|(index, column)| (column.as_str(), index))
+            .collect();
+        if !columns.contains_key("address") {
+            return Err(BluetoothError::InvalidArguments(String::from(
+                "CSV file has no address column",
+            )));
+        }
+        let devices = rows
+            .iter()
+            .enumerate()
+            .filter(|(_, row)| row.iter().any(|field| !field.is_empty()))
+            .map(|(index, row)| {
+                let row = CsvRow {
+                    columns: &columns,
+                    fields: row,
+                };
+                // the header is row 1
+                device_from_row(&row).map_err(|err| {
+                    BluetoothError::InvalidArguments(format!("row {}: {}", index + 2, err))
+                })
+            })
+            .collect::<Result<Vec<DeviceInfo>>>()?;
+        Ok(Self {
+            exported_at: None,
+            devices,
+        })
+    }
+}
+
+fn device_row(device: &DeviceInfo) -> Vec<String> {
+    let join = |values: Vec<String>| values.join(LIST_SEPARATOR);
+    let mut uuids: Vec<(&String, String)> = device
+        .uuids
+        .iter()
+        .map(|(uuid, info)| (uuid, info.label()))
+        .collect();
+    uuids.sort();
+    let mut manufacturer_data: Vec<(&u16, &Vec<u8>)> = device.manufacturer_data.iter().collect();
+    manufacturer_data.sort();
+    let optional = |value: Option<String>| value.unwrap_or_default();
+    vec![
+        device.address_string.clone(),
+        device.address_type.clone(),
+        device.discovered_adapter_name.clone(),
+        device.name.clone(),
+        device.alias.clone(),
+        format!("0x{:06x}", device.class),
+        optional(device.class_of_device.as_ref().map(ToString::to_string)),
+        join(uuids.iter().map(|(uuid, _)| uuid.to_string()).collect()),
+        join(uuids.into_iter().map(|(_, label)| label).collect()),
+        join(device.profiles.iter().map(ToString::to_string).collect()),
+        device.is_paired.to_string(),
+        device.is_connected.to_string(),
+        device.is_trusted.to_string(),
+        device.is_blocked.to_string(),
+        device.is_wake_allowed.to_string(),
+        device.is_legacy_pairing.to_string(),
+        device.battery_percentage.to_string(),
+        optional(device.rssi.map(|rssi| rssi.to_string())),
+        optional(device.last_seen.map(|last_seen| last_seen.to_string())),
+        join(device.tags.clone()),
+        join(
+            manufacturer_data
+                .into_iter()
+                .map(|(company, data)| format!("{:04x}:{}", company, hex(data)))
+                .collect(),
+        ),
+        optional(device.network.as_ref().map(network_field)),
+    ]
+}
+
+// connection state, then the interface and the role when known, e.g. `connected; bnep0; nap`
+fn network_field(network: &NetworkInfo) -> String {
+    let state = if network.connected {
+        "connected"
+    } else {
+        "disconnected"
+    };
+    [
+        Some(state),
+        network.interface.as_deref(),
+        network.role.map(PanRole::as_bluez),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<&str>>()
+    .join(LIST_SEPARATOR)
+}
+
+fn parse_network(value: &str) -> std::result::Result<Option<NetworkInfo>, String> {
+    let mut entries = list(value);
+    let connected = match entries.next() {
+        None => return Ok(None),
+        Some("connected") => true,
+        Some("disconnected") => false,
+        Some(state) => return Err(format!("{} is not a valid network state", state)),
+    };
+    let mut network = NetworkInfo {
+        connected,
+        ..NetworkInfo::default()
+    };
+    for entry in entries {
+        match PanRole::from_bluez(entry) {
+            Some(role) => network.role = Some(role),
+            None => network.interface = Some(entry.to_string()),
+        }
+    }
+    Ok(Some(network))
+}
+
+fn hex(data: &[u8]) -> String {
+    data.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn parse<T: FromStr>(column: &str, value: &str) -> std::result::Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("{} is not a valid {}", value, column))
+}
+
+fn parse_optional<T: FromStr>(column: &str, value: &str) -> std::result::Result<Option<T>, String> {
+    Some(value)
+        .filter(|value| !value.is_empty())
+        .map(|value| parse(column, value))
+        .transpose()
+}
+
+fn list(value: &str) -> impl Iterator<Item = &str> {
+    value
+        .split(LIST_SEPARATOR.trim())
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+}
+
+fn parse_manufacturer_data(value: &str) -> std::result::Result<HashMap<u16, Vec<u8>>, String> {
+    list(value)
+        .map(|entry| {
+            let invalid = || format!("{} is not valid manufacturer data", entry);
+            let (company, data) = entry.split_once(':').ok_or_else(invalid)?;
+            if data.len() % 2 != 0 {
+                return Err(invalid());
+            }
+            let data = (0..data.len())
+                .step_by(2)
+                .map(|index| {
+                    data.get(index..index + 2)
+                        .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+                })
+                .collect::<Option<Vec<u8>>>()
+                .ok_or_else(invalid)?;
+            let company = u16::from_str_radix(company, 16).map_err(|_| invalid())?;
+            Ok((company, data))
+        })
+        .collect()
+}
+
+struct CsvRow<'a> {
+    columns: &'a HashMap<&'a str, usize>,
+    fields: &'a [String],
+}
+
+impl<'a> CsvRow<'a> {
+    // empty for columns missing from the file, text as it is so names keep their spaces. Only
+    // the quote csv_row escapes formulas with is removed
+    fn field(&self, column: &str) -> &'a str {
+        let field = self
+            .columns
+            .get(column)
+            .and_then(|index| self.fields.get(*index))
+            .map_or("", String::as_str);
+        field
+            .strip_prefix('\'')
+            .filter(|unescaped| unescaped.starts_with(FORMULA_PREFIXES))
+            .unwrap_or(field)
+    }
+
+    // numbers, flags and lists, where spaces around the value are not part of it
+    fn value(&self, column: &str) -> &'a str {
+        self.field(column).trim()
+    }
+}
+
+// Rebuilds the device through its properties, so the class, services and profiles are
+// decoded like for a device loaded from bluetoothd
+fn device_from_row(row: &CsvRow) -> std::result::Result<DeviceInfo, String> {
+    let address: Address = parse("address", row.value("address"))?;
+    let uuids = list(row.value("uuids"))
+        .map(|uuid| parse("UUID", uuid))
+        .collect::<std::result::Result<HashSet<Uuid>, String>>()?;
+    let flag = |column: &str| -> std::result::Result<bool, String> {
+        Ok(parse_optional(column, row.value(column))?.unwrap_or_default())
+    };
+
+    let mut properties = vec![
+        DeviceProperty::Name(row.field("name").to_string()),
+        DeviceProperty::Alias(row.field("alias").to_string()),
+        DeviceProperty::Uuids(uuids),
+        DeviceProperty::Paired(flag("paired")?),
+        DeviceProperty::Connected(flag("connected")?),
+        DeviceProperty::Trusted(flag("trusted")?),
+        DeviceProperty::Blocked(flag("blocked")?),
+        DeviceProperty::WakeAllowed(flag("wake_allowed")?),
+        DeviceProperty::LegacyPairing(flag("legacy_pairing")?),
+        DeviceProperty::BatteryPercentage(
+            parse_optional("battery percentage", row.value("battery_percentage"))?
+                .unwrap_or_default(),
+        ),
+        DeviceProperty::ManufacturerData(parse_manufacturer_data(row.value("manufacturer_data"))?),
+    ];
+    let class = row.value("class");
+    if !class.is_empty() {
+        let class = match class.strip_prefix("0x") {
+            Some(hex_class) => u32::from_str_radix(hex_class, 16).ok(),
+            None => class.parse().ok(),
+        }
+        .ok_or_else(|| format!("{} is not a valid class", class))?;
+        properties.push(DeviceProperty::Class(class));
+    }
+    if let Some(rssi) = parse_optional("RSSI", row.value("rssi"))? {
+        properties.push(DeviceProperty::Rssi(rssi));
+    }
+
+    let mut device = DeviceInfo::from_properties(properties);
+    device.address = address.0;
+    device.address_string = address.to_string();
+    device.address_type = row.field("address_type").to_string();
+    device.discovered_adapter_name = row.field("adapter").to_string();
+    // not the time of the import, which the Rssi and Connected properties set
+    device.last_seen = parse_optional("last seen time", row.value("last_seen"))?;
+    device.tags = list(row.value("tags")).map(String::from).collect();
+    device.tags.sort();
+    device.tags.dedup();
+    device.network = parse_network(row.value("network"))?;
+    Ok(device)
+}
+
+fn csv_row(fields: impl IntoIterator<Item = String>) -> String {
+    let fields: Vec<String> = fields
+        .into_iter()
+        .map(|field| {
+            // numbers like a negative RSSI are no formulas and stay numbers
+            let field = if field.starts_with(FORMULA_PREFIXES) && field.parse::<f64>().is_err() {
+                format!("'{}", field)
+            } else {
+                field
+            };
+            if field.contains(|c| matches!(c, ',' | '"' | '\n' | '\r')) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field
+            }
+        })
+        .collect();
+    format!("{}\r\n", fields.join(","))
+}
+
+// RFC 4180, quoted fields may hold separators, line breaks and doubled quotes
+fn parse_csv(csv: &str) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = csv.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (quoted, c) {
+            (true, '"') if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            (true, '"') => quoted = false,
+            (true, c) => field.push(c),
+            (false, '"') => quoted = true,
+            (false, ',') => row.push(std::mem::take(&mut field)),
+            (false, '\r') => {}
+            (false, '\n') => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            (false, c) => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    rows
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use bluer::{DeviceProperty, Uuid, UuidExt};
+
+    use super::{parse_csv, Inventory, InventoryFormat};
+    use crate::{
+        class_of_device::MajorClass,
+        device::DeviceInfo,
+        network::{NetworkInfo, PanRole},
+        profiles::Profile,
+    };
+
+    fn headset() -> DeviceInfo {
+        let uuids: HashSet<Uuid> = [0x110b, 0x111e]
+            .iter()
+            .map(|uuid| Uuid::from_u16(*uuid))
+            .collect();
+        let mut device = DeviceInfo::from_properties(vec![
+            DeviceProperty::Name(String::from("WH-1000XM4")),
+            DeviceProperty::Alias(String::from("Headphones, \"living room\"")),
+            DeviceProperty::Class(0x240404),
+            DeviceProperty::Uuids(uuids),
+            DeviceProperty::Paired(true),
+            DeviceProperty::WakeAllowed(true),
+            DeviceProperty::BatteryPercentage(80),
+            DeviceProperty::Rssi(-58),
+            DeviceProperty::ManufacturerData([(0x012d, vec![0x03, 0x00, 0x64])].into()),
+        ]);
+        device.address = [0x38, 0x18, 0x4c, 0x10, 0x20, 0x30];
+        device.address_string = String::from("38:18:4C:10:20:30");
+        device.address_type = String::from("br/edr");
+        device.discovered_adapter_name = String::from("hci0");
+        device.last_seen = Some(1_700_000_000);
+        device.tags = vec![String::from("audio"), String::from("travel")];
+        device.network = Some(NetworkInfo {
+            connected: true,
+            interface: Some(String::from("bnep0")),
+            role: Some(PanRole::Nap),
+        });
+        device
+    }
+
+    #[test]
+    fn csv_round_trips_devices() {
+        let csv = Inventory::new(vec![headset()]).to_csv();
+        let rows = parse_csv(&csv);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1][1], "br/edr");
+        assert!(rows[1][8].contains("Audio Sink"));
+
+        let imported = Inventory::from_csv(&csv).unwrap();
+        assert_eq!(imported.exported_at, None);
+        let device = &imported.devices[0];
+        let original = headset();
+        assert_eq!(device.alias, original.alias);
+        assert_eq!(device.address, original.address);
+        assert_eq!(device.uuids, original.uuids);
+        assert_eq!(device.rssi, Some(-58));
+        assert_eq!(device.last_seen, original.last_seen);
+        assert_eq!(device.tags, original.tags);
+        assert_eq!(device.manufacturer_data, original.manufacturer_data);
+        assert!(device.is_wake_allowed);
+        assert!(!device.is_legacy_pairing);
+        assert_eq!(device.network, original.network);
+        assert_eq!(
+            device.class_of_device.as_ref().map(|class| class.major),
+            Some(MajorClass::AudioVideo)
+        );
+        assert!(device.profiles.contains(&Profile::A2dpSink));
+    }
+
+    #[test]
+    fn csv_keeps_spaces_in_names_and_skips_the_byte_order_mark() {
+        let csv = "\u{feff}address,alias,paired,rssi\r\n 00:1B:66:44:55:66 , Board ,true , -70\r\n";
+        let device = &Inventory::from_csv(csv).unwrap().devices[0];
+        assert_eq!(device.address_string, "00:1B:66:44:55:66");
+        assert_eq!(device.alias, " Board ");
+        assert!(device.is_paired);
+        assert_eq!(device.rssi, Some(-70));
+    }
+
+    #[test]
+    fn csv_escapes_formulas_and_reads_them_back() {
+        let mut device = headset();
+        device.alias = String::from("=HYPERLINK(\"http://example.com\")");
+        device.name = String::from("'quoted");
+        device.tags = vec![String::from("@home"), String::from("-")];
+        let csv = Inventory::new(vec![device]).to_csv();
+        let rows = parse_csv(&csv);
+        assert_eq!(rows[1][3], "''quoted");
+        assert_eq!(rows[1][4], "'=HYPERLINK(\"http://example.com\")");
+        assert_eq!(rows[1][19], "'@home; -");
+        // numbers are no formulas
+        assert_eq!(rows[1][17], "-58");
+
+        let imported = &Inventory::from_csv(&csv).unwrap().devices[0];
+        assert_eq!(imported.alias, "=HYPERLINK(\"http://example.com\")");
+        assert_eq!(imported.name, "'quoted");
+        assert_eq!(imported.tags, vec!["-", "@home"]);
+        assert_eq!(imported.rssi, Some(-58));
+        // quotes the export did not add are kept
+        let csv = "address,alias\r\n00:1B:66:44:55:66,'Board\r\n";
+        assert_eq!(Inventory::from_csv(csv).unwrap().devices[0].alias, "'Board");
+    }
+
+    #[test]
+    fn json_round_trips_devices() {
+        let json = Inventory::new(vec![headset()]).to_json().unwrap();
+        let imported = Inventory::from_json(&json).unwrap();
+        assert!(imported.exported_at.is_some());
+        assert_eq!(
+            imported.devices[0].manufacturer_data,
+            headset().manufacturer_data
+        );
+    }
+
+    #[test]
+    fn rejects_rows_without_a_valid_address() {
+        assert!(Inventory::from_csv("name,address\r\nSpeaker,speaker\r\n").is_err());
+        assert!(Inventory::from_csv("name\r\nSpeaker\r\n").is_err());
+        let minimal = Inventory::from_csv("address\n00:1B:66:44:55:66\n\n").unwrap();
+        assert_eq!(minimal.devices.len(), 1);
+        assert_eq!(
+            InventoryFormat::from_path("devices.CSV".as_ref()),
+            Some(InventoryFormat::Csv)
+        );
+    }
+}
@@ -0,0 +1,151 @@
+//! BlueZ adapter and device model shared by the saiyan blue app and tools.
+//!
+//! A [`Client`] opens a session to bluetoothd and picks an [`Adapter`]. The adapter keeps
+//! its [`AdapterInfo`](adapter::AdapterInfo) and the [`DeviceInfo`](device::DeviceInfo) of
+//! every known device up to date and publishes each change as an
+//! [`AdapterEvent`](adapter::AdapterEvent) to any number of subscribers. All models are
+//! serde serializable and every fallible call returns a [`BluetoothError`].
+//!
+//! The adapter reaches the bluetooth stack through the [`backend`] traits, so
+//! [`Client::simulated`] can run everything against an in-memory
+//! [`Simulator`](backend::simulator::Simulator) instead of bluetoothd.
+
+use std::sync::Arc;
+
+use adapter::{Adapter, AdapterErrors};
+use backend::{bluez::BlueZAdapterBackend, simulator::Simulator};
+use bluer::{self, Session};
+use rfkill::DevRfkill;
+
+pub use error::{BluetoothError, Result};
+
+pub mod adapter;
+pub mod backend;
+pub mod capture;
+pub mod class_of_device;
+mod constants;
+pub mod device;
+pub mod device_list;
+pub mod device_query;
+pub mod device_watcher;
+pub mod error;
+pub mod inventory;
+pub mod media;
+pub mod network;
+pub mod pairing;
+pub mod profiles;
+pub mod rfkill;
+pub mod terminal;
+pub mod transport;
+pub mod uuids;
+
+/// Representation of the client system, a BlueZ session bound to one adapter
+pub struct Client {
+    adapter_names: Vec<String>,
+    // None when running against the simulator
+    session: Option<Session>,
+    adapter: Adapter,
+}
+
+impl Client {
+    /// Connects to bluetoothd using hci0, or the first adapter when there is no hci0
+    pub async fn new() -> Result<Client> {
+        Self::with_adapter(None).await
+    }
+
+    /// Uses the named adapter, or hci0 falling back to the first adapter when no name is given
+    pub async fn with_adapter(adapter_name: Option<&str>) -> Result<Client> {
+        let session = bluer::Session::new().await?;
+        let mut adapter_names = session.adapter_names().await?;
+        adapter_names.sort();
+        let default_adapter = match adapter_name {
+            Some(adapter_name) => {
+                if !adapter_names.iter().any(|name| name == adapter_name) {
+                    return Err(AdapterErrors::AdapterNotFound.into());
+                }
+                session.adapter(adapter_name)?
+            }
+            None if adapter_names.iter().any(|name| name == "hci0") => {
+                session.adapter("hci0").unwrap()
+            }
+            None => match adapter_names.first() {
+                Some(adapter_name) => session.adapter(adapter_name).unwrap(),
+                None => return Err(AdapterErrors::AdapterNotFound.into()),
+            },
+        };
+
+        Ok(Self {
+            adapter_names,
+            adapter: Adapter::new(
+                Arc::new(BlueZAdapterBackend::new(default_adapter)),
+                Arc::new(DevRfkill::new()),
+            )
+            .await?,
+            session: Some(session),
+        })
+    }
+
+    /// Runs against the given simulator instead of bluetoothd
+    pub async fn simulated(simulator: &Simulator) -> Result<Client> {
+        let backend = simulator.adapter();
+        Ok(Self {
+            adapter_names: vec![backend.name().to_string()],
+            adapter: Adapter::new(backend, simulator.rfkill()).await?,
+            session: None,
+        })
+    }
+
+    /// The bluetoothd session, None for a simulated client
+    pub fn session(&self) -> Option<&Session> {
+        self.session.as_ref()
+    }
+
+    pub fn adapter(&self) -> &Adapter {
+        &self.adapter
+    }
+
+    pub fn adapter_mut(&mut self) -> &mut Adapter {
+        &mut self.adapter
+    }
+
+    pub fn adapter_names(&self) -> &Vec<String> {
+        &self.adapter_names
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{backend::simulator::Simulator, Result};
+
+    use super::Client;
+
+    #[tokio::test]
+    async fn list_adapters() -> Result<()> {
+        let client = Client::simulated(&Simulator::demo()).await?;
+        assert_eq!(client.adapter_names, vec![String::from("sim0")]);
+        assert_eq!(client.adapter.adapter_info().await.name, "sim0");
+        assert!(client.session().is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn discovered_devices() -> Result<()> {
+        let client = Client::simulated(&Simulator::demo()).await?;
+        let devices = client.adapter().known_devices().await;
+        assert_eq!(devices.len(), 3);
+        assert!(devices
+            .iter()
+            .all(|device| device.discovered_adapter_name == "sim0"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn discover_devices() -> Result<()> {
+        let mut client = Client::simulated(&Simulator::demo()).await?;
+        client.adapter_mut().discover_devices(10).await?;
+        assert!(client.adapter().is_discovering().await);
+        client.adapter_mut().cancel_discovering().await;
+        assert!(!client.adapter().is_discovering().await);
+        Ok(())
+    }
+}
@@ -0,0 +1,83 @@
+use bluer::Address;
+use serde::{Deserialize, Serialize};
+
+/// AVRCP target, controller and remote control, devices with any of them have a media player
+pub const AVRCP_UUIDS: [u16; 3] = [0x110c, 0x110e, 0x110f];
+
+/// Playback state reported by the device
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlaybackStatus {
+    Playing,
+    Paused,
+    Stopped,
+    ForwardSeek,
+    ReverseSeek,
+    Error,
+}
+
+impl Default for PlaybackStatus {
+    fn default() -> Self {
+        PlaybackStatus::Stopped
+    }
+}
+
+impl PlaybackStatus {
+    /// Parses the `Status` values of `org.bluez.MediaPlayer1`
+    pub fn from_bluez(status: &str) -> Self {
+        match status {
+            "playing" => PlaybackStatus::Playing,
+            "paused" => PlaybackStatus::Paused,
+            "forward-seek" => PlaybackStatus::ForwardSeek,
+            "reverse-seek" => PlaybackStatus::ReverseSeek,
+            "error" => PlaybackStatus::Error,
+            _ => PlaybackStatus::Stopped,
+        }
+    }
+}
+
+/// Metadata of the current track, empty fields were not reported by the device
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrackInfo {
+    pub title: String,
+    pub artist: String,
+    pub album: String,
+    pub genre: String,
+    pub track_number: u32,
+    pub number_of_tracks: u32,
+    /// Milliseconds
+    pub duration: u32,
+}
+
+/// Serializable state of the AVRCP media player of a device
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MediaPlayerInfo {
+    pub name: String,
+    pub status: PlaybackStatus,
+    /// Milliseconds into the track
+    pub position: u32,
+    pub track: TrackInfo,
+    pub repeat: String,
+    pub shuffle: String,
+}
+
+/// Playback controls of a media player. AVRCP has no absolute seek, seeking runs until the
+/// next play or pause
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MediaCommand {
+    Play,
+    Pause,
+    Stop,
+    Next,
+    Previous,
+    SeekForward,
+    SeekBackward,
+}
+
+/// The media player of a device appeared or changed, or went away when `player` is None
+#[derive(Debug, Clone)]
+pub struct MediaPlayerEvent {
+    pub address: Address,
+    pub player: Option<MediaPlayerInfo>,
+}
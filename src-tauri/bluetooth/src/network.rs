@@ -0,0 +1,96 @@
+use bluer::Address;
+use serde::{Deserialize, Serialize};
+
+use crate::profiles::Profile;
+
+/// PAN role of the remote side of a network connection
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PanRole {
+    /// Network access point, shares its own connection, like a tethering phone
+    Nap,
+    /// Group ad-hoc network between the connected devices
+    Gn,
+    /// PAN user, a plain client
+    Panu,
+}
+
+impl Default for PanRole {
+    fn default() -> Self {
+        PanRole::Nap
+    }
+}
+
+impl PanRole {
+    /// The role names `org.bluez.Network1` and `org.bluez.NetworkServer1` take
+    pub fn as_bluez(self) -> &'static str {
+        match self {
+            PanRole::Nap => "nap",
+            PanRole::Gn => "gn",
+            PanRole::Panu => "panu",
+        }
+    }
+
+    /// Parses the role names and UUIDs bluetoothd reports in the `UUID` property
+    pub fn from_bluez(role: &str) -> Option<Self> {
+        match role.to_lowercase().as_str() {
+            "nap" | "00001116-0000-1000-8000-00805f9b34fb" => Some(PanRole::Nap),
+            "gn" | "00001117-0000-1000-8000-00805f9b34fb" => Some(PanRole::Gn),
+            "panu" | "00001115-0000-1000-8000-00805f9b34fb" => Some(PanRole::Panu),
+            _ => None,
+        }
+    }
+
+    /// The profile a device needs to take this role
+    pub fn profile(self) -> Profile {
+        match self {
+            PanRole::Nap => Profile::PanNetworkAccessPoint,
+            PanRole::Gn => Profile::PanGroupNetwork,
+            PanRole::Panu => Profile::PanUser,
+        }
+    }
+}
+
+/// PAN service the adapter offers, connections are bridged to `bridge`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetworkServer {
+    /// [`PanRole::Nap`] or [`PanRole::Gn`], the adapter cannot serve as a PAN user
+    pub role: PanRole,
+    /// Bridge interface the bnep interfaces of the clients are added to, it has to exist
+    pub bridge: String,
+}
+
+/// Serializable state of the PAN connection to a device
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetworkInfo {
+    pub connected: bool,
+    /// Network interface bluetoothd created for the connection, like `bnep0`
+    pub interface: Option<String>,
+    /// Role of the device while connected
+    pub role: Option<PanRole>,
+}
+
+/// The network interface of a device appeared or changed, or went away when `network` is None
+#[derive(Debug, Clone)]
+pub struct NetworkEvent {
+    pub address: Address,
+    pub network: Option<NetworkInfo>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::PanRole;
+
+    #[test]
+    fn parses_roles_and_uuids() {
+        assert_eq!(PanRole::from_bluez("nap"), Some(PanRole::Nap));
+        assert_eq!(
+            PanRole::from_bluez("00001117-0000-1000-8000-00805F9B34FB"),
+            Some(PanRole::Gn)
+        );
+        assert_eq!(PanRole::from_bluez(""), None);
+        for role in [PanRole::Nap, PanRole::Gn, PanRole::Panu] {
+            assert_eq!(PanRole::from_bluez(role.as_bluez()), Some(role));
+        }
+    }
+}
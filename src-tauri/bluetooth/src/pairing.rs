@@ -0,0 +1,70 @@
+//! Pairing and authorization requests of bluetoothd.
+//!
+//! While [`Adapter::register_agent`](crate::adapter::Adapter::register_agent) is in effect the
+//! adapter is the default pairing agent of the system: bluetoothd asks it, instead of the agent
+//! of the desktop, to confirm passkeys and to authorize devices and services. Each request is
+//! published as [`AdapterEvent::PairingRequested`](crate::adapter::AdapterEvent) and waits for
+//! [`Adapter::answer_pairing_request`](crate::adapter::Adapter::answer_pairing_request).
+//! Requests nobody answers are rejected once bluetoothd stops waiting for them.
+
+use bluer::{Address, Uuid};
+use serde::{Deserialize, Serialize};
+use tokio::sync::oneshot;
+
+use crate::{backend::BackendStream, uuids};
+
+/// What bluetoothd asks the agent
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PairingRequestKind {
+    /// Whether the passkey the device shows is this one
+    Confirmation { passkey: u32 },
+    /// Whether the device may pair without a passkey
+    Authorization,
+    /// Whether the device may use the service
+    Service { uuid: String, service: String },
+    /// Passkey to type on the device, nothing to answer
+    DisplayPasskey { passkey: u32 },
+    /// PIN code to type on the device, nothing to answer
+    DisplayPinCode { pin_code: String },
+}
+
+impl PairingRequestKind {
+    pub fn service(uuid: Uuid) -> Self {
+        PairingRequestKind::Service {
+            uuid: uuid.to_string(),
+            service: uuids::lookup(&uuid).label(),
+        }
+    }
+
+    /// Display requests only tell what to type on the device
+    pub fn needs_answer(&self) -> bool {
+        !matches!(
+            self,
+            PairingRequestKind::DisplayPasskey { .. } | PairingRequestKind::DisplayPinCode { .. }
+        )
+    }
+}
+
+/// A request of the agent as published to subscribers, answered by its `id`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PairingRequest {
+    pub id: u64,
+    pub address: [u8; 6],
+    pub address_string: String,
+    /// Alias or name of the device, empty when bluetoothd does not know one yet
+    pub name: String,
+    #[serde(flatten)]
+    pub kind: PairingRequestKind,
+}
+
+/// A request as the backend reports it. Dropping `answer` rejects the request
+#[derive(Debug)]
+pub struct AgentRequest {
+    pub address: Address,
+    pub kind: PairingRequestKind,
+    /// None for requests that do not need an answer
+    pub answer: Option<oneshot::Sender<bool>>,
+}
+
+pub type AgentRequests = BackendStream<AgentRequest>;
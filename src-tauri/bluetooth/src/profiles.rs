@@ -0,0 +1,204 @@
+//! Profile roles a device supports, derived from the service class UUIDs it advertises.
+//!
+//! Devices that were never connected often have no UUIDs yet, for BR/EDR ones the Class of
+//! Device then gives a first guess of the audio, input and network profiles.
+
+use std::fmt;
+
+use bluer::{Uuid, UuidExt};
+use serde::{Deserialize, Serialize};
+
+use crate::class_of_device::{AudioVideoClass, ClassOfDevice, MajorClass, MinorClass, PhoneClass};
+
+/// A profile in the role the device plays in it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Profile {
+    /// Plays audio streamed to it, like headphones
+    A2dpSink,
+    /// Streams audio, like phones
+    A2dpSource,
+    HfpHandsFree,
+    HfpAudioGateway,
+    HspHeadset,
+    HspAudioGateway,
+    /// HID over BR/EDR
+    Hid,
+    /// HID over GATT
+    Hogp,
+    PanUser,
+    PanNetworkAccessPoint,
+    PanGroupNetwork,
+    ObjectPush,
+    FileTransfer,
+    PbapServer,
+    PbapClient,
+    MapServer,
+    MapClient,
+    SerialPort,
+}
+
+const PROFILE_UUIDS: [(Profile, u16); 18] = [
+    (Profile::A2dpSink, 0x110b),
+    (Profile::A2dpSource, 0x110a),
+    (Profile::HfpHandsFree, 0x111e),
+    (Profile::HfpAudioGateway, 0x111f),
+    (Profile::HspHeadset, 0x1108),
+    (Profile::HspAudioGateway, 0x1112),
+    (Profile::Hid, 0x1124),
+    (Profile::Hogp, 0x1812),
+    (Profile::PanUser, 0x1115),
+    (Profile::PanNetworkAccessPoint, 0x1116),
+    (Profile::PanGroupNetwork, 0x1117),
+    (Profile::ObjectPush, 0x1105),
+    (Profile::FileTransfer, 0x1106),
+    (Profile::PbapServer, 0x112f),
+    (Profile::PbapClient, 0x112e),
+    (Profile::MapServer, 0x1132),
+    (Profile::MapClient, 0x1133),
+    (Profile::SerialPort, 0x1101),
+];
+
+impl Profile {
+    /// The 16 bit service class UUID of the role
+    pub fn uuid_u16(self) -> u16 {
+        PROFILE_UUIDS
+            .iter()
+            .find(|(profile, _)| *profile == self)
+            .map(|(_, uuid)| *uuid)
+            .expect("every profile has a UUID")
+    }
+
+    /// The service class UUID bluetoothd connects the profile by
+    pub fn uuid(self) -> Uuid {
+        Uuid::from_u16(self.uuid_u16())
+    }
+
+    fn from_uuid(uuid: &Uuid) -> Option<Self> {
+        let uuid = uuid.as_u16()?;
+        PROFILE_UUIDS
+            .iter()
+            .find(|(_, profile_uuid)| *profile_uuid == uuid)
+            .map(|(profile, _)| *profile)
+    }
+
+    /// Roles advertised through `uuids`, or guessed from the class when there are none,
+    /// in the order of this enum
+    pub fn detect<'a>(
+        uuids: impl IntoIterator<Item = &'a Uuid>,
+        class_of_device: Option<&ClassOfDevice>,
+    ) -> Vec<Profile> {
+        let mut profiles: Vec<Profile> = uuids.into_iter().filter_map(Self::from_uuid).collect();
+        if profiles.is_empty() {
+            profiles = class_of_device.map(Self::guess).unwrap_or_default();
+        }
+        profiles.sort_by_key(|profile| *profile as u8);
+        profiles.dedup();
+        profiles
+    }
+
+    fn guess(class_of_device: &ClassOfDevice) -> Vec<Profile> {
+        match (class_of_device.major, class_of_device.minor) {
+            (_, MinorClass::AudioVideo(AudioVideoClass::WearableHeadset)) => {
+                vec![
+                    Profile::A2dpSink,
+                    Profile::HfpHandsFree,
+                    Profile::HspHeadset,
+                ]
+            }
+            (_, MinorClass::AudioVideo(AudioVideoClass::HandsFree))
+            | (_, MinorClass::AudioVideo(AudioVideoClass::CarAudio)) => {
+                vec![Profile::A2dpSink, Profile::HfpHandsFree]
+            }
+            (_, MinorClass::AudioVideo(AudioVideoClass::Headphones))
+            | (_, MinorClass::AudioVideo(AudioVideoClass::Loudspeaker))
+            | (_, MinorClass::AudioVideo(AudioVideoClass::PortableAudio))
+            | (_, MinorClass::AudioVideo(AudioVideoClass::HifiAudio)) => vec![Profile::A2dpSink],
+            (_, MinorClass::Phone(PhoneClass::Cellular))
+            | (_, MinorClass::Phone(PhoneClass::Smartphone)) => {
+                vec![Profile::A2dpSource, Profile::HfpAudioGateway]
+            }
+            (MajorClass::Peripheral, _) => vec![Profile::Hid],
+            (MajorClass::NetworkAccessPoint, _) => vec![Profile::PanNetworkAccessPoint],
+            _ => vec![],
+        }
+    }
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Profile::A2dpSink => "A2DP sink",
+            Profile::A2dpSource => "A2DP source",
+            Profile::HfpHandsFree => "HFP hands-free",
+            Profile::HfpAudioGateway => "HFP audio gateway",
+            Profile::HspHeadset => "HSP headset",
+            Profile::HspAudioGateway => "HSP audio gateway",
+            Profile::Hid => "HID",
+            Profile::Hogp => "HID over GATT",
+            Profile::PanUser => "PAN user",
+            Profile::PanNetworkAccessPoint => "PAN network access point",
+            Profile::PanGroupNetwork => "PAN group network",
+            Profile::ObjectPush => "Object push",
+            Profile::FileTransfer => "File transfer",
+            Profile::PbapServer => "Phonebook access server",
+            Profile::PbapClient => "Phonebook access client",
+            Profile::MapServer => "Message access server",
+            Profile::MapClient => "Message access client",
+            Profile::SerialPort => "Serial port",
+        };
+        f.write_str(name)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bluer::{Uuid, UuidExt};
+
+    use super::{Profile, PROFILE_UUIDS};
+    use crate::class_of_device::ClassOfDevice;
+
+    #[test]
+    fn detects_roles_from_uuids() {
+        // a phone: OPP, A2DP source, AVRCP target, HSP and HFP gateway, NAP
+        let uuids: Vec<Uuid> = [0x1105, 0x110a, 0x110c, 0x1112, 0x1116, 0x111f]
+            .iter()
+            .map(|uuid| Uuid::from_u16(*uuid))
+            .collect();
+        let class = ClassOfDevice::decode(0x5a020c);
+        assert_eq!(
+            Profile::detect(&uuids, class.as_ref()),
+            vec![
+                Profile::A2dpSource,
+                Profile::HfpAudioGateway,
+                Profile::HspAudioGateway,
+                Profile::PanNetworkAccessPoint,
+                Profile::ObjectPush,
+            ]
+        );
+    }
+
+    #[test]
+    fn guesses_from_the_class_without_uuids() {
+        let headset = ClassOfDevice::decode(0x240404);
+        assert_eq!(
+            Profile::detect(&[], headset.as_ref()),
+            vec![
+                Profile::A2dpSink,
+                Profile::HfpHandsFree,
+                Profile::HspHeadset
+            ]
+        );
+        let keyboard = ClassOfDevice::decode(0x000540);
+        assert_eq!(Profile::detect(&[], keyboard.as_ref()), vec![Profile::Hid]);
+        assert_eq!(Profile::detect(&[], None), vec![]);
+    }
+
+    #[test]
+    fn every_profile_has_its_own_uuid() {
+        for (profile, uuid) in PROFILE_UUIDS {
+            assert_eq!(profile.uuid_u16(), uuid);
+            assert_eq!(Profile::from_uuid(&profile.uuid()), Some(profile));
+        }
+    }
+}
@@ -0,0 +1,408 @@
+//! Radio kill switch state of the bluetooth adapters.
+//!
+//! A soft block is set by software and can be lifted through `/dev/rfkill`, a hard block is a
+//! physical switch or firmware setting that only the user can change. bluetoothd refuses to
+//! power a blocked adapter, so [`Adapter`](crate::adapter::Adapter) follows the blocks and
+//! reports them in its [`AdapterInfo`](crate::adapter::AdapterInfo).
+
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    fs::{File, OpenOptions},
+    io::{self, Read, Write},
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use futures::{future, FutureExt, StreamExt};
+use log::debug;
+use tokio::sync::{broadcast, mpsc};
+use tokio_stream::wrappers::{BroadcastStream, UnboundedReceiverStream};
+
+use crate::{
+    backend::{BackendFuture, BackendStream},
+    error::{BluetoothError, Result},
+};
+
+const RFKILL_DEVICE: &str = "/dev/rfkill";
+// size of the original struct rfkill_event, newer kernels append fields the app does not need
+const RFKILL_EVENT_SIZE: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RfkillType {
+    All,
+    Wlan,
+    Bluetooth,
+    Other(u8),
+}
+
+impl RfkillType {
+    fn from_raw(kind: u8) -> Self {
+        match kind {
+            0 => RfkillType::All,
+            1 => RfkillType::Wlan,
+            2 => RfkillType::Bluetooth,
+            kind => RfkillType::Other(kind),
+        }
+    }
+
+    fn raw(self) -> u8 {
+        match self {
+            RfkillType::All => 0,
+            RfkillType::Wlan => 1,
+            RfkillType::Bluetooth => 2,
+            RfkillType::Other(kind) => kind,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RfkillOp {
+    Add,
+    Del,
+    Change,
+    ChangeAll,
+}
+
+impl RfkillOp {
+    fn from_raw(op: u8) -> Option<Self> {
+        match op {
+            0 => Some(RfkillOp::Add),
+            1 => Some(RfkillOp::Del),
+            2 => Some(RfkillOp::Change),
+            3 => Some(RfkillOp::ChangeAll),
+            _ => None,
+        }
+    }
+
+    fn raw(self) -> u8 {
+        match self {
+            RfkillOp::Add => 0,
+            RfkillOp::Del => 1,
+            RfkillOp::Change => 2,
+            RfkillOp::ChangeAll => 3,
+        }
+    }
+}
+
+/// One `struct rfkill_event` as read from or written to `/dev/rfkill`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RfkillEvent {
+    pub idx: u32,
+    pub kind: RfkillType,
+    pub op: RfkillOp,
+    pub soft: bool,
+    pub hard: bool,
+}
+
+impl RfkillEvent {
+    /// None when the buffer is too short or holds an unknown operation
+    pub fn parse(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < RFKILL_EVENT_SIZE {
+            return None;
+        }
+        Some(RfkillEvent {
+            idx: u32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+            kind: RfkillType::from_raw(bytes[4]),
+            op: RfkillOp::from_raw(bytes[5])?,
+            soft: bytes[6] != 0,
+            hard: bytes[7] != 0,
+        })
+    }
+
+    pub fn to_bytes(&self) -> [u8; RFKILL_EVENT_SIZE] {
+        let idx = self.idx.to_ne_bytes();
+        [
+            idx[0],
+            idx[1],
+            idx[2],
+            idx[3],
+            self.kind.raw(),
+            self.op.raw(),
+            self.soft as u8,
+            self.hard as u8,
+        ]
+    }
+}
+
+/// Block state of the bluetooth rfkill entries, a block on any of them blocks bluetooth
+#[derive(Debug, Clone, Default)]
+pub struct RfkillState {
+    entries: HashMap<u32, (bool, bool)>,
+}
+
+impl RfkillState {
+    /// Applies an event read from an [`RfkillSource`], returns whether the blocks changed
+    pub fn apply(&mut self, event: &RfkillEvent) -> bool {
+        if event.kind != RfkillType::Bluetooth {
+            return false;
+        }
+        let before = (self.soft_blocked(), self.hard_blocked());
+        match event.op {
+            RfkillOp::Add | RfkillOp::Change => {
+                self.entries.insert(event.idx, (event.soft, event.hard));
+            }
+            RfkillOp::Del => {
+                self.entries.remove(&event.idx);
+            }
+            RfkillOp::ChangeAll => {
+                for (soft, _) in self.entries.values_mut() {
+                    *soft = event.soft;
+                }
+            }
+        }
+        before != (self.soft_blocked(), self.hard_blocked())
+    }
+
+    pub fn soft_blocked(&self) -> bool {
+        self.entries.values().any(|(soft, _)| *soft)
+    }
+
+    pub fn hard_blocked(&self) -> bool {
+        self.entries.values().any(|(_, hard)| *hard)
+    }
+}
+
+/// Where rfkill events come from and soft blocks go to
+pub trait RfkillSource: Send + Sync + Debug {
+    /// One add event per existing entry, then every change until the source goes away
+    fn events(&self) -> BackendFuture<'_, BackendStream<RfkillEvent>>;
+
+    /// Sets or lifts the soft block of every bluetooth entry
+    fn set_soft_block(&self, blocked: bool) -> BackendFuture<'_, ()>;
+}
+
+fn io_error(err: io::Error) -> BluetoothError {
+    let details = format!("{}: {}", RFKILL_DEVICE, err);
+    match err.kind() {
+        io::ErrorKind::PermissionDenied => BluetoothError::PermissionDenied(details),
+        io::ErrorKind::NotFound => BluetoothError::NotAvailable(details),
+        _ => BluetoothError::Failed(details),
+    }
+}
+
+/// The kernel's rfkill state through `/dev/rfkill`
+#[derive(Debug, Clone, Default)]
+pub struct DevRfkill;
+
+impl DevRfkill {
+    pub fn new() -> Self {
+        DevRfkill
+    }
+}
+
+impl RfkillSource for DevRfkill {
+    fn events(&self) -> BackendFuture<'_, BackendStream<RfkillEvent>> {
+        async move {
+            let mut file = File::open(RFKILL_DEVICE).map_err(io_error)?;
+            let (tx, rx) = mpsc::unbounded_channel();
+            // reads block until the next event, the thread notices a dropped stream with the
+            // event after that
+            tokio::task::spawn_blocking(move || {
+                let mut buffer = [0; RFKILL_EVENT_SIZE];
+                loop {
+                    match file.read(&mut buffer) {
+                        Ok(0) => break,
+                        Ok(read) => {
+                            if let Some(event) = RfkillEvent::parse(&buffer[..read]) {
+                                if tx.send(event).is_err() {
+                                    break;
+                                }
+                            }
+                        }
+                        Err(err) => {
+                            debug!("stopped reading {}: {}", RFKILL_DEVICE, err);
+                            break;
+                        }
+                    }
+                }
+            });
+            Ok(UnboundedReceiverStream::new(rx).boxed())
+        }
+        .boxed()
+    }
+
+    fn set_soft_block(&self, blocked: bool) -> BackendFuture<'_, ()> {
+        async move {
+            let event = RfkillEvent {
+                idx: 0,
+                kind: RfkillType::Bluetooth,
+                op: RfkillOp::ChangeAll,
+                soft: blocked,
+                hard: false,
+            };
+            tokio::task::spawn_blocking(move || {
+                OpenOptions::new()
+                    .write(true)
+                    .open(RFKILL_DEVICE)?
+                    .write_all(&event.to_bytes())
+            })
+            .await
+            .map_err(|err| BluetoothError::Internal(err.to_string()))?
+            .map_err(io_error)
+        }
+        .boxed()
+    }
+}
+
+#[derive(Debug)]
+struct FakeState {
+    entries: HashMap<u32, RfkillEvent>,
+    events: broadcast::Sender<RfkillEvent>,
+}
+
+/// In-memory rfkill entries for tests and the simulator, cheap to clone and shared between
+/// clones
+#[derive(Debug, Clone)]
+pub struct FakeRfkill {
+    state: Arc<Mutex<FakeState>>,
+}
+
+impl Default for FakeRfkill {
+    fn default() -> Self {
+        let (events, _) = broadcast::channel(64);
+        Self {
+            state: Arc::new(Mutex::new(FakeState {
+                entries: HashMap::new(),
+                events,
+            })),
+        }
+    }
+}
+
+impl FakeRfkill {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, FakeState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Adds an entry, or changes it when the index is taken
+    pub fn set_entry(&self, idx: u32, kind: RfkillType, soft: bool, hard: bool) {
+        let mut state = self.lock();
+        let op = if state.entries.contains_key(&idx) {
+            RfkillOp::Change
+        } else {
+            RfkillOp::Add
+        };
+        let event = RfkillEvent {
+            idx,
+            kind,
+            op,
+            soft,
+            hard,
+        };
+        state.entries.insert(idx, event);
+        let _ = state.events.send(event);
+    }
+
+    pub fn remove_entry(&self, idx: u32) {
+        let mut state = self.lock();
+        if let Some(mut event) = state.entries.remove(&idx) {
+            event.op = RfkillOp::Del;
+            let _ = state.events.send(event);
+        }
+    }
+
+    /// Whether any bluetooth entry is blocked, the way bluetoothd sees it
+    pub fn is_blocked(&self) -> bool {
+        self.lock()
+            .entries
+            .values()
+            .any(|event| event.kind == RfkillType::Bluetooth && (event.soft || event.hard))
+    }
+}
+
+impl RfkillSource for FakeRfkill {
+    fn events(&self) -> BackendFuture<'_, BackendStream<RfkillEvent>> {
+        let state = self.lock();
+        let existing: Vec<RfkillEvent> = state
+            .entries
+            .values()
+            .map(|event| RfkillEvent {
+                op: RfkillOp::Add,
+                ..*event
+            })
+            .collect();
+        let changes = BroadcastStream::new(state.events.subscribe())
+            .filter_map(|event| future::ready(event.ok()));
+        future::ready(Ok(futures::stream::iter(existing).chain(changes).boxed())).boxed()
+    }
+
+    fn set_soft_block(&self, blocked: bool) -> BackendFuture<'_, ()> {
+        let bluetooth: Vec<(u32, bool)> = self
+            .lock()
+            .entries
+            .values()
+            .filter(|event| event.kind == RfkillType::Bluetooth)
+            .map(|event| (event.idx, event.hard))
+            .collect();
+        for (idx, hard) in bluetooth {
+            self.set_entry(idx, RfkillType::Bluetooth, blocked, hard);
+        }
+        future::ready(Ok(())).boxed()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{RfkillEvent, RfkillOp, RfkillState, RfkillType};
+
+    #[test]
+    fn events_round_trip() {
+        let event = RfkillEvent {
+            idx: 3,
+            kind: RfkillType::Bluetooth,
+            op: RfkillOp::Change,
+            soft: true,
+            hard: false,
+        };
+        assert_eq!(RfkillEvent::parse(&event.to_bytes()), Some(event));
+        // newer kernels append the hard block reasons
+        let mut extended = event.to_bytes().to_vec();
+        extended.push(0x01);
+        assert_eq!(RfkillEvent::parse(&extended), Some(event));
+        assert_eq!(RfkillEvent::parse(&extended[..4]), None);
+    }
+
+    #[test]
+    fn state_follows_bluetooth_entries_only() {
+        let event = |idx, kind, op, soft, hard| RfkillEvent {
+            idx,
+            kind,
+            op,
+            soft,
+            hard,
+        };
+        let mut state = RfkillState::default();
+        assert!(!state.apply(&event(0, RfkillType::Wlan, RfkillOp::Add, true, true)));
+        assert!(!state.apply(&event(
+            1,
+            RfkillType::Bluetooth,
+            RfkillOp::Add,
+            false,
+            false
+        )));
+        assert!(state.apply(&event(
+            1,
+            RfkillType::Bluetooth,
+            RfkillOp::Change,
+            false,
+            true
+        )));
+        assert!(state.hard_blocked() && !state.soft_blocked());
+        assert!(state.apply(&event(
+            0,
+            RfkillType::Bluetooth,
+            RfkillOp::ChangeAll,
+            true,
+            false
+        )));
+        assert!(state.soft_blocked());
+        assert!(state.apply(&event(1, RfkillType::Bluetooth, RfkillOp::Del, true, true)));
+        assert!(!state.hard_blocked() && !state.soft_blocked());
+    }
+}
@@ -0,0 +1,670 @@
+//! Byte channels to remote devices presented like a terminal.
+//!
+//! Every open channel gets an id, received data is published as [`TerminalEvent`]s and data
+//! is sent with [`Terminals::send`]. The channels themselves are opened elsewhere, see
+//! [`Adapter::open_serial`](crate::adapter::Adapter::open_serial),
+//! [`Adapter::listen_serial`](crate::adapter::Adapter::listen_serial),
+//! [`Adapter::open_l2cap`](crate::adapter::Adapter::open_l2cap) and
+//! [`Adapter::listen_l2cap`](crate::adapter::Adapter::listen_l2cap).
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use bluer::{Address, Uuid, UuidExt};
+use futures::{lock::Mutex, Stream};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use tokio::{
+    fs::{File, OpenOptions},
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    sync::broadcast,
+};
+use tokio_stream::{
+    wrappers::{errors::BroadcastStreamRecvError, BroadcastStream},
+    StreamExt,
+};
+
+use crate::{
+    device::DeviceInfo,
+    error::{BluetoothError, Result},
+};
+
+pub type ChannelId = u64;
+
+/// Serial port profile, what [`ChannelKind::Serial`] channels connect to
+pub const SERIAL_PORT_UUID: u16 = 0x1101;
+
+const EVENT_CHANNEL_CAPACITY: usize = 256;
+const READ_BUFFER_SIZE: usize = 4096;
+
+/// Appended to every line sent in text mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LineEnding {
+    None,
+    Lf,
+    Cr,
+    CrLf,
+}
+
+impl Default for LineEnding {
+    fn default() -> Self {
+        LineEnding::Lf
+    }
+}
+
+impl LineEnding {
+    fn bytes(self) -> &'static [u8] {
+        match self {
+            LineEnding::None => b"",
+            LineEnding::Lf => b"\n",
+            LineEnding::Cr => b"\r",
+            LineEnding::CrLf => b"\r\n",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TerminalOptions {
+    pub line_ending: LineEnding,
+    /// Sent data is parsed as hex bytes and received data is shown as hex
+    pub hex: bool,
+    /// Both directions of the session are appended to this file
+    pub log_path: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChannelKind {
+    /// RFCOMM connection to the serial port profile of a device
+    Serial,
+    /// RFCOMM connection a device opened to one of our [`SerialServer`]s
+    SerialServer,
+    /// LE L2CAP connection-oriented channel to a PSM of a device
+    L2cap,
+    /// LE L2CAP connection-oriented channel a device opened to one of our [`L2capServer`]s
+    L2capServer,
+}
+
+/// MTUs negotiated for an L2CAP channel, in bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChannelMtu {
+    pub send: u16,
+    pub receive: u16,
+}
+
+/// RFCOMM service the adapter offers to other devices
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SerialServer {
+    /// 16 bit or full UUID of the service, the serial port profile when empty
+    pub uuid: String,
+    pub name: String,
+    /// RFCOMM channel to listen on, bluetoothd picks a free one when None
+    pub channel: Option<u16>,
+    /// Options of the channels opened by incoming connections
+    pub options: TerminalOptions,
+}
+
+impl SerialServer {
+    pub fn parsed_uuid(&self) -> Result<Uuid> {
+        let uuid = self.uuid.trim();
+        let parsed = if uuid.is_empty() {
+            Some(Uuid::from_u16(SERIAL_PORT_UUID))
+        } else if uuid.len() <= 6 {
+            u16::from_str_radix(uuid.trim_start_matches("0x"), 16)
+                .ok()
+                .map(Uuid::from_u16)
+        } else {
+            Uuid::parse_str(uuid).ok()
+        };
+        parsed.ok_or_else(|| BluetoothError::InvalidArguments(format!("{} is not a UUID", uuid)))
+    }
+}
+
+/// LE L2CAP service the adapter offers to other devices
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct L2capServer {
+    /// PSM to listen on, a free dynamic one is picked when 0
+    pub psm: u16,
+    /// Options of the channels opened by incoming connections
+    pub options: TerminalOptions,
+}
+
+/// Serializable state of an open channel
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChannelInfo {
+    pub id: ChannelId,
+    pub address: String,
+    pub kind: ChannelKind,
+    pub options: TerminalOptions,
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+    /// Only L2CAP channels negotiate an MTU
+    pub mtu: Option<ChannelMtu>,
+    /// Average throughput since the channel was opened, in bytes per second
+    pub send_rate: u64,
+    pub receive_rate: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TerminalEvent {
+    Opened {
+        channel: ChannelInfo,
+        device: DeviceInfo,
+    },
+    /// An untrusted device tried to connect to a [`SerialServer`] or an [`L2capServer`],
+    /// `service` is the UUID or the PSM of the server
+    Rejected { address: String, service: String },
+    /// `data` is formatted according to the options of the channel
+    Received { id: ChannelId, data: String },
+    /// The channel was closed, `reason` is None when it was closed through
+    /// [`Terminals::close`]
+    Closed {
+        id: ChannelId,
+        reason: Option<String>,
+    },
+    /// The subscriber fell behind and missed this many events
+    Lagged { skipped: u64 },
+}
+
+/// Bytes to send for `input`, hex digits may be separated by whitespace in hex mode
+pub fn encode(input: &str, options: &TerminalOptions) -> Result<Vec<u8>> {
+    if !options.hex {
+        let mut bytes = input.as_bytes().to_vec();
+        bytes.extend_from_slice(options.line_ending.bytes());
+        return Ok(bytes);
+    }
+    let digits: Vec<char> = input.chars().filter(|c| !c.is_whitespace()).collect();
+    if digits.len() % 2 != 0 {
+        return Err(BluetoothError::InvalidArguments(String::from(
+            "Hex input needs two digits per byte",
+        )));
+    }
+    digits
+        .chunks(2)
+        .map(|pair| {
+            let pair: String = pair.iter().collect();
+            u8::from_str_radix(&pair, 16).map_err(|_| {
+                BluetoothError::InvalidArguments(format!("{} is not a hex byte", pair))
+            })
+        })
+        .collect()
+}
+
+/// Received bytes as shown to users, lossy UTF-8 in text mode
+pub fn format(bytes: &[u8], hex: bool) -> String {
+    if hex {
+        bytes
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect::<Vec<String>>()
+            .join(" ")
+    } else {
+        String::from_utf8_lossy(bytes).into_owned()
+    }
+}
+
+// Received text, where a read may end in the middle of a character. The incomplete tail is
+// kept for the next read, invalid bytes become U+FFFD like with from_utf8_lossy
+#[derive(Debug, Default)]
+struct Utf8Decoder {
+    pending: Vec<u8>,
+}
+
+impl Utf8Decoder {
+    fn decode(&mut self, bytes: &[u8]) -> String {
+        self.pending.extend_from_slice(bytes);
+        let mut text = String::new();
+        let mut rest = self.pending.as_slice();
+        while !rest.is_empty() {
+            match std::str::from_utf8(rest) {
+                Ok(valid) => {
+                    text.push_str(valid);
+                    rest = &[];
+                }
+                Err(err) => {
+                    let (valid, invalid) = rest.split_at(err.valid_up_to());
+                    text.push_str(std::str::from_utf8(valid).expect("valid up to here"));
+                    match err.error_len() {
+                        Some(len) => {
+                            text.push(char::REPLACEMENT_CHARACTER);
+                            rest = &invalid[len..];
+                        }
+                        None => {
+                            rest = invalid;
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+        let decoded = self.pending.len() - rest.len();
+        self.pending.drain(..decoded);
+        text
+    }
+
+    // what is left when the channel closes can no longer be completed
+    fn finish(&mut self) -> Option<String> {
+        if self.pending.is_empty() {
+            return None;
+        }
+        let text = String::from_utf8_lossy(&self.pending).into_owned();
+        self.pending.clear();
+        Some(text)
+    }
+}
+
+// One line per chunk: seconds since the epoch, > for sent and < for received, the data
+#[derive(Debug)]
+struct SessionLog {
+    file: Mutex<File>,
+    hex: bool,
+}
+
+impl SessionLog {
+    async fn open(path: &Path, hex: bool) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await?;
+        Ok(Self {
+            file: Mutex::new(file),
+            hex,
+        })
+    }
+
+    // `data` as formatted for the channel
+    async fn write(&self, direction: char, data: &str) {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let line = format!(
+            "{}.{:03} {} {}\n",
+            timestamp.as_secs(),
+            timestamp.subsec_millis(),
+            direction,
+            if self.hex {
+                data.to_string()
+            } else {
+                data.escape_debug().to_string()
+            }
+        );
+        if let Err(err) = self.file.lock().await.write_all(line.as_bytes()).await {
+            warn!("could not write the terminal log: {}", err);
+        }
+    }
+}
+
+type Writer = Box<dyn AsyncWrite + Send + Unpin>;
+
+struct Channel {
+    info: ChannelInfo,
+    opened: Instant,
+    writer: Arc<Mutex<Writer>>,
+    log: Option<Arc<SessionLog>>,
+    reader: tokio::task::JoinHandle<()>,
+}
+
+impl Channel {
+    // the first second counts as a whole one so a burst right after opening is not inflated
+    fn snapshot(&self) -> ChannelInfo {
+        let seconds = self.opened.elapsed().as_secs_f64().max(1.0);
+        ChannelInfo {
+            send_rate: (self.info.bytes_sent as f64 / seconds) as u64,
+            receive_rate: (self.info.bytes_received as f64 / seconds) as u64,
+            ..self.info.clone()
+        }
+    }
+}
+
+/// Open channels of an adapter, cheap to clone and shared between clones
+#[derive(Clone)]
+pub struct Terminals {
+    channels: Arc<Mutex<HashMap<ChannelId, Channel>>>,
+    next_id: Arc<AtomicU64>,
+    event_tx: broadcast::Sender<TerminalEvent>,
+}
+
+impl std::fmt::Debug for Terminals {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Terminals")
+            .field("next_id", &self.next_id)
+            .finish()
+    }
+}
+
+impl Default for Terminals {
+    fn default() -> Self {
+        let (event_tx, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        Self {
+            channels: Arc::new(Mutex::new(HashMap::new())),
+            next_id: Arc::new(AtomicU64::new(1)),
+            event_tx,
+        }
+    }
+}
+
+impl Terminals {
+    /// Same semantics as [`Adapter::subscribe`](crate::adapter::Adapter::subscribe)
+    pub fn subscribe(&self) -> impl Stream<Item = TerminalEvent> {
+        BroadcastStream::new(self.event_tx.subscribe()).map(|event| {
+            event.unwrap_or_else(|BroadcastStreamRecvError::Lagged(skipped)| {
+                TerminalEvent::Lagged { skipped }
+            })
+        })
+    }
+
+    /// Takes over `stream` and publishes everything it receives until it closes
+    pub async fn open<S>(
+        &self,
+        stream: S,
+        device: DeviceInfo,
+        kind: ChannelKind,
+        options: TerminalOptions,
+        mtu: Option<ChannelMtu>,
+    ) -> Result<ChannelInfo>
+    where
+        S: AsyncRead + AsyncWrite + Send + 'static,
+    {
+        let log = match &options.log_path {
+            Some(path) => Some(Arc::new(SessionLog::open(path, options.hex).await?)),
+            None => None,
+        };
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let info = ChannelInfo {
+            id,
+            address: device.address_string.clone(),
+            kind,
+            options,
+            bytes_sent: 0,
+            bytes_received: 0,
+            mtu,
+            send_rate: 0,
+            receive_rate: 0,
+        };
+        let (read_half, write_half) = tokio::io::split(stream);
+
+        // the reader only starts once the channel is registered
+        let mut channels = self.channels.lock().await;
+        let reader = tokio::spawn(Self::read_loop(
+            self.clone(),
+            id,
+            read_half,
+            info.options.hex,
+            log.clone(),
+        ));
+        channels.insert(
+            id,
+            Channel {
+                info: info.clone(),
+                opened: Instant::now(),
+                writer: Arc::new(Mutex::new(Box::new(write_half))),
+                log,
+                reader,
+            },
+        );
+        let _ = self.event_tx.send(TerminalEvent::Opened {
+            channel: info.clone(),
+            device,
+        });
+        Ok(info)
+    }
+
+    async fn read_loop(
+        self,
+        id: ChannelId,
+        mut reader: impl AsyncRead + Unpin,
+        hex: bool,
+        log: Option<Arc<SessionLog>>,
+    ) {
+        let mut buffer = vec![0; READ_BUFFER_SIZE];
+        let mut decoder = Utf8Decoder::default();
+        let reason = loop {
+            match reader.read(&mut buffer).await {
+                Ok(0) => break String::from("Closed by the remote device"),
+                Ok(read) => {
+                    let bytes = &buffer[..read];
+                    if let Some(channel) = self.channels.lock().await.get_mut(&id) {
+                        channel.info.bytes_received += read as u64;
+                    }
+                    let data = if hex {
+                        format(bytes, true)
+                    } else {
+                        decoder.decode(bytes)
+                    };
+                    self.received(id, data, log.as_deref()).await;
+                }
+                Err(err) => break err.to_string(),
+            }
+        };
+        if let Some(data) = decoder.finish() {
+            self.received(id, data, log.as_deref()).await;
+        }
+        if self.channels.lock().await.remove(&id).is_some() {
+            let _ = self.event_tx.send(TerminalEvent::Closed {
+                id,
+                reason: Some(reason),
+            });
+        }
+    }
+
+    async fn received(&self, id: ChannelId, data: String, log: Option<&SessionLog>) {
+        // nothing to show while the only character read so far is incomplete
+        if data.is_empty() {
+            return;
+        }
+        if let Some(log) = log {
+            log.write('<', &data).await;
+        }
+        let _ = self.event_tx.send(TerminalEvent::Received { id, data });
+    }
+
+    /// Encodes `input` with the options of the channel and sends it, returns the number of
+    /// bytes sent
+    pub async fn send(&self, id: ChannelId, input: &str) -> Result<usize> {
+        let (writer, log, bytes) = {
+            let channels = self.channels.lock().await;
+            let channel = channels.get(&id).ok_or_else(|| no_channel(id))?;
+            (
+                Arc::clone(&channel.writer),
+                channel.log.clone(),
+                encode(input, &channel.info.options)?,
+            )
+        };
+        {
+            let mut writer = writer.lock().await;
+            writer.write_all(&bytes).await?;
+            writer.flush().await?;
+        }
+        if let Some(log) = log {
+            log.write('>', &format(&bytes, log.hex)).await;
+        }
+        if let Some(channel) = self.channels.lock().await.get_mut(&id) {
+            channel.info.bytes_sent += bytes.len() as u64;
+        }
+        Ok(bytes.len())
+    }
+
+    pub async fn close(&self, id: ChannelId) -> Result<()> {
+        let channel = self
+            .channels
+            .lock()
+            .await
+            .remove(&id)
+            .ok_or_else(|| no_channel(id))?;
+        channel.reader.abort();
+        // the remote side may be gone already, the channel is closed either way
+        let _ = channel.writer.lock().await.shutdown().await;
+        let _ = self
+            .event_tx
+            .send(TerminalEvent::Closed { id, reason: None });
+        Ok(())
+    }
+
+    pub(crate) fn rejected(&self, address: Address, service: String) {
+        let _ = self.event_tx.send(TerminalEvent::Rejected {
+            address: address.to_string(),
+            service,
+        });
+    }
+
+    pub async fn channels(&self) -> Vec<ChannelInfo> {
+        let mut channels: Vec<ChannelInfo> = self
+            .channels
+            .lock()
+            .await
+            .values()
+            .map(Channel::snapshot)
+            .collect();
+        channels.sort_by_key(|channel| channel.id);
+        channels
+    }
+
+    /// Open channels to or from the device with `address`
+    pub async fn device_channels(&self, address: Address) -> Vec<ChannelInfo> {
+        let address = address.to_string();
+        let mut channels = self.channels().await;
+        channels.retain(|channel| channel.address == address);
+        channels
+    }
+}
+
+fn no_channel(id: ChannelId) -> BluetoothError {
+    BluetoothError::DoesNotExist(format!("No open channel {}", id))
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use futures::StreamExt;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use super::{
+        encode, format, ChannelKind, ChannelMtu, LineEnding, SerialServer, TerminalEvent,
+        TerminalOptions, Terminals, Utf8Decoder,
+    };
+    use crate::{device::DeviceInfo, error::BluetoothError, Result};
+
+    #[test]
+    fn parses_server_uuids() -> Result<()> {
+        let server = |uuid: &str| SerialServer {
+            uuid: uuid.to_string(),
+            ..SerialServer::default()
+        };
+        let spp = "00001101-0000-1000-8000-00805f9b34fb";
+        assert_eq!(server("").parsed_uuid()?.to_string(), spp);
+        assert_eq!(server("0x1101").parsed_uuid()?.to_string(), spp);
+        assert_eq!(server(spp).parsed_uuid()?.to_string(), spp);
+        assert!(server("serial").parsed_uuid().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn encodes_text_and_hex() -> Result<()> {
+        let crlf = TerminalOptions {
+            line_ending: LineEnding::CrLf,
+            ..TerminalOptions::default()
+        };
+        assert_eq!(encode("AT", &crlf)?, b"AT\r\n");
+        let hex = TerminalOptions {
+            hex: true,
+            ..TerminalOptions::default()
+        };
+        assert_eq!(encode("de ad BE\tef", &hex)?, vec![0xde, 0xad, 0xbe, 0xef]);
+        assert!(matches!(
+            encode("abc", &hex),
+            Err(BluetoothError::InvalidArguments(_))
+        ));
+        assert!(matches!(
+            encode("zz", &hex),
+            Err(BluetoothError::InvalidArguments(_))
+        ));
+        assert_eq!(format(&[0x0a, 0xff], true), "0a ff");
+        assert_eq!(format(b"OK\r\n", false), "OK\r\n");
+        Ok(())
+    }
+
+    #[test]
+    fn decodes_characters_split_across_reads() {
+        let mut decoder = Utf8Decoder::default();
+        let text = "25 °C ✓".as_bytes();
+        // the degree sign and the check mark are cut in two
+        assert_eq!(decoder.decode(&text[..4]), "25 ");
+        assert_eq!(decoder.decode(&text[4..9]), "°C ");
+        assert_eq!(decoder.decode(&text[9..]), "✓");
+        assert_eq!(decoder.finish(), None);
+
+        assert_eq!(
+            decoder.decode(&[b'a', 0xff, b'b', 0xe2, 0x9c]),
+            "a\u{fffd}b"
+        );
+        assert_eq!(decoder.finish().as_deref(), Some("\u{fffd}"));
+    }
+
+    #[tokio::test]
+    async fn channels_carry_data_both_ways() -> Result<()> {
+        let terminals = Terminals::default();
+        let mut events = Box::pin(terminals.subscribe());
+        let (local, mut remote) = tokio::io::duplex(64);
+        let device = DeviceInfo {
+            address_string: String::from("00:1B:66:44:55:66"),
+            ..DeviceInfo::default()
+        };
+        let channel = terminals
+            .open(
+                local,
+                device,
+                ChannelKind::L2cap,
+                TerminalOptions::default(),
+                Some(ChannelMtu {
+                    send: 247,
+                    receive: 512,
+                }),
+            )
+            .await?;
+
+        terminals.send(channel.id, "ping").await?;
+        let mut sent = [0; 5];
+        remote.read_exact(&mut sent).await?;
+        assert_eq!(&sent, b"ping\n");
+
+        remote.write_all(b"pong").await?;
+        let mut received = String::new();
+        while received != "pong" {
+            match tokio::time::timeout(Duration::from_secs(1), events.next()).await {
+                Ok(Some(TerminalEvent::Received { data, .. })) => received.push_str(&data),
+                Ok(Some(_)) => (),
+                _ => panic!("nothing received"),
+            }
+        }
+        let info = &terminals.channels().await[0];
+        assert_eq!((info.bytes_sent, info.bytes_received), (5, 4));
+        // nothing was open for a whole second yet
+        assert_eq!((info.send_rate, info.receive_rate), (5, 4));
+        assert_eq!(info.mtu.map(|mtu| mtu.send), Some(247));
+
+        drop(remote);
+        loop {
+            match tokio::time::timeout(Duration::from_secs(1), events.next()).await {
+                Ok(Some(TerminalEvent::Closed { reason, .. })) => {
+                    assert!(reason.is_some());
+                    break;
+                }
+                Ok(Some(_)) => (),
+                _ => panic!("channel was not closed"),
+            }
+        }
+        assert!(terminals.channels().await.is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,293 @@
+use bluer::Address;
+use serde::{Deserialize, Serialize};
+
+/// Highest absolute volume of a media transport, AVRCP volume is 7 bits
+pub const MAX_VOLUME: u16 = 127;
+
+/// Whether audio is streaming over the transport
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransportState {
+    Idle,
+    Pending,
+    Active,
+}
+
+impl Default for TransportState {
+    fn default() -> Self {
+        TransportState::Idle
+    }
+}
+
+impl TransportState {
+    /// Parses the `State` values of `org.bluez.MediaTransport1`
+    pub fn from_bluez(state: &str) -> Self {
+        match state {
+            "pending" => TransportState::Pending,
+            "active" => TransportState::Active,
+            _ => TransportState::Idle,
+        }
+    }
+}
+
+/// A2DP codec, vendor codecs are told apart by their vendor and codec ids
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Codec {
+    Sbc,
+    Mpeg12,
+    Aac,
+    Atrac,
+    AptX,
+    AptXHd,
+    Ldac,
+    Vendor { vendor_id: u32, codec_id: u16 },
+    Unknown(u8),
+}
+
+impl Default for Codec {
+    fn default() -> Self {
+        Codec::Sbc
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChannelMode {
+    Mono,
+    DualChannel,
+    Stereo,
+    JointStereo,
+}
+
+/// Negotiated codec and the parts of its configuration that matter to users, fields the
+/// codec does not have are None
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CodecInfo {
+    pub codec: Codec,
+    pub sample_rate: Option<u32>,
+    pub channel_mode: Option<ChannelMode>,
+    pub block_length: Option<u8>,
+    pub subbands: Option<u8>,
+    pub min_bitpool: Option<u8>,
+    pub max_bitpool: Option<u8>,
+    /// Bits per second
+    pub bitrate: Option<u32>,
+    pub variable_bitrate: Option<bool>,
+}
+
+// first set bit of `bits` picks the value at the same position of `values`, highest bit first
+fn pick<T: Copy>(bits: u8, values: &[(u8, T)]) -> Option<T> {
+    values
+        .iter()
+        .find(|(mask, _)| bits & mask != 0)
+        .map(|(_, value)| *value)
+}
+
+const A2DP_SBC: u8 = 0x00;
+const A2DP_MPEG12: u8 = 0x01;
+const A2DP_AAC: u8 = 0x02;
+const A2DP_ATRAC: u8 = 0x04;
+const A2DP_VENDOR: u8 = 0xff;
+
+const APTX_VENDOR: (u32, u16) = (0x0000_004f, 0x0001);
+const APTX_HD_VENDOR: (u32, u16) = (0x0000_00d7, 0x0024);
+const LDAC_VENDOR: (u32, u16) = (0x0000_012d, 0x00aa);
+
+impl CodecInfo {
+    /// Decodes the `Codec` and `Configuration` properties of a media transport, see the A2DP
+    /// specification for the layout of the configuration
+    pub fn decode(codec: u8, configuration: &[u8]) -> Self {
+        let byte = |index: usize| configuration.get(index).copied().unwrap_or_default();
+        match codec {
+            A2DP_SBC => CodecInfo {
+                codec: Codec::Sbc,
+                sample_rate: pick(
+                    byte(0),
+                    &[(0x80, 16000), (0x40, 32000), (0x20, 44100), (0x10, 48000)],
+                ),
+                channel_mode: pick(
+                    byte(0),
+                    &[
+                        (0x08, ChannelMode::Mono),
+                        (0x04, ChannelMode::DualChannel),
+                        (0x02, ChannelMode::Stereo),
+                        (0x01, ChannelMode::JointStereo),
+                    ],
+                ),
+                block_length: pick(byte(1), &[(0x80, 4), (0x40, 8), (0x20, 12), (0x10, 16)]),
+                subbands: pick(byte(1), &[(0x08, 4), (0x04, 8)]),
+                min_bitpool: configuration.get(2).copied(),
+                max_bitpool: configuration.get(3).copied(),
+                ..CodecInfo::default()
+            },
+            A2DP_AAC => CodecInfo {
+                codec: Codec::Aac,
+                sample_rate: pick(
+                    byte(1),
+                    &[
+                        (0x80, 8000),
+                        (0x40, 11025),
+                        (0x20, 12000),
+                        (0x10, 16000),
+                        (0x08, 22050),
+                        (0x04, 24000),
+                        (0x02, 32000),
+                        (0x01, 44100),
+                    ],
+                )
+                .or_else(|| {
+                    pick(
+                        byte(2),
+                        &[(0x80, 48000), (0x40, 64000), (0x20, 88200), (0x10, 96000)],
+                    )
+                }),
+                channel_mode: pick(
+                    byte(2),
+                    &[(0x08, ChannelMode::Mono), (0x04, ChannelMode::Stereo)],
+                ),
+                bitrate: (configuration.len() >= 6).then(|| {
+                    (u32::from(byte(3) & 0x7f) << 16)
+                        | (u32::from(byte(4)) << 8)
+                        | u32::from(byte(5))
+                }),
+                variable_bitrate: (configuration.len() >= 4).then(|| byte(3) & 0x80 != 0),
+                ..CodecInfo::default()
+            },
+            A2DP_MPEG12 => CodecInfo {
+                codec: Codec::Mpeg12,
+                ..CodecInfo::default()
+            },
+            A2DP_ATRAC => CodecInfo {
+                codec: Codec::Atrac,
+                ..CodecInfo::default()
+            },
+            A2DP_VENDOR if configuration.len() >= 6 => {
+                let vendor_id = u32::from_le_bytes([byte(0), byte(1), byte(2), byte(3)]);
+                let codec_id = u16::from_le_bytes([byte(4), byte(5)]);
+                match (vendor_id, codec_id) {
+                    APTX_VENDOR | APTX_HD_VENDOR => CodecInfo {
+                        codec: if (vendor_id, codec_id) == APTX_VENDOR {
+                            Codec::AptX
+                        } else {
+                            Codec::AptXHd
+                        },
+                        sample_rate: pick(
+                            byte(6),
+                            &[(0x80, 16000), (0x40, 32000), (0x20, 44100), (0x10, 48000)],
+                        ),
+                        channel_mode: pick(
+                            byte(6),
+                            &[(0x02, ChannelMode::Stereo), (0x01, ChannelMode::Mono)],
+                        ),
+                        ..CodecInfo::default()
+                    },
+                    LDAC_VENDOR => CodecInfo {
+                        codec: Codec::Ldac,
+                        sample_rate: pick(
+                            byte(6),
+                            &[
+                                (0x20, 44100),
+                                (0x10, 48000),
+                                (0x08, 88200),
+                                (0x04, 96000),
+                                (0x02, 176400),
+                                (0x01, 192000),
+                            ],
+                        ),
+                        channel_mode: pick(
+                            byte(7),
+                            &[
+                                (0x04, ChannelMode::Mono),
+                                (0x02, ChannelMode::DualChannel),
+                                (0x01, ChannelMode::Stereo),
+                            ],
+                        ),
+                        ..CodecInfo::default()
+                    },
+                    _ => CodecInfo {
+                        codec: Codec::Vendor {
+                            vendor_id,
+                            codec_id,
+                        },
+                        ..CodecInfo::default()
+                    },
+                }
+            }
+            codec => CodecInfo {
+                codec: Codec::Unknown(codec),
+                ..CodecInfo::default()
+            },
+        }
+    }
+}
+
+/// Serializable state of the A2DP media transport of a device
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransportInfo {
+    /// Profile UUID of the transport, A2DP source or sink
+    pub uuid: String,
+    pub state: TransportState,
+    /// Absolute volume from 0 to [`MAX_VOLUME`], None when the device does not support it
+    pub volume: Option<u16>,
+    /// Transport delay in 1/10 of milliseconds
+    pub delay: Option<u16>,
+    pub codec: CodecInfo,
+}
+
+/// The media transport of a device appeared or changed, or went away when `transport` is None
+#[derive(Debug, Clone)]
+pub struct MediaTransportEvent {
+    pub address: Address,
+    pub transport: Option<TransportInfo>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ChannelMode, Codec, CodecInfo};
+
+    #[test]
+    fn decodes_sbc() {
+        let info = CodecInfo::decode(0x00, &[0x21, 0x15, 2, 53]);
+        assert_eq!(info.codec, Codec::Sbc);
+        assert_eq!(info.sample_rate, Some(44100));
+        assert_eq!(info.channel_mode, Some(ChannelMode::JointStereo));
+        assert_eq!(info.block_length, Some(16));
+        assert_eq!(info.subbands, Some(8));
+        assert_eq!((info.min_bitpool, info.max_bitpool), (Some(2), Some(53)));
+    }
+
+    #[test]
+    fn decodes_aac() {
+        // MPEG-2 AAC LC, 48 kHz, stereo, VBR at 256 kbit/s
+        let info = CodecInfo::decode(0x02, &[0x80, 0x00, 0x84, 0x83, 0xe8, 0x00]);
+        assert_eq!(info.codec, Codec::Aac);
+        assert_eq!(info.sample_rate, Some(48000));
+        assert_eq!(info.channel_mode, Some(ChannelMode::Stereo));
+        assert_eq!(info.bitrate, Some(256000));
+        assert_eq!(info.variable_bitrate, Some(true));
+    }
+
+    #[test]
+    fn decodes_vendor_codecs() {
+        let aptx = CodecInfo::decode(0xff, &[0x4f, 0, 0, 0, 0x01, 0, 0x22]);
+        assert_eq!(aptx.codec, Codec::AptX);
+        assert_eq!(aptx.sample_rate, Some(44100));
+        assert_eq!(aptx.channel_mode, Some(ChannelMode::Stereo));
+
+        let ldac = CodecInfo::decode(0xff, &[0x2d, 0x01, 0, 0, 0xaa, 0, 0x04, 0x01]);
+        assert_eq!(ldac.codec, Codec::Ldac);
+        assert_eq!(ldac.sample_rate, Some(96000));
+        assert_eq!(ldac.channel_mode, Some(ChannelMode::Stereo));
+
+        let other = CodecInfo::decode(0xff, &[0x75, 0, 0, 0, 0x02, 0x01]);
+        assert_eq!(
+            other.codec,
+            Codec::Vendor {
+                vendor_id: 0x75,
+                codec_id: 0x0102
+            }
+        );
+        assert_eq!(CodecInfo::decode(0x09, &[]).codec, Codec::Unknown(0x09));
+    }
+}
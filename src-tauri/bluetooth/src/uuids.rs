@@ -0,0 +1,190 @@
+//! Lookup of service, characteristic and descriptor UUIDs.
+//!
+//! 16 bit UUIDs live on the Bluetooth base UUID and are looked up in the Assigned Numbers
+//! tables of the Bluetooth SIG, where the range decides the kind of an unlisted number.
+//! Full 128 bit UUIDs are only known when they belong to a handful of widely used vendor
+//! services.
+
+use std::collections::{HashMap, HashSet};
+
+use bluer::{Uuid, UuidExt};
+use serde::{Deserialize, Serialize};
+
+use crate::constants::{
+    CHARACTERISTIC_UUIDS, DECLARATION_UUIDS, DESCRIPTOR_UUIDS, GATT_SERVICE_UUIDS, MEMBER_UUIDS,
+    PROTOCOL_UUIDS, SDO_UUIDS, SERVICE_CLASS_UUIDS, VENDOR_UUIDS,
+};
+
+/// What a UUID identifies
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UuidKind {
+    Protocol,
+    /// Service class or profile, mostly found in SDP records of BR/EDR devices
+    ServiceClass,
+    GattService,
+    /// Attribute type of a GATT service, include or characteristic declaration
+    Declaration,
+    Descriptor,
+    Characteristic,
+    /// 16 bit UUID a SIG member bought for its own services
+    Member,
+    /// 16 bit UUID of a standards development organization
+    Sdo,
+    /// 128 bit UUID of a vendor service or characteristic
+    Vendor,
+    Unknown,
+}
+
+/// Everything known about a UUID
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UuidInfo {
+    pub kind: UuidKind,
+    /// None for numbers without an assigned name, like most member UUIDs
+    pub name: Option<String>,
+    /// Owner of a member or vendor UUID
+    pub company: Option<String>,
+}
+
+impl UuidInfo {
+    fn new(kind: UuidKind, name: Option<&str>, company: Option<&str>) -> Self {
+        Self {
+            kind,
+            name: name.map(String::from),
+            company: company.map(String::from),
+        }
+    }
+
+    /// The name, falling back to the company and then to "Unknown"
+    pub fn label(&self) -> String {
+        match (&self.name, &self.company) {
+            (Some(name), _) => name.clone(),
+            (None, Some(company)) => company.clone(),
+            (None, None) => String::from("Unknown"),
+        }
+    }
+}
+
+/// Looks the UUID up, 16 bit UUIDs through [`lookup_u16`]
+pub fn lookup(uuid: &Uuid) -> UuidInfo {
+    if let Some(short_uuid) = uuid.as_u16() {
+        return lookup_u16(short_uuid);
+    }
+    match VENDOR_UUIDS.get(uuid) {
+        Some((company, name)) => UuidInfo::new(UuidKind::Vendor, Some(*name), Some(*company)),
+        None => UuidInfo::new(UuidKind::Unknown, None, None),
+    }
+}
+
+/// Looks up a 16 bit UUID of the Bluetooth base UUID
+pub fn lookup_u16(uuid: u16) -> UuidInfo {
+    let tables: [(&HashMap<u16, &str>, UuidKind); 7] = [
+        (&PROTOCOL_UUIDS, UuidKind::Protocol),
+        (&SERVICE_CLASS_UUIDS, UuidKind::ServiceClass),
+        (&GATT_SERVICE_UUIDS, UuidKind::GattService),
+        (&DECLARATION_UUIDS, UuidKind::Declaration),
+        (&DESCRIPTOR_UUIDS, UuidKind::Descriptor),
+        (&CHARACTERISTIC_UUIDS, UuidKind::Characteristic),
+        (&SDO_UUIDS, UuidKind::Sdo),
+    ];
+    for (table, kind) in tables {
+        if let Some(name) = table.get(&uuid) {
+            return UuidInfo::new(kind, Some(*name), None);
+        }
+    }
+
+    // numbers assigned after the tables were written still fall into the right range
+    let kind = match uuid {
+        0x0001..=0x0100 => UuidKind::Protocol,
+        0x1800..=0x18ff => UuidKind::GattService,
+        0x1000..=0x1fff => UuidKind::ServiceClass,
+        0x2800..=0x28ff => UuidKind::Declaration,
+        0x2900..=0x29ff => UuidKind::Descriptor,
+        0x2a00..=0x2fff => UuidKind::Characteristic,
+        0xfd00..=0xfeff => UuidKind::Member,
+        _ => UuidKind::Unknown,
+    };
+    let company = match kind {
+        UuidKind::Member => MEMBER_UUIDS.get(&uuid).copied(),
+        _ => None,
+    };
+    UuidInfo::new(kind, None, company)
+}
+
+/// Describes every UUID keyed by its string form, as kept in the adapter and device models
+pub(crate) fn describe(uuids: HashSet<Uuid>) -> HashMap<String, UuidInfo> {
+    uuids
+        .iter()
+        .map(|uuid| (uuid.to_string(), lookup(uuid)))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use bluer::{Uuid, UuidExt};
+
+    use super::{lookup, lookup_u16, UuidInfo, UuidKind};
+
+    #[test]
+    fn tells_kinds_of_assigned_numbers_apart() {
+        assert_eq!(
+            lookup(&Uuid::from_u16(0x0003)),
+            UuidInfo::new(UuidKind::Protocol, Some("RFCOMM"), None)
+        );
+        assert_eq!(lookup_u16(0x110b).kind, UuidKind::ServiceClass);
+        assert_eq!(lookup_u16(0x180f).label(), "Battery Service");
+        assert_eq!(lookup_u16(0x2800).kind, UuidKind::Declaration);
+        assert_eq!(
+            lookup_u16(0x2902),
+            UuidInfo::new(
+                UuidKind::Descriptor,
+                Some("Client Characteristic Configuration"),
+                None
+            )
+        );
+        assert_eq!(
+            lookup_u16(0x2a37),
+            UuidInfo::new(
+                UuidKind::Characteristic,
+                Some("Heart Rate Measurement"),
+                None
+            )
+        );
+        assert_eq!(lookup_u16(0xfffd).kind, UuidKind::Sdo);
+    }
+
+    #[test]
+    fn labels_member_uuids_with_their_company() {
+        let eddystone = lookup_u16(0xfeaa);
+        assert_eq!(
+            eddystone,
+            UuidInfo::new(UuidKind::Member, None, Some("Google LLC"))
+        );
+        assert_eq!(eddystone.label(), "Google LLC");
+        assert_eq!(lookup_u16(0xfd01).kind, UuidKind::Member);
+    }
+
+    #[test]
+    fn falls_back_to_the_range_of_unlisted_numbers() {
+        assert_eq!(lookup_u16(0x2bff).kind, UuidKind::Characteristic);
+        assert_eq!(lookup_u16(0x29ff).kind, UuidKind::Descriptor);
+        assert_eq!(lookup_u16(0x18ff).kind, UuidKind::GattService);
+        assert_eq!(lookup_u16(0x0000).kind, UuidKind::Unknown);
+        assert_eq!(lookup_u16(0x0000).name, None);
+    }
+
+    #[test]
+    fn looks_up_vendor_uuids() {
+        let nordic_uart = Uuid::parse_str("6e400001-b5a3-f393-e0a9-e50e24dcca9e").unwrap();
+        assert_eq!(
+            lookup(&nordic_uart),
+            UuidInfo::new(
+                UuidKind::Vendor,
+                Some("Nordic UART Service"),
+                Some("Nordic Semiconductor ASA")
+            )
+        );
+        let random = Uuid::parse_str("0b7a5a4e-3f0c-4b55-9e3e-6f2f2a0e4a11").unwrap();
+        assert_eq!(lookup(&random).kind, UuidKind::Unknown);
+    }
+}
@@ -0,0 +1,356 @@
+use std::{collections::HashSet, path::PathBuf, process::ExitCode, str::FromStr};
+
+use anyhow::{bail, Result};
+use bluer::Address;
+use bluetooth::{
+    adapter::{AdapterEvent, AdapterInfo, DeviceEvent},
+    backend::simulator::Simulator,
+    device::DeviceInfo,
+    error::BluetoothError,
+    Client,
+};
+use serde::Serialize;
+use tokio_stream::StreamExt;
+
+const USAGE: &str = "\
+Usage: saiyan-blue-cli [--json] [--simulate] [--adapter <name>] <command>
+
+Commands:
+    adapters                    List the bluetooth adapters of the system
+    info                        Show the adapter details
+    power on|off                Power the adapter on or off
+    unblock                     Lift the rfkill soft block of bluetooth
+    scan [--timeout <seconds>]  Discover nearby devices, 10 seconds by default
+    devices                     List the known devices
+    export <file>               Write the known devices to a .json or .csv file
+    connect <address>           Connect to a device
+    disconnect <address>        Disconnect from a device
+    pair <address>              Pair with a device
+    watch                       Print adapter events until interrupted
+
+Options:
+    --json                      Print JSON instead of human readable output
+    --adapter <name>            Adapter to use, hci0 or the first adapter by default
+    --simulate                  Use an in-memory demo adapter instead of bluetoothd
+";
+
+const DEFAULT_SCAN_TIMEOUT: u64 = 10;
+
+enum Command {
+    Help,
+    Adapters,
+    Info,
+    Power(bool),
+    Unblock,
+    Scan(u64),
+    Devices,
+    Export(PathBuf),
+    Connect(Address),
+    Disconnect(Address),
+    Pair(Address),
+    Watch,
+}
+
+struct Options {
+    json: bool,
+    simulate: bool,
+    adapter: Option<String>,
+    command: Command,
+}
+
+fn parse_address(value: Option<String>) -> Result<Address> {
+    match value {
+        Some(value) => Ok(Address::from_str(&value)?),
+        None => bail!("missing device address"),
+    }
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options> {
+    let mut json = false;
+    let mut simulate = false;
+    let mut adapter = None;
+    let mut timeout = DEFAULT_SCAN_TIMEOUT;
+    let mut positional = vec![];
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => positional.insert(0, String::from("help")),
+            "--json" => json = true,
+            "--simulate" => simulate = true,
+            "--adapter" => match args.next() {
+                Some(name) => adapter = Some(name),
+                None => bail!("--adapter needs an adapter name"),
+            },
+            "--timeout" => match args.next().map(|value| value.parse()) {
+                Some(Ok(value)) => timeout = value,
+                _ => bail!("--timeout needs a number of seconds"),
+            },
+            _ => positional.push(arg),
+        }
+    }
+
+    let mut positional = positional.into_iter();
+    let command = match positional.next().as_deref() {
+        Some("help") => Command::Help,
+        Some("adapters") => Command::Adapters,
+        Some("info") => Command::Info,
+        Some("power") => match positional.next().as_deref() {
+            Some("on") => Command::Power(true),
+            Some("off") => Command::Power(false),
+            _ => bail!("power needs either on or off"),
+        },
+        Some("unblock") => Command::Unblock,
+        Some("scan") => Command::Scan(timeout),
+        Some("devices") => Command::Devices,
+        Some("export") => match positional.next() {
+            Some(path) => Command::Export(PathBuf::from(path)),
+            None => bail!("export needs a file name"),
+        },
+        Some("connect") => Command::Connect(parse_address(positional.next())?),
+        Some("disconnect") => Command::Disconnect(parse_address(positional.next())?),
+        Some("pair") => Command::Pair(parse_address(positional.next())?),
+        Some("watch") => Command::Watch,
+        Some(command) => bail!("unknown command {}", command),
+        None => bail!("missing command"),
+    };
+
+    Ok(Options {
+        json,
+        simulate,
+        adapter,
+        command,
+    })
+}
+
+fn print_json<T: Serialize>(value: &T) {
+    match serde_json::to_string(value) {
+        Ok(json) => println!("{}", json),
+        Err(err) => eprintln!("could not serialize output: {}", err),
+    }
+}
+
+fn print_adapter_info(info: &AdapterInfo) {
+    println!("Name:         {}", info.name);
+    println!("Alias:        {}", info.alias);
+    println!("Address:      {} ({})", info.address, info.address_type);
+    println!("Powered:      {} ({:?})", info.is_powered, info.power_state);
+    println!(
+        "Blocked:      soft={} hard={}",
+        info.soft_blocked, info.hard_blocked
+    );
+    println!("Discoverable: {}", info.is_discoverable);
+    println!("Pairable:     {}", info.pairable);
+    println!("Discovering:  {}", info.discovering);
+    match &info.class_of_device {
+        Some(class_of_device) => {
+            println!("Class:        {:#08x} {}", info.class, class_of_device)
+        }
+        None => println!("Class:        {:#08x}", info.class),
+    }
+    for (uuid, uuid_info) in &info.uuids {
+        println!("UUID:         {} {}", uuid, uuid_info.label());
+    }
+}
+
+fn print_device(device: &DeviceInfo) {
+    let mut flags = vec![];
+    if device.is_connected {
+        flags.push("connected");
+    }
+    if device.is_paired {
+        flags.push("paired");
+    }
+    if device.is_trusted {
+        flags.push("trusted");
+    }
+    if device.is_blocked {
+        flags.push("blocked");
+    }
+    println!(
+        "{}  {:<32}  {}",
+        device.address_string,
+        if device.alias.is_empty() {
+            &device.name
+        } else {
+            &device.alias
+        },
+        flags.join(",")
+    );
+}
+
+fn print_event(event: &AdapterEvent) {
+    match event {
+        AdapterEvent::AdapterPropertyChanged(info) => {
+            println!(
+                "adapter {} power={:?} discoverable={} discovering={}",
+                info.name, info.power_state, info.is_discoverable, info.discovering
+            );
+        }
+        AdapterEvent::DeviceChanged(delta) => match &delta.event {
+            DeviceEvent::DeviceAdded { device } => {
+                print!("[{}] added    ", delta.seq);
+                print_device(device);
+            }
+            DeviceEvent::DeviceRemoved { device } => {
+                print!("[{}] removed  ", delta.seq);
+                print_device(device);
+            }
+            DeviceEvent::DeviceUpdated { device, patch } => {
+                let changes = patch
+                    .changes
+                    .iter()
+                    .map(|(field, value)| format!("{}={}", field, value))
+                    .collect::<Vec<String>>();
+                println!(
+                    "[{}] updated  {}  {}",
+                    delta.seq,
+                    device.address_string,
+                    changes.join(" ")
+                );
+            }
+        },
+        AdapterEvent::DiscoveryStarted(timeout) => {
+            println!("discovery started for {} seconds", timeout)
+        }
+        AdapterEvent::DiscoveryStopped(reason) => println!("discovery stopped: {:?}", reason),
+        AdapterEvent::PairingRequested(request) => {
+            println!(
+                "pairing request {:?} from {}",
+                request.kind, request.address_string
+            )
+        }
+        AdapterEvent::Lagged(skipped) => println!("missed {} events", skipped),
+    }
+}
+
+async fn run(options: Options) -> Result<()> {
+    if let Command::Help = options.command {
+        print!("{}", USAGE);
+        return Ok(());
+    }
+
+    let mut client = if options.simulate {
+        let simulator = Simulator::demo();
+        simulator.spawn_demo_script();
+        Client::simulated(&simulator).await?
+    } else {
+        Client::with_adapter(options.adapter.as_deref()).await?
+    };
+
+    match options.command {
+        Command::Help => (),
+        Command::Adapters => {
+            if options.json {
+                print_json(client.adapter_names());
+            } else {
+                for name in client.adapter_names() {
+                    println!("{}", name);
+                }
+            }
+        }
+        Command::Info => {
+            let info = client.adapter().adapter_info().await;
+            if options.json {
+                print_json(&info);
+            } else {
+                print_adapter_info(&info);
+            }
+        }
+        Command::Power(powered) => client.adapter().set_powered(powered).await?,
+        Command::Unblock => client.adapter().unblock().await?,
+        Command::Scan(timeout) => {
+            let mut events = Box::pin(client.adapter().subscribe());
+            client.adapter_mut().discover_devices(timeout).await?;
+            let mut found = HashSet::new();
+            while let Some(event) = events.next().await {
+                match &event {
+                    AdapterEvent::DeviceChanged(delta) => {
+                        // devices bluetoothd knows already are not added again, they are only
+                        // heard, which updates their signal strength
+                        let device = match &delta.event {
+                            DeviceEvent::DeviceAdded { device } => device,
+                            DeviceEvent::DeviceUpdated { device, patch }
+                                if device.rssi.is_some()
+                                    && (patch.changes.contains_key("rssi")
+                                        || patch.changes.contains_key("last_seen")) =>
+                            {
+                                device
+                            }
+                            _ => continue,
+                        };
+                        if found.insert(device.address) {
+                            if options.json {
+                                print_json(device);
+                            } else {
+                                print_device(device);
+                            }
+                        }
+                    }
+                    AdapterEvent::DiscoveryStopped(_) => break,
+                    _ => (),
+                }
+            }
+        }
+        Command::Devices => {
+            let devices = client.adapter().known_devices().await;
+            if options.json {
+                print_json(&devices);
+            } else {
+                devices.iter().for_each(print_device);
+            }
+        }
+        Command::Export(path) => {
+            let count = client.adapter().export_devices(&path, None, None).await?;
+            if options.json {
+                print_json(&count);
+            } else {
+                println!("wrote {} devices to {}", count, path.display());
+            }
+        }
+        Command::Connect(address) => client.adapter().connect_device(address).await?,
+        Command::Disconnect(address) => client.adapter().disconnect_device(address).await?,
+        Command::Pair(address) => client.adapter().pair_device(address).await?,
+        Command::Watch => {
+            let mut events = Box::pin(client.adapter().subscribe());
+            while let Some(event) = events.next().await {
+                if options.json {
+                    print_json(&event);
+                } else {
+                    print_event(&event);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{}\n\n{}", err, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    let json = options.json;
+
+    match run(options).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            match err.downcast::<BluetoothError>() {
+                Ok(err) if json => match serde_json::to_string(&err) {
+                    Ok(output) => eprintln!("{}", output),
+                    Err(_) => eprintln!("{}", err),
+                },
+                Ok(err) => match err.details() {
+                    Some(details) => eprintln!("error: {} ({})", err, details),
+                    None => eprintln!("error: {}", err),
+                },
+                Err(err) => eprintln!("error: {}", err),
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use bluetooth::{adapter::DeviceEvent, device_list::DeviceDelta};
+
+// Deltas emitted to the webview in one go. `first_seq..=last_seq` covers every change
+// folded into the batch, a gap to the previous batch means the frontend has to resync
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceDeltaBatch {
+    pub first_seq: u64,
+    pub last_seq: u64,
+    pub deltas: Vec<DeviceDelta>,
+}
+
+// Buffers device deltas between flushes, merging repeated updates of the same device
+// into a single patch
+#[derive(Default)]
+pub struct DeltaCoalescer {
+    first_seq: Option<u64>,
+    last_seq: u64,
+    deltas: Vec<DeviceDelta>,
+    pending_updates: HashMap<String, usize>,
+}
+
+impl DeltaCoalescer {
+    pub fn push(&mut self, delta: DeviceDelta) {
+        self.first_seq.get_or_insert(delta.seq);
+        self.last_seq = delta.seq;
+
+        let DeviceDelta { seq, event } = delta;
+        let event = match event {
+            DeviceEvent::DeviceUpdated { device, patch } => {
+                if let Some(&index) = self.pending_updates.get(&patch.address) {
+                    let pending = &mut self.deltas[index];
+                    pending.seq = seq;
+                    if let DeviceEvent::DeviceUpdated {
+                        device: pending_device,
+                        patch: pending_patch,
+                    } = &mut pending.event
+                    {
+                        *pending_device = device;
+                        pending_patch.merge(patch);
+                    }
+                    return;
+                }
+                self.pending_updates
+                    .insert(patch.address.clone(), self.deltas.len());
+                DeviceEvent::DeviceUpdated { device, patch }
+            }
+            DeviceEvent::DeviceAdded { device } => {
+                self.pending_updates.remove(&device.address_string);
+                DeviceEvent::DeviceAdded { device }
+            }
+            DeviceEvent::DeviceRemoved { device } => {
+                self.pending_updates.remove(&device.address_string);
+                DeviceEvent::DeviceRemoved { device }
+            }
+        };
+        self.deltas.push(DeviceDelta { seq, event });
+    }
+
+    pub fn take(&mut self) -> Option<DeviceDeltaBatch> {
+        let first_seq = self.first_seq.take()?;
+        self.pending_updates.clear();
+        Some(DeviceDeltaBatch {
+            first_seq,
+            last_seq: self.last_seq,
+            deltas: std::mem::take(&mut self.deltas),
+        })
+    }
+
+    pub fn clear(&mut self) {
+        self.first_seq = None;
+        self.deltas.clear();
+        self.pending_updates.clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::{Map, Value};
+
+    use super::DeltaCoalescer;
+    use bluetooth::{
+        adapter::DeviceEvent,
+        device::{DeviceInfo, DevicePatch},
+        device_list::DeviceDelta,
+    };
+
+    fn device(address: &str) -> DeviceInfo {
+        DeviceInfo {
+            address_string: address.to_string(),
+            ..DeviceInfo::default()
+        }
+    }
+
+    fn update(seq: u64, address: &str, field: &str, value: Value) -> DeviceDelta {
+        let mut changes = Map::new();
+        changes.insert(field.to_string(), value);
+        DeviceDelta {
+            seq,
+            event: DeviceEvent::DeviceUpdated {
+                device: device(address),
+                patch: DevicePatch {
+                    address: address.to_string(),
+                    changes,
+                },
+            },
+        }
+    }
+
+    #[test]
+    fn merges_updates_of_the_same_device() {
+        let mut coalescer = DeltaCoalescer::default();
+        coalescer.push(update(1, "A", "name", Value::from("one")));
+        coalescer.push(update(2, "B", "name", Value::from("other")));
+        coalescer.push(update(3, "A", "name", Value::from("two")));
+        coalescer.push(update(4, "A", "is_connected", Value::from(true)));
+
+        let batch = coalescer.take().unwrap();
+        assert_eq!((batch.first_seq, batch.last_seq), (1, 4));
+        assert_eq!(batch.deltas.len(), 2);
+        assert_eq!(batch.deltas[0].seq, 4);
+        match &batch.deltas[0].event {
+            DeviceEvent::DeviceUpdated { patch, .. } => {
+                assert_eq!(patch.changes["name"], Value::from("two"));
+                assert_eq!(patch.changes["is_connected"], Value::from(true));
+            }
+            event => panic!("unexpected event {:?}", event),
+        }
+        assert!(coalescer.take().is_none());
+    }
+
+    #[test]
+    fn keeps_updates_ordered_around_removal() {
+        let mut coalescer = DeltaCoalescer::default();
+        coalescer.push(update(1, "A", "name", Value::from("one")));
+        coalescer.push(DeviceDelta {
+            seq: 2,
+            event: DeviceEvent::DeviceRemoved {
+                device: device("A"),
+            },
+        });
+        coalescer.push(DeviceDelta {
+            seq: 3,
+            event: DeviceEvent::DeviceAdded {
+                device: device("A"),
+            },
+        });
+        coalescer.push(update(4, "A", "name", Value::from("two")));
+
+        let batch = coalescer.take().unwrap();
+        assert_eq!(batch.deltas.len(), 4);
+    }
+}
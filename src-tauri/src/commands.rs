@@ -1,15 +1,35 @@
-use std::sync::Arc;
+use std::{path::PathBuf, sync::Arc};
 
 use bluer::Address;
 use tauri::State;
 use tokio::sync::Mutex;
 
-use crate::bluetooth::{adapter::AdapterInfo, device::DeviceInfo, Client};
+use bluetooth::{
+    adapter::AdapterInfo,
+    capture::{CaptureFormat, CaptureInfo},
+    device::DeviceInfo,
+    device_list::DeviceSnapshot,
+    device_query::{DevicePage, DeviceQuery},
+    error::BluetoothError,
+    inventory::{Inventory, InventoryFormat},
+    media::MediaCommand,
+    network::{NetworkServer, PanRole},
+    profiles::Profile,
+    terminal::{ChannelId, ChannelInfo, L2capServer, SerialServer, TerminalOptions},
+    transport::TransportInfo,
+    Client,
+};
+
+use crate::notifications::NotificationSettings;
 
 pub struct SharedState(pub Arc<Mutex<Client>>);
 
+pub struct NotificationState(pub Arc<Mutex<NotificationSettings>>);
+
+type CommandResult<T> = Result<T, BluetoothError>;
+
 #[tauri::command]
-pub async fn adapter_info(state: State<'_, SharedState>) -> Result<AdapterInfo, ()> {
+pub async fn adapter_info(state: State<'_, SharedState>) -> CommandResult<AdapterInfo> {
     let state_clone = Arc::clone(&state.0);
     let client: tokio::sync::MutexGuard<Client> = state_clone.lock().await;
     let adapter_info = client.adapter().adapter_info().await;
@@ -17,18 +37,26 @@ pub async fn adapter_info(state: State<'_, SharedState>) -> Result<AdapterInfo,
 }
 
 #[tauri::command]
-pub async fn set_alias(alias: String, state: State<'_, SharedState>) -> Result<bool, ()> {
+pub async fn set_alias(alias: String, state: State<'_, SharedState>) -> CommandResult<bool> {
+    let state_clone = Arc::clone(&state.0);
+    let client = state_clone.lock().await;
+    client.adapter().set_alias(alias).await?;
+    Ok(true)
+}
+
+#[tauri::command]
+pub async fn set_powered(powered: bool, state: State<'_, SharedState>) -> CommandResult<bool> {
     let state_clone = Arc::clone(&state.0);
     let client = state_clone.lock().await;
-    client.adapter().set_alias(alias).await;
+    client.adapter().set_powered(powered).await?;
     Ok(true)
 }
 
 #[tauri::command]
-pub async fn set_powered(powered: bool, state: State<'_, SharedState>) -> Result<bool, ()> {
+pub async fn unblock(state: State<'_, SharedState>) -> CommandResult<bool> {
     let state_clone = Arc::clone(&state.0);
     let client = state_clone.lock().await;
-    client.adapter().set_powered(powered).await;
+    client.adapter().unblock().await?;
     Ok(true)
 }
 
@@ -36,10 +64,10 @@ pub async fn set_powered(powered: bool, state: State<'_, SharedState>) -> Result
 pub async fn set_discoverable(
     discoverable: bool,
     state: State<'_, SharedState>,
-) -> Result<bool, ()> {
+) -> CommandResult<bool> {
     let state_clone = Arc::clone(&state.0);
     let client = state_clone.lock().await;
-    client.adapter().set_discoverable(discoverable).await;
+    client.adapter().set_discoverable(discoverable).await?;
     Ok(true)
 }
 
@@ -47,26 +75,26 @@ pub async fn set_discoverable(
 pub async fn set_discoverable_timeout(
     discoverable_timeout: u32,
     state: State<'_, SharedState>,
-) -> Result<bool, ()> {
+) -> CommandResult<bool> {
     let state_clone = Arc::clone(&state.0);
     let client = state_clone.lock().await;
     client
         .adapter()
         .set_discoverable_timeout(discoverable_timeout)
-        .await;
+        .await?;
     Ok(true)
 }
 
 #[tauri::command]
-pub async fn set_pairable(pairable: bool, state: State<'_, SharedState>) -> Result<bool, ()> {
+pub async fn set_pairable(pairable: bool, state: State<'_, SharedState>) -> CommandResult<bool> {
     let state_clone = Arc::clone(&state.0);
     let client = state_clone.lock().await;
-    client.adapter().set_pairable(pairable).await;
+    client.adapter().set_pairable(pairable).await?;
     Ok(true)
 }
 
 #[tauri::command]
-pub async fn known_devices(state: State<'_, SharedState>) -> Result<Vec<DeviceInfo>, String> {
+pub async fn known_devices(state: State<'_, SharedState>) -> CommandResult<Vec<DeviceInfo>> {
     let state_clone = Arc::clone(&state.0);
     let client = state_clone.lock().await;
     let result = client.adapter().known_devices().await;
@@ -74,7 +102,86 @@ pub async fn known_devices(state: State<'_, SharedState>) -> Result<Vec<DeviceIn
 }
 
 #[tauri::command]
-pub async fn cancel_discovering(state: State<'_, SharedState>) -> Result<(), ()> {
+pub async fn device_snapshot(state: State<'_, SharedState>) -> CommandResult<DeviceSnapshot> {
+    let state_clone = Arc::clone(&state.0);
+    let client = state_clone.lock().await;
+    Ok(client.adapter().device_snapshot().await)
+}
+
+#[tauri::command]
+pub async fn query_devices(
+    query: DeviceQuery,
+    state: State<'_, SharedState>,
+) -> CommandResult<DevicePage> {
+    let state_clone = Arc::clone(&state.0);
+    let client = state_clone.lock().await;
+    client.adapter().query_devices(&query).await
+}
+
+#[tauri::command]
+pub async fn set_device_tags(
+    address: [u8; 6],
+    tags: Vec<String>,
+    state: State<'_, SharedState>,
+) -> CommandResult<()> {
+    let state_clone = Arc::clone(&state.0);
+    let client = state_clone.lock().await;
+    client
+        .adapter()
+        .set_device_tags(Address::new(address), tags)
+        .await
+}
+
+#[tauri::command]
+pub async fn export_devices(
+    path: PathBuf,
+    format: Option<InventoryFormat>,
+    query: Option<DeviceQuery>,
+    state: State<'_, SharedState>,
+) -> CommandResult<usize> {
+    let adapter = {
+        let state_clone = Arc::clone(&state.0);
+        let client = state_clone.lock().await;
+        client.adapter().clone()
+    };
+    adapter.export_devices(&path, format, query.as_ref()).await
+}
+
+#[tauri::command]
+pub async fn import_devices(
+    path: PathBuf,
+    format: Option<InventoryFormat>,
+) -> CommandResult<Inventory> {
+    Inventory::read(&path, format).await
+}
+
+#[tauri::command]
+pub async fn start_capture(
+    path: PathBuf,
+    format: Option<CaptureFormat>,
+    state: State<'_, SharedState>,
+) -> CommandResult<CaptureInfo> {
+    let state_clone = Arc::clone(&state.0);
+    let client = state_clone.lock().await;
+    client.adapter().start_capture(&path, format).await
+}
+
+#[tauri::command]
+pub async fn stop_capture(state: State<'_, SharedState>) -> CommandResult<CaptureInfo> {
+    let state_clone = Arc::clone(&state.0);
+    let client = state_clone.lock().await;
+    client.adapter().stop_capture().await
+}
+
+#[tauri::command]
+pub async fn capture_info(state: State<'_, SharedState>) -> CommandResult<Option<CaptureInfo>> {
+    let state_clone = Arc::clone(&state.0);
+    let client = state_clone.lock().await;
+    Ok(client.adapter().capture_info().await)
+}
+
+#[tauri::command]
+pub async fn cancel_discovering(state: State<'_, SharedState>) -> CommandResult<()> {
     let state_clone = Arc::clone(&state.0);
     let mut client = state_clone.lock().await;
     client.adapter_mut().cancel_discovering().await;
@@ -82,45 +189,339 @@ pub async fn cancel_discovering(state: State<'_, SharedState>) -> Result<(), ()>
 }
 
 #[tauri::command]
-pub async fn discover_devices(timeout: u64, state: State<'_, SharedState>) -> Result<(), String> {
+pub async fn discover_devices(timeout: u64, state: State<'_, SharedState>) -> CommandResult<()> {
     let state_clone = Arc::clone(&state.0);
     let mut client = state_clone.lock().await;
-
-    if let Err(err) = client.adapter_mut().discover_devices(timeout).await {
-        Err(err.to_string())
-    } else {
-        Ok(())
-    }
+    client.adapter_mut().discover_devices(timeout).await?;
+    Ok(())
 }
 
 #[tauri::command]
-pub async fn connect(address: [u8; 6], state: State<'_, SharedState>) -> Result<(), String> {
+pub async fn connect(address: [u8; 6], state: State<'_, SharedState>) -> CommandResult<()> {
     let state_clone = Arc::clone(&state.0);
     let mut client = state_clone.lock().await;
-
-    if let Err(err) = client
+    client
         .adapter_mut()
         .connect_device(Address::new(address))
-        .await
-    {
-        Err(err.to_string())
-    } else {
-        Ok(())
-    }
+        .await?;
+    Ok(())
 }
 
 #[tauri::command]
-pub async fn disconnect(address: [u8; 6], state: State<'_, SharedState>) -> Result<(), String> {
+pub async fn disconnect(address: [u8; 6], state: State<'_, SharedState>) -> CommandResult<()> {
     let state_clone = Arc::clone(&state.0);
     let mut client = state_clone.lock().await;
-
-    if let Err(err) = client
+    client
         .adapter_mut()
         .disconnect_device(Address::new(address))
+        .await?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn connect_profile(
+    address: [u8; 6],
+    profile: Profile,
+    state: State<'_, SharedState>,
+) -> CommandResult<()> {
+    // bluetoothd answers once the profile is up, which can take seconds
+    let adapter = {
+        let state_clone = Arc::clone(&state.0);
+        let client = state_clone.lock().await;
+        client.adapter().clone()
+    };
+    adapter
+        .connect_profile(Address::new(address), profile)
+        .await
+}
+
+#[tauri::command]
+pub async fn disconnect_profile(
+    address: [u8; 6],
+    profile: Profile,
+    state: State<'_, SharedState>,
+) -> CommandResult<()> {
+    let adapter = {
+        let state_clone = Arc::clone(&state.0);
+        let client = state_clone.lock().await;
+        client.adapter().clone()
+    };
+    adapter
+        .disconnect_profile(Address::new(address), profile)
+        .await
+}
+
+#[tauri::command]
+pub async fn connect_network(
+    address: [u8; 6],
+    role: PanRole,
+    state: State<'_, SharedState>,
+) -> CommandResult<String> {
+    // bluetoothd answers once the bnep interface is up
+    let adapter = {
+        let state_clone = Arc::clone(&state.0);
+        let client = state_clone.lock().await;
+        client.adapter().clone()
+    };
+    adapter.connect_network(Address::new(address), role).await
+}
+
+#[tauri::command]
+pub async fn disconnect_network(
+    address: [u8; 6],
+    state: State<'_, SharedState>,
+) -> CommandResult<()> {
+    let state_clone = Arc::clone(&state.0);
+    let client = state_clone.lock().await;
+    client
+        .adapter()
+        .disconnect_network(Address::new(address))
         .await
-    {
-        Err(err.to_string())
-    } else {
-        Ok(())
+}
+
+#[tauri::command]
+pub async fn media_command(
+    address: [u8; 6],
+    command: MediaCommand,
+    state: State<'_, SharedState>,
+) -> CommandResult<()> {
+    let state_clone = Arc::clone(&state.0);
+    let client = state_clone.lock().await;
+    client
+        .adapter()
+        .media_command(Address::new(address), command)
+        .await?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn media_transport(
+    address: [u8; 6],
+    state: State<'_, SharedState>,
+) -> CommandResult<Option<TransportInfo>> {
+    let state_clone = Arc::clone(&state.0);
+    let client = state_clone.lock().await;
+    client
+        .adapter()
+        .media_transport(Address::new(address))
+        .await
+}
+
+#[tauri::command]
+pub async fn set_volume(
+    address: [u8; 6],
+    volume: u16,
+    state: State<'_, SharedState>,
+) -> CommandResult<()> {
+    let state_clone = Arc::clone(&state.0);
+    let client = state_clone.lock().await;
+    client
+        .adapter()
+        .set_volume(Address::new(address), volume)
+        .await?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn open_serial(
+    address: [u8; 6],
+    channel: Option<u8>,
+    options: TerminalOptions,
+    state: State<'_, SharedState>,
+) -> CommandResult<ChannelInfo> {
+    // connecting goes through SDP and can take seconds, keep other commands going meanwhile
+    let adapter = {
+        let state_clone = Arc::clone(&state.0);
+        let client = state_clone.lock().await;
+        client.adapter().clone()
+    };
+    adapter
+        .open_serial(Address::new(address), channel, options)
+        .await
+}
+
+#[tauri::command]
+pub async fn terminal_send(
+    id: ChannelId,
+    data: String,
+    state: State<'_, SharedState>,
+) -> CommandResult<usize> {
+    let state_clone = Arc::clone(&state.0);
+    let client = state_clone.lock().await;
+    client.adapter().terminal_send(id, &data).await
+}
+
+#[tauri::command]
+pub async fn close_terminal(id: ChannelId, state: State<'_, SharedState>) -> CommandResult<()> {
+    let state_clone = Arc::clone(&state.0);
+    let client = state_clone.lock().await;
+    client.adapter().close_terminal(id).await
+}
+
+#[tauri::command]
+pub async fn terminal_channels(state: State<'_, SharedState>) -> CommandResult<Vec<ChannelInfo>> {
+    let state_clone = Arc::clone(&state.0);
+    let client = state_clone.lock().await;
+    Ok(client.adapter().terminal_channels().await)
+}
+
+#[tauri::command]
+pub async fn listen_serial(
+    server: SerialServer,
+    state: State<'_, SharedState>,
+) -> CommandResult<()> {
+    let state_clone = Arc::clone(&state.0);
+    let client = state_clone.lock().await;
+    client.adapter().listen_serial(server).await
+}
+
+#[tauri::command]
+pub async fn stop_serial_server(uuid: String, state: State<'_, SharedState>) -> CommandResult<()> {
+    let state_clone = Arc::clone(&state.0);
+    let client = state_clone.lock().await;
+    client.adapter().stop_serial_server(&uuid).await
+}
+
+#[tauri::command]
+pub async fn serial_servers(state: State<'_, SharedState>) -> CommandResult<Vec<SerialServer>> {
+    let state_clone = Arc::clone(&state.0);
+    let client = state_clone.lock().await;
+    Ok(client.adapter().serial_servers().await)
+}
+
+#[tauri::command]
+pub async fn listen_network(
+    server: NetworkServer,
+    state: State<'_, SharedState>,
+) -> CommandResult<()> {
+    let state_clone = Arc::clone(&state.0);
+    let client = state_clone.lock().await;
+    client.adapter().listen_network(server).await
+}
+
+#[tauri::command]
+pub async fn stop_network_server(
+    role: PanRole,
+    state: State<'_, SharedState>,
+) -> CommandResult<()> {
+    let state_clone = Arc::clone(&state.0);
+    let client = state_clone.lock().await;
+    client.adapter().stop_network_server(role).await
+}
+
+#[tauri::command]
+pub async fn network_servers(state: State<'_, SharedState>) -> CommandResult<Vec<NetworkServer>> {
+    let state_clone = Arc::clone(&state.0);
+    let client = state_clone.lock().await;
+    Ok(client.adapter().network_servers().await)
+}
+
+#[tauri::command]
+pub async fn possible_network_clients(
+    state: State<'_, SharedState>,
+) -> CommandResult<Vec<DeviceInfo>> {
+    let state_clone = Arc::clone(&state.0);
+    let client = state_clone.lock().await;
+    Ok(client.adapter().possible_network_clients().await)
+}
+
+#[tauri::command]
+pub async fn open_l2cap(
+    address: [u8; 6],
+    psm: u16,
+    options: TerminalOptions,
+    state: State<'_, SharedState>,
+) -> CommandResult<ChannelInfo> {
+    let adapter = {
+        let state_clone = Arc::clone(&state.0);
+        let client = state_clone.lock().await;
+        client.adapter().clone()
+    };
+    adapter
+        .open_l2cap(Address::new(address), psm, options)
+        .await
+}
+
+#[tauri::command]
+pub async fn listen_l2cap(
+    server: L2capServer,
+    state: State<'_, SharedState>,
+) -> CommandResult<L2capServer> {
+    let state_clone = Arc::clone(&state.0);
+    let client = state_clone.lock().await;
+    client.adapter().listen_l2cap(server).await
+}
+
+#[tauri::command]
+pub async fn stop_l2cap_server(psm: u16, state: State<'_, SharedState>) -> CommandResult<()> {
+    let state_clone = Arc::clone(&state.0);
+    let client = state_clone.lock().await;
+    client.adapter().stop_l2cap_server(psm).await
+}
+
+#[tauri::command]
+pub async fn l2cap_servers(state: State<'_, SharedState>) -> CommandResult<Vec<L2capServer>> {
+    let state_clone = Arc::clone(&state.0);
+    let client = state_clone.lock().await;
+    Ok(client.adapter().l2cap_servers().await)
+}
+
+#[tauri::command]
+pub async fn device_channels(
+    address: [u8; 6],
+    state: State<'_, SharedState>,
+) -> CommandResult<Vec<ChannelInfo>> {
+    let state_clone = Arc::clone(&state.0);
+    let client = state_clone.lock().await;
+    client
+        .adapter()
+        .device_channels(Address::new(address))
+        .await
+}
+
+#[tauri::command]
+pub async fn notification_settings(
+    state: State<'_, NotificationState>,
+) -> CommandResult<NotificationSettings> {
+    let state_clone = Arc::clone(&state.0);
+    let settings = state_clone.lock().await;
+    Ok(settings.clone())
+}
+
+/// Also registers or unregisters the pairing agent when `pairing_requests` changes
+#[tauri::command]
+pub async fn set_notification_settings(
+    settings: NotificationSettings,
+    state: State<'_, NotificationState>,
+    client_state: State<'_, SharedState>,
+) -> CommandResult<()> {
+    let adapter = {
+        let state_clone = Arc::clone(&client_state.0);
+        let client = state_clone.lock().await;
+        client.adapter().clone()
+    };
+    if settings.pairing_requests != adapter.is_agent_registered().await {
+        if settings.pairing_requests {
+            adapter.register_agent().await?;
+        } else {
+            adapter.unregister_agent().await?;
+        }
     }
+    let state_clone = Arc::clone(&state.0);
+    *state_clone.lock().await = settings;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn answer_pairing_request(
+    id: u64,
+    accept: bool,
+    state: State<'_, SharedState>,
+) -> CommandResult<()> {
+    let adapter = {
+        let state_clone = Arc::clone(&state.0);
+        let client = state_clone.lock().await;
+        client.adapter().clone()
+    };
+    adapter.answer_pairing_request(id, accept).await
 }
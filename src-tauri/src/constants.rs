@@ -1,276 +0,0 @@
-use std::collections::HashMap;
-
-use lazy_static::lazy_static;
-
-lazy_static! {
-    pub static ref ERROR: u32 = 0xFF000000;
-    pub static ref CLASS_SERVICES: HashMap<u32, &'static str> = {
-        let services = [
-            (0x002000, "LIMITED_DISCOVERABILITY"),
-            (0x010000, "POSITIONING"),
-            (0x020000, "NETWORKING"),
-            (0x040000, "RENDER"),
-            (0x080000, "CAPTURE"),
-            (0x100000, "OBJECT_TRANSFER"),
-            (0x200000, "AUDIO"),
-            (0x400000, "TELEPHONY"),
-            (0x800000, "INFORMATION"),
-        ]
-        .iter()
-        .cloned()
-        .collect();
-        services
-    };
-    pub static ref CLASS_DEVICES_MAJOR: HashMap<u32, &'static str> = {
-        let device_major_names = [
-            (0x0000, "MISC"),
-            (0x0100, "COMPUTER"),
-            (0x0200, "PHONE"),
-            (0x0300, "NETWORKING"),
-            (0x0400, "AUDIO_VIDEO"),
-            (0x0500, "PERIPHERAL"),
-            (0x0600, "IMAGING"),
-            (0x0700, "WEARABLE"),
-            (0x0800, "TOY"),
-            (0x0900, "HEALTH"),
-            (0x1F00, "UNCATEGORIZED"),
-        ]
-        .iter()
-        .cloned()
-        .collect();
-        device_major_names
-    };
-    pub static ref CLASS_DEVICES_MINOR: HashMap<u32, &'static str> = {
-        let device_names_minor = [
-            (0x0100, "COMPUTER_UNCATEGORIZED"),
-            (0x0104, "COMPUTER_DESKTOP"),
-            (0x0108, "COMPUTER_SERVER"),
-            (0x010C, "COMPUTER_LAPTOP"),
-            (0x0110, "COMPUTER_HANDHELD_PC_PDA"),
-            (0x0114, "COMPUTER_PALM_SIZE_PC_PDA"),
-            (0x0118, "COMPUTER_WEARABLE"),
-            (0x0200, "PHONE_UNCATEGORIZED"),
-            (0x0204, "PHONE_CELLULAR"),
-            (0x0208, "PHONE_CORDLESS"),
-            (0x020C, "PHONE_SMART"),
-            (0x0210, "PHONE_MODEM_OR_GATEWAY"),
-            (0x0214, "PHONE_ISDN"),
-            (0x0400, "AUDIO_VIDEO_UNCATEGORIZED"),
-            (0x0404, "AUDIO_VIDEO_WEARABLE_HEADSET"),
-            (0x0408, "AUDIO_VIDEO_HANDSFREE"),
-            (0x0410, "AUDIO_VIDEO_MICROPHONE"),
-            (0x0414, "AUDIO_VIDEO_LOUDSPEAKER"),
-            (0x0418, "AUDIO_VIDEO_HEADPHONES"),
-            (0x041C, "AUDIO_VIDEO_PORTABLE_AUDIO"),
-            (0x0420, "AUDIO_VIDEO_CAR_AUDIO"),
-            (0x0424, "AUDIO_VIDEO_SET_TOP_BOX"),
-            (0x0428, "AUDIO_VIDEO_HIFI_AUDIO"),
-            (0x042C, "AUDIO_VIDEO_VCR"),
-            (0x0430, "AUDIO_VIDEO_VIDEO_CAMERA"),
-            (0x0434, "AUDIO_VIDEO_CAMCORDER"),
-            (0x0438, "AUDIO_VIDEO_VIDEO_MONITOR"),
-            (0x043C, "AUDIO_VIDEO_VIDEO_DISPLAY_AND_LOUDSPEAKER"),
-            (0x0440, "AUDIO_VIDEO_VIDEO_CONFERENCING"),
-            (0x0448, "AUDIO_VIDEO_VIDEO_GAMING_TOY"),
-            (0x0700, "WEARABLE_UNCATEGORIZED"),
-            (0x0704, "WEARABLE_WRIST_WATCH"),
-            (0x0708, "WEARABLE_PAGER"),
-            (0x070C, "WEARABLE_JACKET"),
-            (0x0710, "WEARABLE_HELMET"),
-            (0x0714, "WEARABLE_GLASSES"),
-            (0x0800, "TOY_UNCATEGORIZED"),
-            (0x0804, "TOY_ROBOT"),
-            (0x0808, "TOY_VEHICLE"),
-            (0x080C, "TOY_DOLL_ACTION_FIGURE"),
-            (0x0810, "TOY_CONTROLLER"),
-            (0x0814, "TOY_GAME"),
-            (0x0900, "HEALTH_UNCATEGORIZED"),
-            (0x0904, "HEALTH_BLOOD_PRESSURE"),
-            (0x0908, "HEALTH_THERMOMETER"),
-            (0x090C, "HEALTH_WEIGHING"),
-            (0x0910, "HEALTH_GLUCOSE"),
-            (0x0914, "HEALTH_PULSE_OXIMETER"),
-            (0x0918, "HEALTH_PULSE_RATE"),
-            (0x091C, "HEALTH_DATA_DISPLAY"),
-        ]
-        .iter()
-        .cloned()
-        .collect();
-        device_names_minor
-    };
-    pub static ref UUID_SERVICES: HashMap<u16, &'static str> = {
-        let uuid_services = [
-            (0x0001, "SDP"),
-            (0x0002, "UDP"),
-            (0x0003, "RFCOMM"),
-            (0x0004, "TCP"),
-            (0x0005, "TCS-BIN"),
-            (0x0006, "TCS-AT"),
-            (0x0007, "ATT"),
-            (0x0008, "OBEX"),
-            (0x0009, "IP"),
-            (0x000a, "FTP"),
-            (0x000c, "HTTP"),
-            (0x000e, "WSP"),
-            (0x000f, "BNEP"),
-            (0x0010, "UPnP/ESDP"),
-            (0x0011, "HIDP"),
-            (0x0012, "Hardcopy Control Channel"),
-            (0x0014, "Hardcopy Data Channel"),
-            (0x0016, "Hardcopy Notification"),
-            (0x0017, "AVCTP"),
-            (0x0019, "AVDTP"),
-            (0x001b, "CMTP"),
-            (0x001d, "UDI_C-Plane"),
-            (0x001e, "Multi-Channel Adaptation Protocol (MCAP)"),
-            (0x001f, "Multi-Channel Adaptation Protocol (MCAP)"),
-            (0x0100, "L2CAP"),
-            (0x1000, "ServiceDiscoveryServerServiceClassID"),
-            (0x1001, "BrowseGroupDescriptorServiceClassID"),
-            (0x1002, "Public Browse Group"),
-            (0x1101, "Serial Port"),
-            (0x1102, "LAN Access Using PPP"),
-            (0x1103, "Dialup Networking (DUN)"),
-            (0x1104, "IrMC Sync"),
-            (0x1105, "OBEX Object Push"),
-            (0x1106, "OBEX File Transfer"),
-            (0x1107, "IrMC Sync Command"),
-            (0x1108, "Headset"),
-            (0x1109, "Cordless Telephony"),
-            (0x110a, "Audio Source"),
-            (0x110b, "Audio Sink"),
-            (0x110c, "Remote Control Target"),
-            (0x110d, "Advanced Audio"),
-            (0x110e, "Remote Control"),
-            (0x110f, "Video Conferencing"),
-            (0x1110, "Intercom"),
-            (0x1111, "Fax"),
-            (0x1112, "Headset Audio Gateway"),
-            (0x1113, "WAP"),
-            (0x1114, "WAP Client"),
-            (0x1115, "PANU"),
-            (0x1116, "Network Access Point"),
-            (0x1117, "Group Network"),
-            (0x1118, "DirectPrinting (BPP)"),
-            (0x1119, "ReferencePrinting (BPP)"),
-            (0x111a, "Imaging (BIP)"),
-            (0x111b, "ImagingResponder (BIP)"),
-            (0x111c, "ImagingAutomaticArchive (BIP)"),
-            (0x111d, "ImagingReferencedObjects (BIP)"),
-            (0x111e, "Handsfree"),
-            (0x111f, "Handsfree Audio Gateway"),
-            (0x1120, "DirectPrintingReferenceObjectsService (BPP)"),
-            (0x1121, "ReflectedUI (BPP)"),
-            (0x1122, "Basic Printing (BPP)"),
-            (0x1123, "Printing Status (BPP)"),
-            (0x1124, "Human Interface Device Service (HID)"),
-            (0x1125, "HardcopyCableReplacement (HCR)"),
-            (0x1126, "HCR_Print (HCR)"),
-            (0x1127, "HCR_Scan (HCR)"),
-            (0x1128, "Common ISDN Access (CIP)"),
-            (0x1129, "VideoConferencingGW (VCP)"),
-            (0x112a, "UDI-MT"),
-            (0x112b, "UDI-TA"),
-            (0x112c, "Audio/Video"),
-            (0x112d, "SIM Access (SAP)"),
-            (0x112e, "Phonebook Access (PBAP)"),
-            (0x112f, "Phonebook Access (PBAP) - PCE"),
-            (0x1130, "Phonebook Access (PBAP)"),
-            (0x1131, "Headset"),
-            (0x1132, "Message Access Server"),
-            (0x1133, "Message Notification Server"),
-            (0x1134, "Message Access Profile (MAP)"),
-            (0x1135, "GNSS"),
-            (0x1136, "GNSS Server"),
-            (0x1137, "3D Display"),
-            (0x1138, "3D Glasses"),
-            (0x1139, "3D Synchronization (3DSP)"),
-            (0x113a, "Multi-Profile Specification (MPS) Profile"),
-            (0x113b, "Multi-Profile Specification (MPS) Service"),
-            (0x113c, "Calendar, Task, and Notes (CTN) Access Service"),
-            (
-                0x113d,
-                "Calendar, Task, and Notes (CTN) Notification Service",
-            ),
-            (0x113e, "Calendar, Task, and Notes (CTN) Profile"),
-            (0x1200, "PnP Information"),
-            (0x1201, "Generic Networking"),
-            (0x1202, "Generic FileTransfer"),
-            (0x1203, "Generic Audio"),
-            (0x1204, "Generic Telephony"),
-            (0x1303, "Video Source"),
-            (0x1304, "Video Sink"),
-            (0x1305, "Video Distribution"),
-            (0x1400, "HDP"),
-            (0x1401, "HDP Source"),
-            (0x1402, "HDP Sink"),
-            (0x1800, "Generic Access"),
-            (0x1801, "Generic Attribute"),
-            (0x1802, "Immediate Alert"),
-            (0x1803, "Link Loss"),
-            (0x1804, "Tx Power"),
-            (0x1805, "Current Time Service"),
-            (0x1806, "Reference Time Update Service"),
-            (0x1807, "Next DST Change Service"),
-            (0x1808, "Glucose"),
-            (0x1809, "Health Thermometer"),
-            (0x180a, "Device Information"),
-            (0x180d, "Heart Rate"),
-            (0x180e, "Phone Alert Status Service"),
-            (0x180f, "Battery Service"),
-            (0x1810, "Blood Pressure"),
-            (0x1811, "Alert Notification Service"),
-            (0x1812, "Human Interface Device"),
-            (0x1813, "Scan Parameters"),
-            (0x1814, "Running Speed and Cadence"),
-            (0x1815, "Automation IO"),
-            (0x1816, "Cycling Speed and Cadence"),
-            (0x1818, "Cycling Power"),
-            (0x1819, "Location and Navigation"),
-            (0x181a, "Environmental Sensing"),
-            (0x181b, "Body Composition"),
-            (0x181c, "User Data"),
-            (0x181d, "Weight Scale"),
-            (0x181e, "Bond Management"),
-            (0x181f, "Continuous Glucose Monitoring"),
-            (0x1820, "Internet Protocol Support"),
-            (0x1821, "Indoor Positioning"),
-            (0x1822, "Pulse Oximeter"),
-            (0x1823, "HTTP Proxy"),
-            (0x1824, "Transport Discovery"),
-            (0x1825, "Object Transfer"),
-            (0x2112, "AppleAgent"),
-            (0x2800, "Primary Service"),
-            (0x2801, "Secondary Service"),
-            (0x2802, "Include"),
-            (0x2803, "Characteristic Declaration"),
-            (0x2a00, "Device Name"),
-            (0x2a01, "Appearance"),
-            (0x2a02, "Peripheral Privacy Flag"),
-            (0x2a03, "Reconnection Address"),
-            (0x2a04, "Peripheral Preferred Connection Parameters"),
-            (0x2a05, "Service Changed"),
-            (0x2a23, "System ID"),
-            (0x2a24, "Model Number String"),
-            (0x2a25, "Serial Number String"),
-            (0x2a26, "Firmware Revision String"),
-            (0x2a27, "Hardware Revision String"),
-            (0x2a28, "Software Revision String"),
-            (0x2a29, "Manufacturer Name String"),
-            (0x2a50, "PnP ID"),
-            (0x2900, "Characteristic Extended Properties"),
-            (0x2901, "Characteristic User Description"),
-            (0x2902, "Client Characteristic Configuration"),
-            (0x2903, "Server Characteristic Configuration"),
-            (0x2904, "Characteristic Presentation Format"),
-            (0x2905, "Characteristic Aggregate Format"),
-            (0x2906, "Valid Range"),
-            (0x2907, "External Report Reference"),
-            (0x2908, "Report Reference"),
-        ]
-        .iter()
-        .cloned()
-        .collect();
-        uuid_services
-    };
-}
@@ -0,0 +1,445 @@
+// Session bus service so desktop widgets and scripts can use the app's device model.
+// AdapterInfo, DeviceInfo and event payloads are a{sv} dictionaries keyed like the JSON the
+// webview receives, with integers as x, lists as av and null fields left out.
+//
+// Only the adapter and the connection state of devices can be changed over the bus: the
+// SetAlias, SetPowered, Unblock, SetPairable, SetDiscoverable, SetDiscoverableTimeout,
+// DiscoverDevices, CancelDiscovering, Connect and Disconnect methods. Media, volume, profiles,
+// networks, serial terminals, device queries, inventories and captures stay in the app
+//
+//   busctl --user get-property io.saiyanblue.Manager1 /io/saiyanblue/Manager1 \
+//       io.saiyanblue.Manager1 AdapterInfo
+//   busctl --user call io.saiyanblue.Manager1 /io/saiyanblue/Manager1 \
+//       io.saiyanblue.Manager1 Connect s 38:18:4C:10:20:30
+
+use std::{str::FromStr, sync::Arc};
+
+use bluer::Address;
+use bluetooth::{
+    adapter::{Adapter, AdapterEvent},
+    error::BluetoothError,
+    Client,
+};
+use dbus::{
+    arg::{PropMap, RefArg, Variant},
+    channel::{MatchingReceiver, Sender},
+    message::{MatchRule, SignalArgs},
+    nonblock::{
+        stdintf::org_freedesktop_dbus::{PropertiesPropertiesChanged, RequestNameReply},
+        SyncConnection,
+    },
+    Message, Path,
+};
+use dbus_crossroads::{Crossroads, IfaceBuilder, MethodErr};
+use log::warn;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use tokio::sync::Mutex;
+use tokio_stream::StreamExt;
+
+const BUS_NAME: &str = "io.saiyanblue.Manager1";
+const INTERFACE: &str = "io.saiyanblue.Manager1";
+const OBJECT_PATH: &str = "/io/saiyanblue/Manager1";
+const ERROR_PREFIX: &str = "io.saiyanblue.Error";
+
+type ClientState = Arc<Mutex<Client>>;
+
+// ADAPTER_NOT_POWERED_ON becomes io.saiyanblue.Error.AdapterNotPoweredOn
+fn method_err(err: BluetoothError) -> MethodErr {
+    let name = err
+        .code()
+        .split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_string() + &chars.as_str().to_lowercase(),
+                None => String::new(),
+            }
+        })
+        .collect::<String>();
+    let message = match err.details() {
+        Some(details) => format!("{} ({})", err, details),
+        None => err.to_string(),
+    };
+    MethodErr::from((format!("{}.{}", ERROR_PREFIX, name), message))
+}
+
+fn to_variant(value: &Value) -> Option<Variant<Box<dyn RefArg>>> {
+    let arg: Box<dyn RefArg> = match value {
+        Value::Null => return None,
+        Value::Bool(value) => Box::new(*value),
+        // one integer type, so a field keeps its signature whatever its sign
+        Value::Number(number) => match (number.as_i64(), number.as_u64()) {
+            (Some(integer), _) => Box::new(integer),
+            (None, Some(integer)) => Box::new(integer),
+            (None, None) => Box::new(number.as_f64()?),
+        },
+        Value::String(value) => Box::new(value.clone()),
+        Value::Array(values) => Box::new(values.iter().filter_map(to_variant).collect::<Vec<_>>()),
+        Value::Object(fields) => Box::new(object_to_dict(fields)),
+    };
+    Some(Variant(arg))
+}
+
+fn object_to_dict(fields: &Map<String, Value>) -> PropMap {
+    fields
+        .iter()
+        .filter_map(|(name, value)| Some((name.clone(), to_variant(value)?)))
+        .collect()
+}
+
+// a{sv} of a model serialized as a JSON object
+fn to_dict<T: Serialize>(value: &T) -> Result<PropMap, MethodErr> {
+    match serde_json::to_value(value).map_err(|err| MethodErr::failed(&err))? {
+        Value::Object(fields) => Ok(object_to_dict(&fields)),
+        _ => Err(MethodErr::failed(&"not a dictionary")),
+    }
+}
+
+fn to_dicts<T: Serialize>(values: &[T]) -> Result<Vec<PropMap>, MethodErr> {
+    values.iter().map(to_dict).collect()
+}
+
+fn parse_address(address: &str) -> Result<Address, MethodErr> {
+    Address::from_str(address).map_err(|_| MethodErr::invalid_arg(&address))
+}
+
+// The adapter is cloned so the client is not locked while a call awaits bluetoothd
+async fn adapter(client: &ClientState) -> Adapter {
+    client.lock().await.adapter().clone()
+}
+
+fn register_interface(cr: &mut Crossroads, client: &ClientState) {
+    let token = cr.register(INTERFACE, |b: &mut IfaceBuilder<ClientState>| {
+        b.property::<PropMap, _>("AdapterInfo")
+            .get_async(|mut ctx, client| {
+                let client = Arc::clone(client);
+                async move {
+                    let adapter_info = adapter(&client).await.adapter_info().await;
+                    ctx.reply(to_dict(&adapter_info))
+                }
+            });
+        // the list is re-read on demand instead of being sent along with every device change
+        b.property::<Vec<PropMap>, _>("Devices")
+            .emits_changed_invalidates()
+            .get_async(|mut ctx, client| {
+                let client = Arc::clone(client);
+                async move {
+                    let devices = adapter(&client).await.known_devices().await;
+                    ctx.reply(to_dicts(&devices))
+                }
+            });
+
+        b.signal::<(PropMap,), _>("AdapterPropertyChanged", ("adapter_info",));
+        b.signal::<(PropMap,), _>("DeviceChanged", ("delta",));
+        b.signal::<(u64,), _>("DiscoveryStarted", ("timeout",));
+        b.signal::<(String,), _>("DiscoveryStopped", ("reason",));
+        b.signal::<(u64,), _>("Lagged", ("skipped",));
+
+        let state = Arc::clone(client);
+        b.method_with_cr_async(
+            "SetAlias",
+            ("alias",),
+            (),
+            move |mut ctx, _, (alias,): (String,)| {
+                let client = Arc::clone(&state);
+                async move {
+                    let adapter = adapter(&client).await;
+                    ctx.reply(adapter.set_alias(alias).await.map_err(method_err))
+                }
+            },
+        );
+        let state = Arc::clone(client);
+        b.method_with_cr_async(
+            "SetPowered",
+            ("powered",),
+            (),
+            move |mut ctx, _, (powered,): (bool,)| {
+                let client = Arc::clone(&state);
+                async move {
+                    let adapter = adapter(&client).await;
+                    ctx.reply(adapter.set_powered(powered).await.map_err(method_err))
+                }
+            },
+        );
+        let state = Arc::clone(client);
+        b.method_with_cr_async("Unblock", (), (), move |mut ctx, _, (): ()| {
+            let client = Arc::clone(&state);
+            async move {
+                let adapter = adapter(&client).await;
+                ctx.reply(adapter.unblock().await.map_err(method_err))
+            }
+        });
+        let state = Arc::clone(client);
+        b.method_with_cr_async(
+            "SetPairable",
+            ("pairable",),
+            (),
+            move |mut ctx, _, (pairable,): (bool,)| {
+                let client = Arc::clone(&state);
+                async move {
+                    let adapter = adapter(&client).await;
+                    ctx.reply(adapter.set_pairable(pairable).await.map_err(method_err))
+                }
+            },
+        );
+        let state = Arc::clone(client);
+        b.method_with_cr_async(
+            "SetDiscoverable",
+            ("discoverable",),
+            (),
+            move |mut ctx, _, (discoverable,): (bool,)| {
+                let client = Arc::clone(&state);
+                async move {
+                    let adapter = adapter(&client).await;
+                    let result = adapter.set_discoverable(discoverable).await;
+                    ctx.reply(result.map_err(method_err))
+                }
+            },
+        );
+        let state = Arc::clone(client);
+        b.method_with_cr_async(
+            "SetDiscoverableTimeout",
+            ("discoverable_timeout",),
+            (),
+            move |mut ctx, _, (discoverable_timeout,): (u32,)| {
+                let client = Arc::clone(&state);
+                async move {
+                    let adapter = adapter(&client).await;
+                    let result = adapter.set_discoverable_timeout(discoverable_timeout).await;
+                    ctx.reply(result.map_err(method_err))
+                }
+            },
+        );
+        let state = Arc::clone(client);
+        b.method_with_cr_async(
+            "KnownDevices",
+            (),
+            ("devices",),
+            move |mut ctx, _, (): ()| {
+                let client = Arc::clone(&state);
+                async move {
+                    let devices = adapter(&client).await.known_devices().await;
+                    ctx.reply(to_dicts(&devices).map(|devices| (devices,)))
+                }
+            },
+        );
+        let state = Arc::clone(client);
+        b.method_with_cr_async(
+            "DeviceSnapshot",
+            (),
+            ("snapshot",),
+            move |mut ctx, _, (): ()| {
+                let client = Arc::clone(&state);
+                async move {
+                    let snapshot = adapter(&client).await.device_snapshot().await;
+                    ctx.reply(to_dict(&snapshot).map(|snapshot| (snapshot,)))
+                }
+            },
+        );
+        let state = Arc::clone(client);
+        b.method_with_cr_async(
+            "DiscoverDevices",
+            ("timeout",),
+            (),
+            move |mut ctx, _, (timeout,): (u64,)| {
+                let client = Arc::clone(&state);
+                async move {
+                    let mut adapter = adapter(&client).await;
+                    let result = adapter.discover_devices(timeout).await;
+                    ctx.reply(result.map_err(method_err))
+                }
+            },
+        );
+        let state = Arc::clone(client);
+        b.method_with_cr_async("CancelDiscovering", (), (), move |mut ctx, _, (): ()| {
+            let client = Arc::clone(&state);
+            async move {
+                adapter(&client).await.cancel_discovering().await;
+                ctx.reply(Ok(()))
+            }
+        });
+        let state = Arc::clone(client);
+        b.method_with_cr_async(
+            "Connect",
+            ("address",),
+            (),
+            move |mut ctx, _, (address,): (String,)| {
+                let client = Arc::clone(&state);
+                async move {
+                    let result = match parse_address(&address) {
+                        Ok(address) => {
+                            let adapter = adapter(&client).await;
+                            adapter.connect_device(address).await.map_err(method_err)
+                        }
+                        Err(err) => Err(err),
+                    };
+                    ctx.reply(result)
+                }
+            },
+        );
+        let state = Arc::clone(client);
+        b.method_with_cr_async(
+            "Disconnect",
+            ("address",),
+            (),
+            move |mut ctx, _, (address,): (String,)| {
+                let client = Arc::clone(&state);
+                async move {
+                    let result = match parse_address(&address) {
+                        Ok(address) => {
+                            let adapter = adapter(&client).await;
+                            adapter.disconnect_device(address).await.map_err(method_err)
+                        }
+                        Err(err) => Err(err),
+                    };
+                    ctx.reply(result)
+                }
+            },
+        );
+    });
+    cr.insert(OBJECT_PATH, &[token], Arc::clone(client));
+}
+
+fn event_signal(event: &AdapterEvent) -> Option<Message> {
+    let signal = |member: &str| Message::new_signal(OBJECT_PATH, INTERFACE, member).ok();
+    match event {
+        AdapterEvent::AdapterPropertyChanged(adapter_info) => {
+            let adapter_info = to_dict(adapter_info).ok()?;
+            Some(signal("AdapterPropertyChanged")?.append1(adapter_info))
+        }
+        AdapterEvent::DeviceChanged(delta) => {
+            let delta = to_dict(delta).ok()?;
+            Some(signal("DeviceChanged")?.append1(delta))
+        }
+        AdapterEvent::DiscoveryStarted(timeout) => {
+            Some(signal("DiscoveryStarted")?.append1(*timeout))
+        }
+        AdapterEvent::DiscoveryStopped(reason) => {
+            // serialized as a plain string like "timeout"
+            let reason = serde_json::to_value(reason).ok()?.as_str()?.to_string();
+            Some(signal("DiscoveryStopped")?.append1(reason))
+        }
+        AdapterEvent::Lagged(skipped) => Some(signal("Lagged")?.append1(*skipped)),
+        // answered in the app, other processes have no say in them
+        AdapterEvent::PairingRequested(_) => None,
+    }
+}
+
+// PropertiesChanged of the AdapterInfo and Devices properties, for clients caching them
+fn properties_changed(event: &AdapterEvent) -> Option<Message> {
+    let mut changed = PropMap::new();
+    let mut invalidated = vec![];
+    match event {
+        AdapterEvent::AdapterPropertyChanged(adapter_info) => {
+            let adapter_info = to_dict(adapter_info).ok()?;
+            changed.insert(String::from("AdapterInfo"), Variant(Box::new(adapter_info)));
+        }
+        AdapterEvent::DeviceChanged(_) => invalidated.push(String::from("Devices")),
+        AdapterEvent::Lagged(_) => {
+            invalidated.push(String::from("AdapterInfo"));
+            invalidated.push(String::from("Devices"));
+        }
+        AdapterEvent::DiscoveryStarted(_)
+        | AdapterEvent::DiscoveryStopped(_)
+        | AdapterEvent::PairingRequested(_) => return None,
+    }
+    let signal = PropertiesPropertiesChanged {
+        interface_name: String::from(INTERFACE),
+        changed_properties: changed,
+        invalidated_properties: invalidated,
+    };
+    Some(signal.to_emit_message(&Path::from(OBJECT_PATH)))
+}
+
+async fn forward_events(connection: Arc<SyncConnection>, client: ClientState) {
+    let mut event_stream = Box::pin(adapter(&client).await.subscribe());
+    while let Some(event) = event_stream.next().await {
+        if let Some(signal) = event_signal(&event) {
+            let _ = connection.send(signal);
+        }
+        if let Some(signal) = properties_changed(&event) {
+            let _ = connection.send(signal);
+        }
+    }
+}
+
+/// Publishes `io.saiyanblue.Manager1` on the session bus for as long as the adapter lives.
+/// Failing to do so only disables the service, the app keeps running
+pub async fn serve(client: ClientState) {
+    let (resource, connection) = match dbus_tokio::connection::new_session_sync() {
+        Ok(connection) => connection,
+        Err(err) => {
+            warn!("dbus service disabled, no session bus: {}", err);
+            return;
+        }
+    };
+    tokio::spawn(async move {
+        let err = resource.await;
+        warn!("lost the session bus connection: {}", err);
+    });
+
+    match connection.request_name(BUS_NAME, false, true, true).await {
+        Ok(RequestNameReply::PrimaryOwner) | Ok(RequestNameReply::AlreadyOwner) => (),
+        Ok(_) => {
+            warn!(
+                "dbus service disabled, {} is owned by another instance",
+                BUS_NAME
+            );
+            return;
+        }
+        Err(err) => {
+            warn!(
+                "dbus service disabled, could not request {}: {}",
+                BUS_NAME, err
+            );
+            return;
+        }
+    }
+
+    let mut cr = Crossroads::new();
+    cr.set_async_support(Some((
+        connection.clone(),
+        Box::new(|future| {
+            tokio::spawn(future);
+        }),
+    )));
+    register_interface(&mut cr, &client);
+    connection.start_receive(
+        MatchRule::new_method_call(),
+        Box::new(move |message, connection| {
+            let _ = cr.handle_message(message, connection);
+            true
+        }),
+    );
+
+    forward_events(connection, client).await;
+}
+
+#[cfg(test)]
+mod test {
+    use bluetooth::device::DeviceInfo;
+    use dbus::arg::RefArg;
+
+    use super::{to_dict, to_dicts};
+
+    #[test]
+    fn models_are_published_as_typed_dictionaries() {
+        let device = DeviceInfo {
+            alias: String::from("Headphones"),
+            battery_percentage: 80,
+            rssi: Some(-58),
+            tags: vec![String::from("audio")],
+            ..DeviceInfo::default()
+        };
+        let dict = to_dict(&device).unwrap();
+        assert_eq!(dict["alias"].0.as_str(), Some("Headphones"));
+        assert_eq!(dict["rssi"].0.as_i64(), Some(-58));
+        assert_eq!(&*dict["battery_percentage"].0.signature(), "x");
+        assert_eq!(&*dict["tags"].0.signature(), "av");
+        assert_eq!(&*dict["is_paired"].0.signature(), "b");
+        // D-Bus has no null, unset fields are left out
+        assert!(!dict.contains_key("last_seen"));
+        assert_eq!(to_dicts(&[device]).unwrap().len(), 1);
+    }
+}
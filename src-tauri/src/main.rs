@@ -1,71 +1,228 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use std::sync::Arc;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
-use anyhow::Error;
-use bluetooth::adapter::AdapterEvent;
-use commands::SharedState;
-use tauri::Manager;
-use tokio::sync::Mutex;
+use bluetooth::{adapter::AdapterEvent, backend::simulator::Simulator};
+use coalescer::DeltaCoalescer;
+use commands::{NotificationState, SharedState};
+use notifications::{NotificationSettings, Notifier};
+use serde_json::json;
+use tauri::{Manager, WindowEvent};
+use tokio::{sync::Mutex, time::MissedTickBehavior};
 use tokio_stream::StreamExt;
 
-mod bluetooth;
+mod coalescer;
 mod commands;
-mod constants;
+mod dbus_service;
+mod notifications;
+mod tray;
+
+const DEFAULT_MAX_DEVICE_UPDATES_PER_SECOND: u64 = 10;
+// a flush every millisecond, higher rates would need a zero period
+const MAX_DEVICE_UPDATES_PER_SECOND: u64 = 1000;
+
+// Device updates are batched so the webview receives at most this many `devices_update`
+// events per second, 0 disables batching. Override with SAIYAN_BLUE_MAX_UPDATES_PER_SECOND,
+// rates above 1000 are capped
+fn max_device_updates_per_second() -> u64 {
+    std::env::var("SAIYAN_BLUE_MAX_UPDATES_PER_SECOND")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(DEFAULT_MAX_DEVICE_UPDATES_PER_SECOND)
+        .min(MAX_DEVICE_UPDATES_PER_SECOND)
+}
 
 #[tokio::main]
 async fn main() {
-    let client_response = bluetooth::Client::new().await;
+    // --simulate runs the app against an in-memory demo adapter instead of bluetoothd
+    let client_response = if std::env::args().any(|arg| arg == "--simulate") {
+        let simulator = Simulator::demo();
+        simulator.spawn_demo_script();
+        bluetooth::Client::simulated(&simulator).await
+    } else {
+        bluetooth::Client::new().await
+    };
     let tauri_default = tauri::Builder::default();
     if let Ok(client) = client_response {
-        println!("client is ok");
         let state_arc = SharedState(Arc::new(Mutex::new(client)));
         tauri_default
             .manage(state_arc)
+            .manage(NotificationState(Arc::new(Mutex::new(
+                NotificationSettings::default(),
+            ))))
             .setup(|app| {
                 let handle = app.handle();
                 let state_clone = Arc::clone(&app.state::<SharedState>().0);
+                let notification_settings = Arc::clone(&app.state::<NotificationState>().0);
+
+                tauri::async_runtime::spawn(dbus_service::serve(Arc::clone(&state_clone)));
 
+                // terminal data goes out as is, it is not worth batching like device updates
+                let terminal_handle = handle.clone();
+                let terminal_state = Arc::clone(&state_clone);
                 tauri::async_runtime::spawn(async move {
-                    let mut event_stream;
-                    {
-                        let mut client = state_clone.lock().await;
-                        event_stream =
-                            client.adapter_mut().adaptor_event_stream().await.unwrap();
+                    let mut terminal_events =
+                        Box::pin(terminal_state.lock().await.adapter().subscribe_terminals());
+                    while let Some(event) = terminal_events.next().await {
+                        let _ = terminal_handle.emit_to("main", "terminal_event", event);
                     }
+                });
+
+                tauri::async_runtime::spawn(async move {
+                    // commands hold the client lock for as long as BlueZ takes to answer, the
+                    // pump only uses its own adapter handle so it never waits for them
+                    let adapter = state_clone.lock().await.adapter().clone();
+                    let mut event_stream = Box::pin(adapter.subscribe());
 
                     let label = "main";
                     let property_event = "adapter_info_update";
                     let device_event = "devices_update";
-                    while let Some(event) = event_stream.next().await {
-                        match event {
-                            AdapterEvent::AdapterPropertyChanged(adapter_info) => {
-                                println!("adapter updated");
-                                let _ = handle.emit_to(label, property_event, adapter_info);
+                    let snapshot_event = "devices_snapshot";
+                    let discovery_event = "discovery_update";
+                    let pairing_event = "pairing_request";
+
+                    let max_updates_per_second = max_device_updates_per_second();
+                    let mut coalescer = DeltaCoalescer::default();
+                    let mut flush_interval = tokio::time::interval(Duration::from_millis(
+                        1000 / max_updates_per_second.clamp(1, MAX_DEVICE_UPDATES_PER_SECOND),
+                    ));
+                    flush_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+                    // the tray menu is rebuilt at most once per flush, not for every event
+                    let mut tray_outdated = true;
+                    let mut notifier = Notifier::default();
+
+                    loop {
+                        tokio::select! {
+                            event = event_stream.next() => {
+                                tray_outdated = true;
+                                if let Some(event) = &event {
+                                    let settings = notification_settings.lock().await.clone();
+                                    for notification in
+                                        notifier.notifications_for(event, &settings, Instant::now())
+                                    {
+                                        notification.show(&handle);
+                                    }
+                                }
+                                match event {
+                                    Some(AdapterEvent::AdapterPropertyChanged(adapter_info)) => {
+                                        let _ = handle.emit_to(label, property_event, adapter_info);
+                                    }
+                                    Some(AdapterEvent::DeviceChanged(delta)) => {
+                                        coalescer.push(delta);
+                                        if max_updates_per_second == 0 {
+                                            if let Some(batch) = coalescer.take() {
+                                                let _ = handle.emit_to(label, device_event, batch);
+                                            }
+                                        }
+                                    }
+                                    Some(AdapterEvent::DiscoveryStarted(timeout)) => {
+                                        let _ = handle.emit_to(
+                                            label,
+                                            discovery_event,
+                                            json!({ "discovering": true, "timeout": timeout }),
+                                        );
+                                    }
+                                    Some(AdapterEvent::DiscoveryStopped(reason)) => {
+                                        let _ = handle.emit_to(
+                                            label,
+                                            discovery_event,
+                                            json!({ "discovering": false, "reason": reason }),
+                                        );
+                                    }
+                                    Some(AdapterEvent::PairingRequested(request)) => {
+                                        let _ = handle.emit_to(label, pairing_event, request);
+                                    }
+                                    Some(AdapterEvent::Lagged(_)) => {
+                                        // missed some updates, push the current state instead
+                                        coalescer.clear();
+                                        let _ = handle.emit_to(
+                                            label,
+                                            property_event,
+                                            adapter.adapter_info().await,
+                                        );
+                                        let _ = handle.emit_to(
+                                            label,
+                                            snapshot_event,
+                                            adapter.device_snapshot().await,
+                                        );
+                                    }
+                                    None => break,
+                                }
                             }
-                            AdapterEvent::DevicesUpdated(devices, _) => {
-                                let _ = handle.emit_to(label, device_event, devices);
+                            _ = flush_interval.tick() => {
+                                if let Some(batch) = coalescer.take() {
+                                    let _ = handle.emit_to(label, device_event, batch);
+                                }
+                                if tray_outdated {
+                                    tray::refresh(&handle, &adapter).await;
+                                    tray_outdated = false;
+                                }
                             }
                         }
                     }
-                    Ok::<(), Error>(())
                 });
-                println!("setup done");
                 Ok(())
             })
+            .system_tray(tray::system_tray())
+            .on_system_tray_event(|handle, event| tray::handle_event(handle, event))
+            // closing the window keeps the app running in the tray
+            .on_window_event(|event| {
+                if let WindowEvent::CloseRequested { api, .. } = event.event() {
+                    let _ = event.window().hide();
+                    api.prevent_close();
+                }
+            })
             .invoke_handler(tauri::generate_handler![
                 commands::adapter_info,
                 commands::set_alias,
                 commands::set_pairable,
                 commands::set_powered,
+                commands::unblock,
                 commands::set_discoverable,
                 commands::set_discoverable_timeout,
                 commands::discover_devices,
                 commands::cancel_discovering,
+                commands::start_capture,
+                commands::stop_capture,
+                commands::capture_info,
                 commands::known_devices,
+                commands::device_snapshot,
+                commands::query_devices,
+                commands::set_device_tags,
+                commands::export_devices,
+                commands::import_devices,
                 commands::connect,
                 commands::disconnect,
+                commands::connect_profile,
+                commands::disconnect_profile,
+                commands::connect_network,
+                commands::disconnect_network,
+                commands::media_command,
+                commands::media_transport,
+                commands::set_volume,
+                commands::open_serial,
+                commands::terminal_send,
+                commands::close_terminal,
+                commands::terminal_channels,
+                commands::listen_serial,
+                commands::stop_serial_server,
+                commands::serial_servers,
+                commands::listen_network,
+                commands::stop_network_server,
+                commands::network_servers,
+                commands::possible_network_clients,
+                commands::open_l2cap,
+                commands::listen_l2cap,
+                commands::stop_l2cap_server,
+                commands::l2cap_servers,
+                commands::device_channels,
+                commands::notification_settings,
+                commands::set_notification_settings,
+                commands::answer_pairing_request,
             ])
             .run(tauri::generate_context!())
             .expect("error while running tauri application");
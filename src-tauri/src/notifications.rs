@@ -0,0 +1,364 @@
+use std::{
+    collections::{HashMap, HashSet},
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use tauri::{api::notification::Notification, AppHandle};
+
+use bluetooth::{
+    adapter::{AdapterEvent, DeviceEvent, DiscoveryStopReason},
+    device::DeviceInfo,
+    pairing::PairingRequestKind,
+};
+
+// A device that keeps dropping and reconnecting produces one connected and one disconnected
+// notification per window
+const CONNECTION_DEDUP_WINDOW: Duration = Duration::from_secs(30);
+
+// Which notifications are shown, changed through the notification_settings commands
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationSettings {
+    pub device_connected: bool,
+    pub device_disconnected: bool,
+    pub device_paired: bool,
+    // registers the app as the pairing agent to notify about pairing and authorization
+    // requests, off by default since it takes pairing over from the desktop for the session
+    pub pairing_requests: bool,
+    pub discovery_finished: bool,
+    pub battery_low: bool,
+    pub battery_low_threshold: u8,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            device_connected: true,
+            device_disconnected: true,
+            device_paired: true,
+            pairing_requests: false,
+            discovery_finished: true,
+            battery_low: true,
+            battery_low_threshold: 20,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DesktopNotification {
+    pub title: String,
+    pub body: String,
+}
+
+impl DesktopNotification {
+    fn new(title: impl Into<String>, body: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            body: body.into(),
+        }
+    }
+
+    pub fn show(&self, handle: &AppHandle) {
+        let _ = Notification::new(&handle.config().tauri.bundle.identifier)
+            .title(&self.title)
+            .body(&self.body)
+            .show();
+    }
+}
+
+fn display_name(device: &DeviceInfo) -> &str {
+    if !device.alias.is_empty() {
+        &device.alias
+    } else if !device.name.is_empty() {
+        &device.name
+    } else {
+        &device.address_string
+    }
+}
+
+// Turns device transitions into notifications, remembering just enough to deduplicate
+#[derive(Default)]
+pub struct Notifier {
+    // keyed by address and the connection state notified about
+    last_connection_notification: HashMap<(String, bool), Instant>,
+    low_battery: HashSet<String>,
+    discovered: usize,
+}
+
+impl Notifier {
+    pub fn notifications_for(
+        &mut self,
+        event: &AdapterEvent,
+        settings: &NotificationSettings,
+        now: Instant,
+    ) -> Vec<DesktopNotification> {
+        let mut notifications = vec![];
+        match event {
+            AdapterEvent::DeviceChanged(delta) => match &delta.event {
+                DeviceEvent::DeviceAdded { .. } => self.discovered += 1,
+                DeviceEvent::DeviceRemoved { device } => {
+                    self.last_connection_notification
+                        .retain(|(address, _), _| *address != device.address_string);
+                    self.low_battery.remove(&device.address_string);
+                }
+                DeviceEvent::DeviceUpdated { device, patch } => {
+                    let name = display_name(device);
+                    if patch.changes.contains_key("is_connected") {
+                        let enabled = if device.is_connected {
+                            settings.device_connected
+                        } else {
+                            settings.device_disconnected
+                        };
+                        if enabled
+                            && self.connection_notification_due(
+                                &device.address_string,
+                                device.is_connected,
+                                now,
+                            )
+                        {
+                            notifications.push(if device.is_connected {
+                                DesktopNotification::new("Device connected", name)
+                            } else {
+                                DesktopNotification::new("Device disconnected", name)
+                            });
+                        }
+                    }
+                    if patch.changes.contains_key("is_paired")
+                        && device.is_paired
+                        && settings.device_paired
+                    {
+                        notifications.push(DesktopNotification::new("Device paired", name));
+                    }
+                    if patch.changes.contains_key("battery_percentage") {
+                        let percentage = device.battery_percentage;
+                        if percentage > settings.battery_low_threshold {
+                            self.low_battery.remove(&device.address_string);
+                        } else if percentage > 0
+                            && self.low_battery.insert(device.address_string.clone())
+                            && settings.battery_low
+                        {
+                            notifications.push(DesktopNotification::new(
+                                "Battery low",
+                                format!("{} is at {}%", name, percentage),
+                            ));
+                        }
+                    }
+                }
+            },
+            AdapterEvent::DiscoveryStarted(_) => self.discovered = 0,
+            AdapterEvent::DiscoveryStopped(reason) => {
+                // a cancelled discovery was stopped by the user, no need to tell them
+                if settings.discovery_finished && !matches!(reason, DiscoveryStopReason::Cancelled)
+                {
+                    let body = match self.discovered {
+                        0 => String::from("No devices found"),
+                        1 => String::from("Found 1 device"),
+                        count => format!("Found {} devices", count),
+                    };
+                    notifications.push(DesktopNotification::new("Discovery finished", body));
+                }
+            }
+            AdapterEvent::PairingRequested(request) if settings.pairing_requests => {
+                let name = if request.name.is_empty() {
+                    &request.address_string
+                } else {
+                    &request.name
+                };
+                let body = match &request.kind {
+                    PairingRequestKind::Confirmation { passkey } => {
+                        format!("Confirm that {} shows {:06}", name, passkey)
+                    }
+                    PairingRequestKind::Authorization => format!("{} wants to pair", name),
+                    PairingRequestKind::Service { service, .. } => {
+                        format!("{} wants to use {}", name, service)
+                    }
+                    PairingRequestKind::DisplayPasskey { passkey } => {
+                        format!("Type {:06} on {}", passkey, name)
+                    }
+                    PairingRequestKind::DisplayPinCode { pin_code } => {
+                        format!("Type {} on {}", pin_code, name)
+                    }
+                };
+                notifications.push(DesktopNotification::new("Pairing request", body));
+            }
+            AdapterEvent::AdapterPropertyChanged(_)
+            | AdapterEvent::PairingRequested(_)
+            | AdapterEvent::Lagged(_) => (),
+        }
+        notifications
+    }
+
+    fn connection_notification_due(
+        &mut self,
+        address: &str,
+        connected: bool,
+        now: Instant,
+    ) -> bool {
+        let key = (address.to_string(), connected);
+        match self.last_connection_notification.get(&key) {
+            Some(last) if now.duration_since(*last) < CONNECTION_DEDUP_WINDOW => false,
+            _ => {
+                self.last_connection_notification.insert(key, now);
+                true
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, Instant};
+
+    use serde_json::{Map, Value};
+
+    use super::{NotificationSettings, Notifier};
+    use bluetooth::{
+        adapter::{AdapterEvent, DeviceEvent, DiscoveryStopReason},
+        device::{DeviceInfo, DevicePatch},
+        device_list::DeviceDelta,
+        pairing::{PairingRequest, PairingRequestKind},
+    };
+
+    fn update(device: DeviceInfo, field: &str, value: Value) -> AdapterEvent {
+        let mut changes = Map::new();
+        changes.insert(field.to_string(), value);
+        AdapterEvent::DeviceChanged(DeviceDelta {
+            seq: 1,
+            event: DeviceEvent::DeviceUpdated {
+                patch: DevicePatch {
+                    address: device.address_string.clone(),
+                    changes,
+                },
+                device,
+            },
+        })
+    }
+
+    fn headset(is_connected: bool, battery_percentage: u8) -> DeviceInfo {
+        DeviceInfo {
+            alias: String::from("Headphones"),
+            address_string: String::from("38:18:4C:10:20:30"),
+            is_connected,
+            battery_percentage,
+            ..DeviceInfo::default()
+        }
+    }
+
+    #[test]
+    fn flapping_connection_notifies_once_per_state_and_window() {
+        let settings = NotificationSettings::default();
+        let mut notifier = Notifier::default();
+        let start = Instant::now();
+
+        let connected = update(headset(true, 0), "is_connected", Value::from(true));
+        let disconnected = update(headset(false, 0), "is_connected", Value::from(false));
+        assert_eq!(
+            notifier
+                .notifications_for(&connected, &settings, start)
+                .len(),
+            1
+        );
+        // a disconnect right after the connect is still reported
+        assert_eq!(
+            notifier.notifications_for(&disconnected, &settings, start + Duration::from_secs(1))[0]
+                .title,
+            "Device disconnected"
+        );
+        assert!(notifier
+            .notifications_for(&connected, &settings, start + Duration::from_secs(2))
+            .is_empty());
+        assert!(notifier
+            .notifications_for(&disconnected, &settings, start + Duration::from_secs(3))
+            .is_empty());
+        let later = start + Duration::from_secs(60);
+        assert_eq!(
+            notifier.notifications_for(&disconnected, &settings, later)[0].title,
+            "Device disconnected"
+        );
+    }
+
+    #[test]
+    fn battery_low_notifies_once_until_recharged() {
+        let settings = NotificationSettings::default();
+        let mut notifier = Notifier::default();
+        let now = Instant::now();
+        let battery = |percentage: u8| {
+            update(
+                headset(true, percentage),
+                "battery_percentage",
+                Value::from(percentage),
+            )
+        };
+
+        assert!(notifier
+            .notifications_for(&battery(50), &settings, now)
+            .is_empty());
+        let low = notifier.notifications_for(&battery(20), &settings, now);
+        assert_eq!(low[0].body, "Headphones is at 20%");
+        assert!(notifier
+            .notifications_for(&battery(19), &settings, now)
+            .is_empty());
+        assert!(notifier
+            .notifications_for(&battery(90), &settings, now)
+            .is_empty());
+        assert_eq!(
+            notifier
+                .notifications_for(&battery(15), &settings, now)
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn disabled_and_cancelled_notifications_are_skipped() {
+        let settings = NotificationSettings {
+            device_connected: false,
+            ..NotificationSettings::default()
+        };
+        let mut notifier = Notifier::default();
+        let now = Instant::now();
+
+        let connected = update(headset(true, 0), "is_connected", Value::from(true));
+        assert!(notifier
+            .notifications_for(&connected, &settings, now)
+            .is_empty());
+
+        notifier.notifications_for(&AdapterEvent::DiscoveryStarted(10), &settings, now);
+        let cancelled = AdapterEvent::DiscoveryStopped(DiscoveryStopReason::Cancelled);
+        assert!(notifier
+            .notifications_for(&cancelled, &settings, now)
+            .is_empty());
+        let timeout = AdapterEvent::DiscoveryStopped(DiscoveryStopReason::Timeout);
+        assert_eq!(
+            notifier.notifications_for(&timeout, &settings, now)[0].body,
+            "No devices found"
+        );
+    }
+
+    #[test]
+    fn pairing_requests_notify_only_while_the_agent_is_on() {
+        let mut notifier = Notifier::default();
+        let now = Instant::now();
+        let request = AdapterEvent::PairingRequested(PairingRequest {
+            id: 1,
+            address: [0x38, 0x18, 0x4C, 0x10, 0x20, 0x30],
+            address_string: String::from("38:18:4C:10:20:30"),
+            name: String::new(),
+            kind: PairingRequestKind::Confirmation { passkey: 4321 },
+        });
+
+        assert!(notifier
+            .notifications_for(&request, &NotificationSettings::default(), now)
+            .is_empty());
+        let settings = NotificationSettings {
+            pairing_requests: true,
+            ..NotificationSettings::default()
+        };
+        let notifications = notifier.notifications_for(&request, &settings, now);
+        assert_eq!(notifications[0].title, "Pairing request");
+        assert_eq!(
+            notifications[0].body,
+            "Confirm that 38:18:4C:10:20:30 shows 004321"
+        );
+    }
+}
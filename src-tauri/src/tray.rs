@@ -0,0 +1,259 @@
+use std::{str::FromStr, sync::Arc};
+
+use bluer::Address;
+use bluetooth::{
+    adapter::{Adapter, AdapterInfo},
+    device::DeviceInfo,
+    Client,
+};
+use log::warn;
+use tauri::{
+    AppHandle, CustomMenuItem, Manager, SystemTray, SystemTrayEvent, SystemTrayMenu,
+    SystemTrayMenuItem, SystemTraySubmenu,
+};
+use tokio::sync::Mutex;
+
+use crate::commands::SharedState;
+
+// Same timeout the scan button of the device list uses
+const SCAN_TIMEOUT: u64 = 10;
+
+#[derive(Debug, PartialEq)]
+enum TrayAction {
+    TogglePower,
+    ToggleDiscoverable,
+    Scan,
+    Connect(Address),
+    Disconnect(Address),
+    Show,
+    Quit,
+}
+
+impl TrayAction {
+    fn id(&self) -> String {
+        match self {
+            TrayAction::TogglePower => String::from("power"),
+            TrayAction::ToggleDiscoverable => String::from("discoverable"),
+            TrayAction::Scan => String::from("scan"),
+            TrayAction::Connect(address) => format!("connect:{}", address),
+            TrayAction::Disconnect(address) => format!("disconnect:{}", address),
+            TrayAction::Show => String::from("show"),
+            TrayAction::Quit => String::from("quit"),
+        }
+    }
+
+    fn from_id(id: &str) -> Option<Self> {
+        match id.split_once(':') {
+            Some(("connect", address)) => Address::from_str(address).ok().map(TrayAction::Connect),
+            Some(("disconnect", address)) => {
+                Address::from_str(address).ok().map(TrayAction::Disconnect)
+            }
+            Some(_) => None,
+            None => match id {
+                "power" => Some(TrayAction::TogglePower),
+                "discoverable" => Some(TrayAction::ToggleDiscoverable),
+                "scan" => Some(TrayAction::Scan),
+                "show" => Some(TrayAction::Show),
+                "quit" => Some(TrayAction::Quit),
+                _ => None,
+            },
+        }
+    }
+
+    fn item(&self, title: impl Into<String>) -> CustomMenuItem {
+        CustomMenuItem::new(self.id(), title)
+    }
+}
+
+fn device_title(device: &DeviceInfo) -> String {
+    let name = if device.alias.is_empty() {
+        &device.name
+    } else {
+        &device.alias
+    };
+    // 0 is also what devices without a battery service report
+    if device.battery_percentage > 0 {
+        format!("{} ({}%)", name, device.battery_percentage)
+    } else {
+        name.to_string()
+    }
+}
+
+fn device_submenu(device: &DeviceInfo) -> SystemTraySubmenu {
+    let address = Address::new(device.address);
+    let item = if device.is_connected {
+        TrayAction::Disconnect(address).item("Disconnect")
+    } else {
+        TrayAction::Connect(address).item("Connect")
+    };
+    let mut title = device_title(device);
+    if device.is_connected {
+        title = format!("{} - connected", title);
+    }
+    SystemTraySubmenu::new(title, SystemTrayMenu::new().add_item(item))
+}
+
+fn build_menu(adapter_info: Option<&AdapterInfo>, devices: &[DeviceInfo]) -> SystemTrayMenu {
+    let powered = adapter_info.map_or(false, |info| info.is_powered);
+    let discoverable = adapter_info.map_or(false, |info| info.is_discoverable);
+    let discovering = adapter_info.map_or(false, |info| info.discovering);
+
+    let hard_blocked = adapter_info.map_or(false, |info| info.hard_blocked);
+
+    let mut power = TrayAction::TogglePower.item(if hard_blocked {
+        "Bluetooth (blocked by switch)"
+    } else {
+        "Bluetooth"
+    });
+    if powered {
+        power = power.selected();
+    }
+    if hard_blocked {
+        power = power.disabled();
+    }
+    let mut visible = TrayAction::ToggleDiscoverable.item("Discoverable");
+    if discoverable {
+        visible = visible.selected();
+    }
+    if !powered {
+        visible = visible.disabled();
+    }
+    let mut scan = TrayAction::Scan.item(if discovering { "Scanning..." } else { "Scan" });
+    if !powered || discovering {
+        scan = scan.disabled();
+    }
+
+    let mut paired = SystemTrayMenu::new();
+    let mut has_paired = false;
+    for device in devices.iter().filter(|device| device.is_paired) {
+        paired = paired.add_submenu(device_submenu(device));
+        has_paired = true;
+    }
+    if !has_paired {
+        paired = paired.add_item(CustomMenuItem::new("no_devices", "No paired devices").disabled());
+    }
+
+    SystemTrayMenu::new()
+        .add_item(power)
+        .add_item(visible)
+        .add_item(scan)
+        .add_native_item(SystemTrayMenuItem::Separator)
+        .add_submenu(SystemTraySubmenu::new("Devices", paired))
+        .add_native_item(SystemTrayMenuItem::Separator)
+        .add_item(TrayAction::Show.item("Show saiyan blue"))
+        .add_item(TrayAction::Quit.item("Quit"))
+}
+
+/// Tray shown until the adapter state is known
+pub fn system_tray() -> SystemTray {
+    SystemTray::new().with_menu(build_menu(None, &[]))
+}
+
+/// Rebuilds the tray menu from the current adapter state and device list. Takes the adapter
+/// rather than the client, so a command holding the client lock does not hold up the tray
+pub async fn refresh(handle: &AppHandle, adapter: &Adapter) {
+    let adapter_info = adapter.adapter_info().await;
+    let devices = adapter.known_devices().await;
+    let _ = handle
+        .tray_handle()
+        .set_menu(build_menu(Some(&adapter_info), &devices));
+}
+
+fn show_main_window(handle: &AppHandle) {
+    if let Some(window) = handle.get_window("main") {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
+
+async fn run_action(action: TrayAction, client: Arc<Mutex<Client>>) {
+    // connecting or scanning takes a while, other commands need the client meanwhile
+    let mut adapter = client.lock().await.adapter().clone();
+    let result = match action {
+        TrayAction::TogglePower => {
+            let adapter_info = adapter.adapter_info().await;
+            // turning bluetooth on from the tray is the user asking for it, soft block or not
+            if adapter_info.soft_blocked && !adapter_info.is_powered {
+                match adapter.unblock().await {
+                    Ok(()) => adapter.set_powered(true).await,
+                    Err(err) => Err(err),
+                }
+            } else {
+                adapter.set_powered(!adapter_info.is_powered).await
+            }
+        }
+        TrayAction::ToggleDiscoverable => {
+            let discoverable = adapter.adapter_info().await.is_discoverable;
+            adapter.set_discoverable(!discoverable).await
+        }
+        TrayAction::Scan => adapter.discover_devices(SCAN_TIMEOUT).await,
+        TrayAction::Connect(address) => adapter.connect_device(address).await,
+        TrayAction::Disconnect(address) => adapter.disconnect_device(address).await,
+        TrayAction::Show | TrayAction::Quit => Ok(()),
+    };
+    // the menu follows the adapter events, so there is nothing to roll back on failure
+    if let Err(err) = result {
+        warn!("tray action failed: {}", err);
+    }
+}
+
+pub fn handle_event(handle: &AppHandle, event: SystemTrayEvent) {
+    match event {
+        SystemTrayEvent::LeftClick { .. } => show_main_window(handle),
+        SystemTrayEvent::MenuItemClick { id, .. } => match TrayAction::from_id(&id) {
+            Some(TrayAction::Show) => show_main_window(handle),
+            Some(TrayAction::Quit) => handle.exit(0),
+            Some(action) => {
+                if let Some(state) = handle.try_state::<SharedState>() {
+                    tauri::async_runtime::spawn(run_action(action, Arc::clone(&state.0)));
+                }
+            }
+            None => (),
+        },
+        _ => (),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bluer::Address;
+    use bluetooth::device::DeviceInfo;
+
+    use super::{device_title, TrayAction};
+
+    #[test]
+    fn action_ids_round_trip() {
+        let address = Address::new([0x38, 0x18, 0x4c, 0x10, 0x20, 0x30]);
+        for action in [
+            TrayAction::TogglePower,
+            TrayAction::ToggleDiscoverable,
+            TrayAction::Scan,
+            TrayAction::Connect(address),
+            TrayAction::Disconnect(address),
+            TrayAction::Show,
+            TrayAction::Quit,
+        ] {
+            assert_eq!(TrayAction::from_id(&action.id()), Some(action));
+        }
+        assert_eq!(TrayAction::from_id("connect:not an address"), None);
+        assert_eq!(TrayAction::from_id("no_devices"), None);
+    }
+
+    #[test]
+    fn device_title_prefers_alias_and_shows_battery() {
+        let device = DeviceInfo {
+            name: String::from("WH-1000XM4"),
+            alias: String::from("Headphones"),
+            battery_percentage: 80,
+            ..DeviceInfo::default()
+        };
+        assert_eq!(device_title(&device), "Headphones (80%)");
+        let device = DeviceInfo {
+            alias: String::new(),
+            battery_percentage: 0,
+            ..device
+        };
+        assert_eq!(device_title(&device), "WH-1000XM4");
+    }
+}
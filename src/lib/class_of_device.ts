@@ -0,0 +1,31 @@
+import type { ClassOfDevice, MajorClass } from './models';
+
+function words(name: string | { reserved: number }): string {
+	return typeof name === 'string' ? name.replaceAll('_', ' ') : 'reserved';
+}
+
+export function majorClassName(major: MajorClass): string {
+	return words(major);
+}
+
+// same wording as the Display impl of ClassOfDevice in the bluetooth crate
+export function minorClassName(classOfDevice: ClassOfDevice): string {
+	const minor = classOfDevice.minor;
+	if ('peripheral' in minor) {
+		const { input, kind } = minor.peripheral;
+		return input === 'none' ? words(kind) : `${words(input)} ${words(kind)}`;
+	}
+	if ('imaging' in minor) {
+		return Object.entries(minor.imaging)
+			.filter(([, set]) => set)
+			.map(([name]) => name)
+			.join(', ');
+	}
+	if ('network_access_point' in minor) {
+		return words(minor.network_access_point.load);
+	}
+	if ('unassigned' in minor) {
+		return '';
+	}
+	return words(Object.values(minor)[0] as string | { reserved: number });
+}
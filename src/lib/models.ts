@@ -4,6 +4,93 @@ export type TargetButtonEvent = Event & { currentTarget: EventTarget & HTMLButto
 
 export type TargetAnchorEvent = Event & { currentTarget: EventTarget & HTMLAnchorElement };
 
+export type PowerState = 'off' | 'turning_on' | 'on' | 'turning_off' | 'blocked';
+
+export type MajorClass =
+	| 'miscellaneous'
+	| 'computer'
+	| 'phone'
+	| 'network_access_point'
+	| 'audio_video'
+	| 'peripheral'
+	| 'imaging'
+	| 'wearable'
+	| 'toy'
+	| 'health'
+	| 'uncategorized'
+	| { reserved: number };
+
+// minor classes of a single major class are snake_case names, or { reserved: bits }
+type MinorName = string | { reserved: number };
+
+export type MinorClass =
+	| { computer: MinorName }
+	| { phone: MinorName }
+	| { network_access_point: { load: string; sub_class: number } }
+	| { audio_video: MinorName }
+	| { peripheral: { input: string; kind: MinorName } }
+	| { imaging: { display: boolean; camera: boolean; scanner: boolean; printer: boolean } }
+	| { wearable: MinorName }
+	| { toy: MinorName }
+	| { health: MinorName }
+	| { unassigned: number };
+
+export type ServiceClass =
+	| 'limited_discoverable_mode'
+	| 'le_audio'
+	| 'positioning'
+	| 'networking'
+	| 'rendering'
+	| 'capturing'
+	| 'object_transfer'
+	| 'audio'
+	| 'telephony'
+	| 'information';
+
+export interface ClassOfDevice {
+	major: MajorClass;
+	minor: MinorClass;
+	services: ServiceClass[];
+}
+
+export type UuidKind =
+	| 'protocol'
+	| 'service_class'
+	| 'gatt_service'
+	| 'declaration'
+	| 'descriptor'
+	| 'characteristic'
+	| 'member'
+	| 'sdo'
+	| 'vendor'
+	| 'unknown';
+
+export interface UuidInfo {
+	kind: UuidKind;
+	name: string | null;
+	company: string | null;
+}
+
+export type Profile =
+	| 'a2dp_sink'
+	| 'a2dp_source'
+	| 'hfp_hands_free'
+	| 'hfp_audio_gateway'
+	| 'hsp_headset'
+	| 'hsp_audio_gateway'
+	| 'hid'
+	| 'hogp'
+	| 'pan_user'
+	| 'pan_network_access_point'
+	| 'pan_group_network'
+	| 'object_push'
+	| 'file_transfer'
+	| 'pbap_server'
+	| 'pbap_client'
+	| 'map_server'
+	| 'map_client'
+	| 'serial_port';
+
 export interface AdapterInfo {
 	discovering: boolean;
 	address: string;
@@ -13,13 +100,14 @@ export interface AdapterInfo {
 	name: string;
 	is_discoverable: boolean;
 	is_powered: boolean;
+	power_state: PowerState;
+	soft_blocked: boolean;
+	hard_blocked: boolean;
 	system_name: string;
 	pairable_timeout: number;
 	discoverable_timeout: number;
 	class: number;
-	device_major_name: string;
-	device_minor_name: string;
-	service_categories: string[];
+	class_of_device: ClassOfDevice | null;
 	icon: string;
 	active_advertising_instances: number;
 	supported_advertising_instances: number;
@@ -29,7 +117,20 @@ export interface AdapterInfo {
 	max_scan_response_length: number;
 	min_tx_power: number;
 	max_tx_power: number;
-	uuids: Map<string, string>;
+	uuids: Map<string, UuidInfo>;
+}
+
+export type PanRole = 'nap' | 'gn' | 'panu';
+
+export interface NetworkServer {
+	role: PanRole;
+	bridge: string;
+}
+
+export interface NetworkInfo {
+	connected: boolean;
+	interface: string | null;
+	role: PanRole | null;
 }
 
 export interface DeviceInfo {
@@ -39,10 +140,9 @@ export interface DeviceInfo {
 	address_string: string;
 	address: number[];
 	class: number;
-	device_major_name: string;
-	device_minor_name: string;
-	service_categories: string[];
-	uuids: Map<string, string>;
+	class_of_device: ClassOfDevice | null;
+	uuids: Map<string, UuidInfo>;
+	profiles: Profile[];
 	is_paired: boolean;
 	is_connected: boolean;
 	is_trusted: boolean;
@@ -50,4 +150,232 @@ export interface DeviceInfo {
 	is_wake_allowed: boolean;
 	is_legacy_pairing: boolean;
 	battery_percentage: number;
+	rssi: number | null;
+	/** seconds since the Unix epoch */
+	last_seen: number | null;
+	tags: string[];
+	/** payload bytes keyed by company identifier */
+	manufacturer_data: { [company: number]: number[] };
+	media_player: MediaPlayerInfo | null;
+	transport: TransportInfo | null;
+	network: NetworkInfo | null;
+}
+
+export type PlaybackStatus =
+	| 'playing'
+	| 'paused'
+	| 'stopped'
+	| 'forward_seek'
+	| 'reverse_seek'
+	| 'error';
+
+export interface TrackInfo {
+	title: string;
+	artist: string;
+	album: string;
+	genre: string;
+	track_number: number;
+	number_of_tracks: number;
+	duration: number;
+}
+
+export interface MediaPlayerInfo {
+	name: string;
+	status: PlaybackStatus;
+	position: number;
+	track: TrackInfo;
+	repeat: string;
+	shuffle: string;
+}
+
+export type MediaCommand =
+	| 'play'
+	| 'pause'
+	| 'stop'
+	| 'next'
+	| 'previous'
+	| 'seek_forward'
+	| 'seek_backward';
+
+export type TransportState = 'idle' | 'pending' | 'active';
+
+export type Codec =
+	| 'sbc'
+	| 'mpeg12'
+	| 'aac'
+	| 'atrac'
+	| 'apt_x'
+	| 'apt_x_hd'
+	| 'ldac'
+	| { vendor: { vendor_id: number; codec_id: number } }
+	| { unknown: number };
+
+export type ChannelMode = 'mono' | 'dual_channel' | 'stereo' | 'joint_stereo';
+
+export interface CodecInfo {
+	codec: Codec;
+	sample_rate: number | null;
+	channel_mode: ChannelMode | null;
+	block_length: number | null;
+	subbands: number | null;
+	min_bitpool: number | null;
+	max_bitpool: number | null;
+	bitrate: number | null;
+	variable_bitrate: boolean | null;
+}
+
+export interface TransportInfo {
+	uuid: string;
+	state: TransportState;
+	volume: number | null;
+	delay: number | null;
+	codec: CodecInfo;
+}
+
+export type LineEnding = 'none' | 'lf' | 'cr' | 'cr_lf';
+
+export interface TerminalOptions {
+	line_ending: LineEnding;
+	hex: boolean;
+	log_path: string | null;
+}
+
+export type ChannelKind = 'serial' | 'serial_server' | 'l2cap' | 'l2cap_server';
+
+export interface ChannelMtu {
+	send: number;
+	receive: number;
+}
+
+export interface SerialServer {
+	uuid: string;
+	name: string;
+	channel: number | null;
+	options: TerminalOptions;
 }
+
+export interface L2capServer {
+	psm: number;
+	options: TerminalOptions;
+}
+
+export interface ChannelInfo {
+	id: number;
+	address: string;
+	kind: ChannelKind;
+	options: TerminalOptions;
+	bytes_sent: number;
+	bytes_received: number;
+	mtu: ChannelMtu | null;
+	send_rate: number;
+	receive_rate: number;
+}
+
+export type TerminalEvent =
+	| { kind: 'opened'; channel: ChannelInfo; device: DeviceInfo }
+	| { kind: 'rejected'; address: string; service: string }
+	| { kind: 'received'; id: number; data: string }
+	| { kind: 'closed'; id: number; reason: string | null }
+	| { kind: 'lagged'; skipped: number };
+
+export interface BluetoothError {
+	code: string;
+	message: string;
+	details: string | null;
+}
+
+export interface DevicePatch {
+	address: string;
+	changes: Partial<DeviceInfo>;
+}
+
+export type DeviceDelta = { seq: number } & (
+	| { kind: 'device_added'; device: DeviceInfo }
+	| { kind: 'device_removed'; device: DeviceInfo }
+	| { kind: 'device_updated'; patch: DevicePatch }
+);
+
+export interface DeviceDeltaBatch {
+	first_seq: number;
+	last_seq: number;
+	deltas: DeviceDelta[];
+}
+
+export type DiscoveryStopReason = 'timeout' | 'cancelled' | 'ended';
+
+export type DiscoveryUpdate =
+	| { discovering: true; timeout: number }
+	| { discovering: false; reason: DiscoveryStopReason };
+
+export interface DeviceSnapshot {
+	seq: number;
+	devices: DeviceInfo[];
+}
+
+export interface DeviceFilter {
+	text?: string | null;
+	address_prefix?: string | null;
+	paired?: boolean | null;
+	connected?: boolean | null;
+	trusted?: boolean | null;
+	major_class?: MajorClass | null;
+	profile?: Profile | null;
+	uuid?: string | null;
+	min_rssi?: number | null;
+	tags?: string[];
+}
+
+export type SortKey = 'name' | 'rssi' | 'last_seen' | 'connected_first';
+
+export interface DeviceQuery {
+	filter?: DeviceFilter;
+	sort?: SortKey[];
+	offset?: number;
+	limit?: number | null;
+}
+
+export interface DevicePage {
+	total: number;
+	devices: DeviceInfo[];
+}
+
+export type CaptureFormat = 'btsnoop' | 'pcap';
+
+export interface CaptureInfo {
+	path: string;
+	format: CaptureFormat;
+	/** seconds since the Unix epoch */
+	started_at: number;
+	records: number;
+}
+
+export type InventoryFormat = 'json' | 'csv';
+
+export interface Inventory {
+	exported_at: number | null;
+	devices: DeviceInfo[];
+}
+
+export interface NotificationSettings {
+	device_connected: boolean;
+	device_disconnected: boolean;
+	device_paired: boolean;
+	pairing_requests: boolean;
+	discovery_finished: boolean;
+	battery_low: boolean;
+	battery_low_threshold: number;
+}
+
+export type PairingRequestKind =
+	| { kind: 'confirmation'; passkey: number }
+	| { kind: 'authorization' }
+	| { kind: 'service'; uuid: string; service: string }
+	| { kind: 'display_passkey'; passkey: number }
+	| { kind: 'display_pin_code'; pin_code: string };
+
+export type PairingRequest = {
+	id: number;
+	address: number[];
+	address_string: string;
+	name: string;
+} & PairingRequestKind;
@@ -0,0 +1,27 @@
+import type { Profile } from './models';
+
+// same wording as the Display impl of Profile in the bluetooth crate
+const PROFILE_NAMES: Record<Profile, string> = {
+	a2dp_sink: 'A2DP sink',
+	a2dp_source: 'A2DP source',
+	hfp_hands_free: 'HFP hands-free',
+	hfp_audio_gateway: 'HFP audio gateway',
+	hsp_headset: 'HSP headset',
+	hsp_audio_gateway: 'HSP audio gateway',
+	hid: 'HID',
+	hogp: 'HID over GATT',
+	pan_user: 'PAN user',
+	pan_network_access_point: 'PAN network access point',
+	pan_group_network: 'PAN group network',
+	object_push: 'Object push',
+	file_transfer: 'File transfer',
+	pbap_server: 'Phonebook access server',
+	pbap_client: 'Phonebook access client',
+	map_server: 'Message access server',
+	map_client: 'Message access client',
+	serial_port: 'Serial port'
+};
+
+export function profileName(profile: Profile): string {
+	return PROFILE_NAMES[profile];
+}
@@ -0,0 +1,10 @@
+import type { UuidInfo } from './models';
+
+// same fallbacks as UuidInfo::label in the bluetooth crate
+export function uuidLabel(info: UuidInfo): string {
+	return info.name ?? info.company ?? 'Unknown';
+}
+
+export function uuidKindName(info: UuidInfo): string {
+	return info.kind === 'sdo' ? 'SDO' : info.kind.replaceAll('_', ' ');
+}